@@ -6,6 +6,19 @@ import learnContent from './data/learn-content.json';
 import helpContent from './data/help-content.json';
 import universalContent from './data/universal-mode.json';
 
+// Entity kinds found in decoded shutdown messages
+const ENTITY_LABELS = {
+  ticket: 'Ticket',
+  url: 'Link',
+  email: 'Contact',
+  asn: 'AS',
+  timestamp: 'Until',
+  time: 'Until (time of day)',
+  duration: 'Duration',
+  peering_db: 'PeeringDB',
+  ix: 'IX',
+};
+
 // Memoized terminal line component
 const TerminalLine = memo(({ line }) => {
   const getColor = () => {
@@ -60,7 +73,11 @@ export default function ChatBGP() {
   const [nickname, setNickname] = useState('');
   const [asNumber, setAsNumber] = useState('');
   const [subcode, setSubcode] = useState(2);
+  const [extendedMessage, setExtendedMessage] = useState(false); // RFC 8654
+  const [compatibility, setCompatibility] = useState('rfc9003'); // or RFC 8203's 128-byte limit
+  const [ellipsis, setEllipsis] = useState(null); // null rejects over-long messages instead of truncating
   const [mode, setMode] = useState('shutdown'); // 'shutdown' or 'universal'
+  const [lastEncoded, setLastEncoded] = useState(null); // hex of the last message encoded, for /pcap and /mrt
   const [input, setInput] = useState('');
   const [history, setHistory] = useState([]);
   const [commandHistory, setCommandHistory] = useState([]);
@@ -115,6 +132,7 @@ export default function ChatBGP() {
         })),
         { type: 'system', text: '' },
         { type: 'system', text: helpContent.usage.encoding },
+        { type: 'system', text: helpContent.usage.capture },
         { type: 'system', text: '───────────────────────────────────────────────────────' }
       ];
 
@@ -152,6 +170,38 @@ export default function ChatBGP() {
     });
   }, [addToHistory]);
 
+  // Encode a shutdown communication, checking its byte budget first
+  const encodeShutdown = useCallback(async (text, messageSubcode) => {
+    // Counted in UTF-8 by the encoder itself, with the character that crosses the limit
+    const limit = compatibility === 'rfc8203' ? 128 : 255;
+    const budget = BgpWasm.shutdown_message_budget(text, limit);
+    if (budget.overflow && ellipsis === null) {
+      const { grapheme, index, offset } = budget.overflow;
+      addToHistory({ type: 'error', text: `Message too long: ${budget.bytes} bytes (max ${limit})` });
+      addToHistory({ type: 'error', text: `"${grapheme}" (character ${index + 1}, byte ${offset}) crosses the limit; /truncate on cuts it there` });
+      return;
+    }
+
+    const status = budget.overflow ? 'truncating' : `${budget.remaining} left`;
+    addToHistory({ type: 'system', text: `* Encoding message (${budget.bytes}/${limit} bytes, ${status})...` });
+
+    const request = {
+      message: text,
+      subcode: messageSubcode,
+      compatibility: compatibility.replace('-', '_'),
+      truncate: ellipsis !== null,
+      ellipsis: ellipsis ?? undefined,
+    };
+    const result = await BgpWasm.encode_shutdown_message(request);
+    setLastEncoded(result.hex);
+
+    const modeText = messageSubcode === 2 ? 'SHUTDOWN' : 'RESET';
+    addToHistory({ type: 'output', text: `┌─ BGP ${modeText} NOTIFICATION ────────────────────────┐` });
+    addToHistory({ type: 'hex', text: result.hex });
+    addToHistory({ type: 'output', text: `└─ ${result.total_bytes} bytes total, ${result.message_bytes} bytes message ─────────┘` });
+    result.warnings.forEach(warning => addToHistory({ type: 'system', text: `* Warning: ${warning}` }));
+  }, [compatibility, ellipsis, addToHistory]);
+
   // Command handlers
   const handleShutdownCommand = useCallback(async (cmd) => {
     const parts = cmd.split(' ');
@@ -194,6 +244,114 @@ export default function ChatBGP() {
         }
         break;
 
+      case '/extended':
+        if (parts[1] === 'on' || parts[1] === 'off') {
+          setExtendedMessage(parts[1] === 'on');
+          const limit = parts[1] === 'on' ? '65535 bytes (RFC 8654 Extended Message)' : '4096 bytes';
+          addToHistory({ type: 'system', text: `* Message size limit: ${limit}` });
+        } else {
+          addToHistory({ type: 'error', text: 'Usage: /extended <on|off>' });
+        }
+        break;
+
+      case '/compat':
+        if (['rfc9003', 'rfc8203-warn', 'rfc8203'].includes(parts[1])) {
+          setCompatibility(parts[1]);
+          addToHistory({ type: 'system', text: `* Shutdown message compatibility: ${parts[1]}` });
+        } else {
+          addToHistory({ type: 'error', text: 'Usage: /compat <rfc9003|rfc8203-warn|rfc8203>' });
+        }
+        break;
+
+      case '/truncate':
+        if (parts[1] === 'on') {
+          const marker = parts.slice(2).join(' ') || '…';
+          setEllipsis(marker);
+          addToHistory({ type: 'system', text: `* Over-long messages will be truncated with "${marker}"` });
+        } else if (parts[1] === 'off') {
+          setEllipsis(null);
+          addToHistory({ type: 'system', text: '* Over-long messages will be rejected' });
+        } else {
+          addToHistory({ type: 'error', text: 'Usage: /truncate <on|off> [ellipsis]' });
+        }
+        break;
+
+      case '/template':
+        const templates = BgpWasm.get_shutdown_templates();
+        const template = templates.find(t => t.name === (parts[1] || '').toLowerCase());
+        if (!template) {
+          addToHistory({ type: 'system', text: 'Templates:' });
+          templates.forEach(t => {
+            addToHistory({ type: 'system', text: `  ${t.name.padEnd(13)} - ${t.description} (subcode ${t.subcode})` });
+            addToHistory({ type: 'system', text: `    ${t.template}` });
+          });
+          addToHistory({ type: 'system', text: 'Usage: /template <name> ticket=<id> until=<time|2h30m> contact=<who>' });
+          break;
+        }
+        // {asn} and {nick} come from /as and /nick, the rest from key=value pairs
+        const values = { asn: asNumber ? parseInt(asNumber) : undefined, nick: nickname || undefined };
+        parts.slice(2).forEach(pair => {
+          const [key, ...value] = pair.split('=');
+          values[key] = value.join('=');
+        });
+        try {
+          const result = BgpWasm.expand_shutdown_template(template.name, values);
+          addToHistory({ type: 'system', text: `* ${template.name}: ${result.message}` });
+          await encodeShutdown(result.message, result.subcode);
+        } catch (error) {
+          addToHistory({ type: 'error', text: `ERROR: ${error.message || error}` });
+        }
+        break;
+
+      case '/pcap':
+        if (!lastEncoded) {
+          addToHistory({ type: 'error', text: 'Nothing to save yet: encode a message first' });
+          break;
+        }
+        try {
+          // Optional client and server, e.g. /pcap 192.0.2.1:49152 192.0.2.2:179
+          const session = {};
+          if (parts[1]) session.client = parts[1];
+          if (parts[2]) session.server = parts[2];
+          const bytes = BgpWasm.encode_capture(lastEncoded, session);
+          const link = document.createElement('a');
+          link.href = URL.createObjectURL(new Blob([bytes], { type: 'application/vnd.tcpdump.pcap' }));
+          link.download = 'chatbgp.pcap';
+          link.click();
+          URL.revokeObjectURL(link.href);
+          addToHistory({ type: 'system', text: `* Saved chatbgp.pcap (${bytes.length} bytes), open it in Wireshark` });
+        } catch (error) {
+          addToHistory({ type: 'error', text: `ERROR: ${error.message || error}` });
+        }
+        break;
+
+      case '/mrt':
+        if (!lastEncoded) {
+          addToHistory({ type: 'error', text: 'Nothing to save yet: encode a message first' });
+          break;
+        }
+        if (!asNumber) {
+          addToHistory({ type: 'error', text: 'Set the local AS first with /as <number>' });
+          break;
+        }
+        try {
+          // Optional peer AS and addresses, e.g. /mrt 64496 192.0.2.1 192.0.2.2
+          const session = { local_as: parseInt(asNumber) };
+          if (parts[1]) session.peer_as = parseInt(parts[1].replace(/^AS/i, ''));
+          if (parts[2]) session.peer_ip = parts[2];
+          if (parts[3]) session.local_ip = parts[3];
+          const bytes = BgpWasm.encode_mrt(lastEncoded, session);
+          const link = document.createElement('a');
+          link.href = URL.createObjectURL(new Blob([bytes], { type: 'application/octet-stream' }));
+          link.download = 'chatbgp.mrt';
+          link.click();
+          URL.revokeObjectURL(link.href);
+          addToHistory({ type: 'system', text: `* Saved chatbgp.mrt (${bytes.length} bytes) as received from the peer by AS${asNumber}` });
+        } catch (error) {
+          addToHistory({ type: 'error', text: `ERROR: ${error.message || error}` });
+        }
+        break;
+
       case '/universal':
         setMode('universal');
         addToHistory({ type: 'system', text: '* Switched to universal BGP notification mode' });
@@ -221,6 +379,9 @@ export default function ChatBGP() {
           { type: 'system', text: `  Nick: ${nickname || '(none - anonymous)'} ` },
           { type: 'system', text: `  AS: ${asNumber || '(none)'} ` },
           { type: 'system', text: `  Mode: ${subcode === 2 ? 'Shutdown (2)' : 'Reset (4)'} ` },
+          { type: 'system', text: `  Extended Message: ${extendedMessage ? 'on (65535 bytes)' : 'off (4096 bytes)'} ` },
+          { type: 'system', text: `  Compatibility: ${compatibility}` },
+          { type: 'system', text: `  Truncate: ${ellipsis === null ? 'off' : `on ("${ellipsis}")`}` },
           { type: 'system', text: `  Display: <${getNick()}>` },
           { type: 'system', text: '───────────────────────────────────────────────────────' }
         ];
@@ -230,7 +391,7 @@ export default function ChatBGP() {
       default:
         addToHistory({ type: 'error', text: `Unknown command: ${command}` });
     }
-  }, [nickname, asNumber, subcode, getNick, addToHistory, showLearnContent, showUniversalHelp]);
+  }, [nickname, asNumber, subcode, extendedMessage, compatibility, ellipsis, lastEncoded, encodeShutdown, getNick, addToHistory, showLearnContent, showUniversalHelp]);
 
   const handleUniversalCommand = useCallback(async (cmd) => {
     const parts = cmd.split(' ');
@@ -253,11 +414,14 @@ export default function ChatBGP() {
             errorCode, 
             subcodeValue, 
             dataType, 
-            dataValue
+            dataValue,
+            extendedMessage
           );
+          setLastEncoded(result.hex);
 
-          const errorName = universalContent.error_codes[errorCode]?.name || 'Unknown';
-          const subcodeName = universalContent.error_codes[errorCode]?.subcodes[subcodeValue] || 'Unknown';
+          const entry = BgpWasm.get_registry().find(e => e.code === errorCode);
+          const errorName = entry?.name || 'Unknown';
+          const subcodeName = entry?.subcodes.find(s => s.subcode === subcodeValue)?.name || 'Unknown';
 
           addToHistory({ type: 'output', text: `┌─ BGP NOTIFICATION ─────────────────────────────────┐` });
           addToHistory({ type: 'output', text: `│ Error: ${errorCode} (${errorName})` });
@@ -286,7 +450,7 @@ export default function ChatBGP() {
       default:
         await handleShutdownCommand(cmd);
     }
-  }, [addToHistory, showUniversalHelp, handleShutdownCommand]);
+  }, [extendedMessage, addToHistory, showUniversalHelp, handleShutdownCommand]);
 
   // Main input processing
   const processInput = useCallback(async (text) => {
@@ -315,7 +479,7 @@ export default function ChatBGP() {
         
         try {
           // Try universal decoder first
-          const result = await BgpWasm.decode_universal_notification(cleanedText);
+          const result = await BgpWasm.decode_universal_notification(cleanedText, extendedMessage);
           
           addToHistory({ type: 'output', text: '┌─ DECODED BGP NOTIFICATION ─────────────────────────┐' });
           addToHistory({ type: 'output', text: `│ Error: ${result.error_code} (${result.error_name})` });
@@ -324,14 +488,107 @@ export default function ChatBGP() {
           if (result.interpretation) {
             addToHistory({ type: 'output', text: `│ Info: ${result.interpretation}` });
           }
+          result.notes.forEach(note => addToHistory({ type: 'output', text: `│ Note: ${note}` }));
+          if (result.encapsulated) {
+            const inner = result.encapsulated;
+            addToHistory({ type: 'output', text: `│ Encapsulated: ${inner.error_code}/${inner.subcode} (${inner.error_name} / ${inner.subcode_name})` });
+          }
           addToHistory({ type: 'output', text: '└────────────────────────────────────────────────────┘' });
         } catch (universalError) {
+          // Declared length shorter than the input: several messages back to back
+          if (universalError.code === 'length_mismatch' && universalError.actual < universalError.expected) {
+            try {
+              const result = await BgpWasm.decode_message_stream(cleanedText, extendedMessage);
+              addToHistory({ type: 'output', text: '┌─ DECODED BGP MESSAGE STREAM ───────────────────────┐' });
+              result.messages.forEach((message, i) => {
+                addToHistory({ type: 'output', text: `│ [${i}] offset ${message.offset}, ${message.length} bytes: ${message.type_name}` });
+                message.summary.forEach(line => addToHistory({ type: 'output', text: `│     ${line}` }));
+                if (message.error) {
+                  addToHistory({ type: 'output', text: `│     Error: ${message.error}` });
+                }
+              });
+              if (result.partial) {
+                const expected = result.partial.expected ? ` of ${result.partial.expected}` : '';
+                addToHistory({ type: 'output', text: `│ Partial message at offset ${result.partial.offset}: ${result.partial.available}${expected} bytes` });
+              }
+              if (result.framing_error) {
+                addToHistory({ type: 'output', text: `│ Framing stopped: ${result.framing_error}` });
+              }
+              addToHistory({ type: 'output', text: '└────────────────────────────────────────────────────┘' });
+            } catch (streamError) {
+              addToHistory({ type: 'error', text: `Decoding error: ${streamError.message || streamError}` });
+            }
+            return;
+          }
+
+          if (universalError.code === 'unexpected_message_type' && universalError.actual === 1) {
+            try {
+              const result = await BgpWasm.decode_open_message(cleanedText);
+              addToHistory({ type: 'output', text: '┌─ DECODED BGP OPEN ─────────────────────────────────┐' });
+              result.summary.forEach(line => addToHistory({ type: 'output', text: `│ ${line}` }));
+              addToHistory({ type: 'output', text: '└────────────────────────────────────────────────────┘' });
+            } catch (openError) {
+              addToHistory({ type: 'error', text: `Decoding error: ${openError.message || openError}` });
+            }
+            return;
+          }
+
+          if (universalError.code === 'unexpected_message_type' && universalError.actual === 2) {
+            try {
+              const result = await BgpWasm.decode_update_message(cleanedText, extendedMessage);
+              addToHistory({ type: 'output', text: '┌─ DECODED BGP UPDATE ───────────────────────────────┐' });
+              addToHistory({ type: 'output', text: `│ Withdrawn routes: ${result.withdrawn_routes_length} bytes` });
+              result.withdrawn_routes.forEach(prefix => addToHistory({ type: 'output', text: `│   ${prefix}` }));
+              addToHistory({ type: 'output', text: `│ Path attributes: ${result.total_path_attribute_length} bytes` });
+              result.path_attributes.forEach(attr => {
+                addToHistory({ type: 'output', text: `│   ${attr.type_name} (${attr.type_code}) [${attr.flags_description}]` });
+                addToHistory({ type: 'output', text: `│     ${attr.interpretation}` });
+              });
+              addToHistory({ type: 'output', text: `│ NLRI: ${result.nlri.length} prefixes` });
+              result.nlri.forEach(prefix => addToHistory({ type: 'output', text: `│   ${prefix}` }));
+              result.notes.forEach(note => addToHistory({ type: 'output', text: `│ Note: ${note}` }));
+              addToHistory({ type: 'output', text: '└────────────────────────────────────────────────────┘' });
+            } catch (updateError) {
+              addToHistory({ type: 'error', text: `Decoding error: ${updateError.message || updateError}` });
+            }
+            return;
+          }
+
+          if (universalError.code === 'unexpected_message_type' && (universalError.actual === 4 || universalError.actual === 5)) {
+            try {
+              const summary = universalError.actual === 4
+                ? await BgpWasm.decode_keepalive_message(cleanedText)
+                : (await BgpWasm.decode_route_refresh_message(cleanedText, extendedMessage)).summary;
+              const title = universalError.actual === 4
+                ? '┌─ DECODED BGP KEEPALIVE ────────────────────────────┐'
+                : '┌─ DECODED BGP ROUTE-REFRESH ────────────────────────┐';
+              addToHistory({ type: 'output', text: title });
+              summary.forEach(line => addToHistory({ type: 'output', text: `│ ${line}` }));
+              addToHistory({ type: 'output', text: '└────────────────────────────────────────────────────┘' });
+            } catch (refreshError) {
+              addToHistory({ type: 'error', text: `Decoding error: ${refreshError.message || refreshError}` });
+            }
+            return;
+          }
+
           // Fall back to shutdown decoder
           try {
             const result = await BgpWasm.decode_shutdown_message(cleanedText);
             addToHistory({ type: 'output', text: '┌─ DECODED BGP SHUTDOWN NOTIFICATION ────────────────┐' });
             addToHistory({ type: 'output', text: `│ Type: ${result.subcode} (${result.subcode_value})` });
-            addToHistory({ type: 'output', text: `│ Message: "${result.message}"` });
+            // Never render the raw text: it may carry bidi overrides or terminal escapes
+            addToHistory({ type: 'output', text: `│ Message: "${result.sanitized}"` });
+            if (result.diagnostics.length > 0) {
+              const declared = result.declared_length ?? 'missing';
+              addToHistory({ type: 'output', text: `│ Length: declared ${declared}, actual ${result.actual_length}` });
+              result.diagnostics.forEach(d => addToHistory({ type: 'error', text: `│ Malformed: ${d.message}${d.rfc ? ` (${d.rfc})` : ''}` }));
+            }
+            result.entities.forEach(entity => {
+              const label = ENTITY_LABELS[entity.kind] || entity.kind;
+              const restore = entity.seconds ? ` (back at ${new Date(Date.now() + entity.seconds * 1000).toISOString()})` : '';
+              addToHistory({ type: 'output', text: `│ ${label}: ${entity.value}${restore}` });
+            });
+            result.notes.forEach(note => addToHistory({ type: 'output', text: `│ Note: ${note}` }));
             addToHistory({ type: 'output', text: '└────────────────────────────────────────────────────┘' });
           } catch (shutdownError) {
             addToHistory({ type: 'error', text: `Decoding error: ${shutdownError.message || shutdownError}` });
@@ -344,26 +601,90 @@ export default function ChatBGP() {
           return;
         }
 
-        const bytes = new TextEncoder().encode(text).length;
-        if (bytes > 255) {
-          addToHistory({ type: 'error', text: `Message too long: ${bytes} bytes (max 255)` });
-          return;
-        }
+        await encodeShutdown(text, subcode);
+      }
+    } catch (error) {
+      addToHistory({ type: 'error', text: `ERROR: ${error.message || error}` });
+    }
+  }, [getNick, subcode, mode, extendedMessage, encodeShutdown, addToHistory, handleUniversalCommand, handleShutdownCommand]);
+
+  // A pcap, pcapng, BMP or MRT file dropped on the terminal: list the BGP messages in it
+  const handleFileDrop = useCallback(async (e) => {
+    e.preventDefault();
+    const file = e.dataTransfer.files[0];
+    if (!file) return;
 
-        addToHistory({ type: 'system', text: `* Encoding message (${bytes}/255 bytes)...` });
+    addToHistory({ type: 'system', text: `* Reading ${file.name} (${file.size} bytes)...` });
+    const bytes = new Uint8Array(await file.arrayBuffer());
+    try {
+      const result = await BgpWasm.decode_capture(bytes, extendedMessage);
+      addToHistory({ type: 'output', text: '┌─ BGP SESSIONS IN CAPTURE ──────────────────────────┐' });
+      addToHistory({ type: 'output', text: `│ ${result.format}, ${result.packets} packets, ${result.flows.length} flows` });
+      result.flows.forEach(flow => {
+        const joined = flow.handshake ? '' : ', joined mid-session';
+        addToHistory({ type: 'output', text: `│ Flow ${flow.id}: ${flow.client} -> ${flow.server} (${flow.messages} messages${joined})` });
+      });
+      result.messages.forEach(captured => {
+        const { message } = captured;
+        const detail = message.error ? `Error: ${message.error}` : (message.summary[0] || '');
+        addToHistory({ type: 'output', text: `│ ${captured.time} #${captured.flow} ${captured.source} -> ${captured.destination} ${message.type_name} ${detail}` });
+      });
+      result.warnings.forEach(warning => addToHistory({ type: 'system', text: `* Warning: ${warning}` }));
+      addToHistory({ type: 'output', text: '└────────────────────────────────────────────────────┘' });
+    } catch (error) {
+      if (error.code !== 'unknown_capture_format') {
+        addToHistory({ type: 'error', text: `Capture error: ${error.message || error}` });
+        return;
+      }
 
-        const request = { message: text, subcode };
-        const result = await BgpWasm.encode_shutdown_message(request);
+      // Not a capture: a recorded BMP feed starts with version 3
+      if (bytes[0] === 3) {
+        try {
+          const result = await BgpWasm.decode_bmp(bytes, extendedMessage);
+          addToHistory({ type: 'output', text: '┌─ BMP MESSAGES ─────────────────────────────────────┐' });
+          addToHistory({ type: 'output', text: `│ ${result.messages.length} messages` });
+          result.messages.forEach(message => {
+            const peer = message.peer ? ` ${message.peer.address} AS${message.peer.peer_as}` : '';
+            addToHistory({ type: 'output', text: `│ ${message.peer ? message.peer.time + ' ' : ''}${message.type_name}${peer}` });
+            message.summary.forEach(line => addToHistory({ type: 'output', text: `│     ${line}` }));
+            message.messages.forEach(bgp => {
+              const detail = bgp.error ? `Error: ${bgp.error}` : (bgp.summary[0] || '');
+              addToHistory({ type: 'output', text: `│     ${bgp.type_name} ${detail}` });
+            });
+            const down = message.peer_down;
+            if (down) {
+              addToHistory({ type: 'output', text: `│     ${down.reason_name}` });
+              if (down.notification) {
+                addToHistory({ type: 'output', text: `│     ${down.notification.error_name} / ${down.notification.subcode_name}: ${down.notification.interpretation}` });
+              }
+              if (down.error) addToHistory({ type: 'output', text: `│     Error: ${down.error}` });
+            }
+          });
+          result.warnings.forEach(warning => addToHistory({ type: 'system', text: `* Warning: ${warning}` }));
+          addToHistory({ type: 'output', text: '└────────────────────────────────────────────────────┘' });
+          return;
+        } catch {
+          // An MRT dump from before 2005 starts with 3 as well
+        }
+      }
 
-        const modeText = subcode === 2 ? 'SHUTDOWN' : 'RESET';
-        addToHistory({ type: 'output', text: `┌─ BGP ${modeText} NOTIFICATION ────────────────────────┐` });
-        addToHistory({ type: 'hex', text: result.hex });
-        addToHistory({ type: 'output', text: `└─ ${result.total_bytes} bytes total, ${result.message_bytes} bytes message ─────────┘` });
+      // Otherwise try it as an (uncompressed) MRT dump from a route collector
+      try {
+        const result = await BgpWasm.decode_mrt(bytes, extendedMessage);
+        addToHistory({ type: 'output', text: '┌─ CEASE NOTIFICATIONS IN MRT DUMP ──────────────────┐' });
+        addToHistory({ type: 'output', text: `│ ${result.records} records, ${result.messages} BGP messages, ${result.ceases.length} Cease` });
+        result.ceases.forEach(cease => {
+          addToHistory({ type: 'output', text: `│ ${cease.time} AS${cease.sender_as} ${cease.sender_ip} -> AS${cease.receiver_as} ${cease.receiver_ip}` });
+          const text = cease.shutdown ? `: "${cease.shutdown.sanitized}"` : '';
+          addToHistory({ type: 'output', text: `│     ${cease.subcode_name}${text}` });
+        });
+        result.warnings.forEach(warning => addToHistory({ type: 'system', text: `* Warning: ${warning}` }));
+        addToHistory({ type: 'output', text: '└────────────────────────────────────────────────────┘' });
+      } catch (mrtError) {
+        addToHistory({ type: 'error', text: `Not a pcap, pcapng, BMP or MRT file: ${mrtError.message || mrtError}` });
       }
-    } catch (error) {
-      addToHistory({ type: 'error', text: `ERROR: ${error.message || error}` });
     }
-  }, [getNick, subcode, mode, addToHistory, handleUniversalCommand, handleShutdownCommand]);
+  }, [extendedMessage, addToHistory]);
 
   // Keyboard handling
   const handleKeyDown = useCallback((e) => {
@@ -404,7 +725,12 @@ export default function ChatBGP() {
   }
 
   return (
-    <div className="bg-black text-green-400 h-screen flex flex-col font-mono text-sm" onClick={focusInput}>
+    <div
+      className="bg-black text-green-400 h-screen flex flex-col font-mono text-sm"
+      onClick={focusInput}
+      onDragOver={(e) => e.preventDefault()}
+      onDrop={handleFileDrop}
+    >
       <TerminalHeader nickname={nickname} asNumber={asNumber} subcode={subcode} mode={mode} />
 
       <div
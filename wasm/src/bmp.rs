@@ -0,0 +1,710 @@
+// BGP Monitoring Protocol (RFC 7854), version 3, as a router streams it to a
+// monitoring station. Every message starts with its own length, so a body
+// that does not decode costs only that message; the per-peer header's V flag
+// says whether the 16-octet address fields hold IPv4 or IPv6. The BGP PDUs a
+// message wraps keep their offsets in the feed, except the NOTIFICATION of a
+// Peer Down (reasons 1 and 3), whose error offsets count from the start of
+// that PDU just as when it is pasted on its own. Route Mirroring is kept raw.
+
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+
+use crate::reader::Reader;
+use crate::{
+    family_name, BmpMessageResponse, BmpPeerResponse, BmpResponse, CaptureTime, DecodeError,
+    FramedMessage, Message, Notification, PeerDownResponse, SessionContext, StreamMessageResponse,
+    UniversalDecodeResponse, BGP_HEADER_LEN,
+};
+
+/// The only BMP version (RFC 7854 4.1).
+pub const BMP_VERSION: u8 = 3;
+/// Length of the common header.
+pub const BMP_COMMON_HEADER_LEN: usize = 6;
+/// Length of the per-peer header.
+pub const BMP_PER_PEER_HEADER_LEN: usize = 42;
+
+/// BMP message types (RFC 7854 4.1).
+pub const BMP_ROUTE_MONITORING: u8 = 0;
+pub const BMP_STATISTICS_REPORT: u8 = 1;
+pub const BMP_PEER_DOWN: u8 = 2;
+pub const BMP_PEER_UP: u8 = 3;
+pub const BMP_INITIATION: u8 = 4;
+pub const BMP_TERMINATION: u8 = 5;
+pub const BMP_ROUTE_MIRRORING: u8 = 6;
+
+/// Peer Down reasons (RFC 7854 4.9).
+pub const BMP_PEER_DOWN_LOCAL_NOTIFICATION: u8 = 1;
+pub const BMP_PEER_DOWN_LOCAL_NO_NOTIFICATION: u8 = 2;
+pub const BMP_PEER_DOWN_REMOTE_NOTIFICATION: u8 = 3;
+pub const BMP_PEER_DOWN_REMOTE_NO_NOTIFICATION: u8 = 4;
+pub const BMP_PEER_DOWN_DECONFIGURED: u8 = 5;
+
+const PEER_FLAG_IPV6: u8 = 0x80;
+const PEER_FLAG_POST_POLICY: u8 = 0x40;
+
+const RFC_BMP_HEADER: &str = "RFC 7854 4.1";
+const RFC_PER_PEER_HEADER: &str = "RFC 7854 4.2";
+const RFC_INITIATION: &str = "RFC 7854 4.3";
+const RFC_TERMINATION: &str = "RFC 7854 4.5";
+const RFC_ROUTE_MONITORING: &str = "RFC 7854 4.6";
+const RFC_STATISTICS_REPORT: &str = "RFC 7854 4.8";
+const RFC_PEER_DOWN: &str = "RFC 7854 4.9";
+const RFC_PEER_UP: &str = "RFC 7854 4.10";
+
+/// The session a BMP message is about.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PeerHeader {
+    pub peer_type: u8,
+    pub flags: u8,
+    pub distinguisher: u64,
+    pub address: IpAddr,
+    pub peer_as: u32,
+    pub bgp_id: Ipv4Addr,
+    /// When the router saw the event, zero if it does not say.
+    pub timestamp: CaptureTime,
+}
+
+impl PeerHeader {
+    /// The routes are as left by the inbound policy (the L flag).
+    pub fn is_post_policy(&self) -> bool {
+        self.flags & PEER_FLAG_POST_POLICY != 0
+    }
+
+    fn parse(r: &mut Reader<'_>) -> Result<Self, DecodeError> {
+        let peer_type = r.u8("peer type")?;
+        let flags = r.u8("peer flags")?;
+        let distinguisher = (u64::from(r.u32("peer distinguisher")?) << 32) | u64::from(r.u32("peer distinguisher")?);
+        let address = ip(r.take(16, "peer address")?, flags & PEER_FLAG_IPV6 != 0);
+        let peer_as = r.u32("peer AS")?;
+        let bgp_id = Ipv4Addr::from(r.u32("peer BGP ID")?);
+        let seconds = r.u32("timestamp")?;
+        let micros = r.u32("timestamp")?.min(999_999);
+        Ok(PeerHeader {
+            peer_type,
+            flags,
+            distinguisher,
+            address,
+            peer_as,
+            bgp_id,
+            timestamp: CaptureTime::new(u64::from(seconds), micros * 1_000),
+        })
+    }
+}
+
+// A 16-octet address field; IPv4 addresses sit in the last four octets
+fn ip(octets: &[u8], ipv6: bool) -> IpAddr {
+    if ipv6 {
+        let mut address = [0; 16];
+        address.copy_from_slice(octets);
+        IpAddr::V6(Ipv6Addr::from(address))
+    } else {
+        IpAddr::V4(Ipv4Addr::new(octets[12], octets[13], octets[14], octets[15]))
+    }
+}
+
+/// An Information TLV of an Initiation, Termination or Peer Up message.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BmpTlv {
+    pub tlv_type: u16,
+    pub value: Vec<u8>,
+}
+
+impl BmpTlv {
+    /// The value as text; the string TLVs are UTF-8 by RFC 7854 4.4.
+    pub fn text(&self) -> String {
+        String::from_utf8_lossy(&self.value).into_owned()
+    }
+}
+
+/// One counter or gauge of a Statistics Report.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BmpStat {
+    pub stat_type: u16,
+    /// AFI and SAFI of the per-family gauges.
+    pub family: Option<(u16, u8)>,
+    /// `None` when the value is not 4 or 8 octets long.
+    pub value: Option<u64>,
+}
+
+/// Peer Up Notification (RFC 7854 4.10).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PeerUp {
+    pub local_address: IpAddr,
+    pub local_port: u16,
+    pub remote_port: u16,
+    pub sent_open: FramedMessage,
+    pub received_open: FramedMessage,
+    pub information: Vec<BmpTlv>,
+}
+
+/// Peer Down Notification (RFC 7854 4.9).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PeerDown {
+    pub reason: u8,
+    /// The NOTIFICATION of reasons 1 and 3. Offsets in its error count from
+    /// the start of the PDU.
+    pub notification: Option<Result<Notification, DecodeError>>,
+    /// The FSM event of reason 2.
+    pub fsm_event: Option<u16>,
+    /// Anything else after the reason, e.g. TLVs of a de-configured peer.
+    pub data: Vec<u8>,
+}
+
+impl PeerDown {
+    /// The NOTIFICATION the session ended with, if there was one and it decodes.
+    pub fn notification(&self) -> Option<&Notification> {
+        self.notification.as_ref()?.as_ref().ok()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BmpBody {
+    RouteMonitoring(FramedMessage),
+    StatisticsReport(Vec<BmpStat>),
+    PeerDown(PeerDown),
+    PeerUp(PeerUp),
+    Initiation(Vec<BmpTlv>),
+    Termination(Vec<BmpTlv>),
+    /// Route Mirroring, or a type this crate does not decode.
+    Other(Vec<u8>),
+}
+
+/// One BMP message.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BmpMessage {
+    /// Offset of the common header in the stream.
+    pub offset: usize,
+    pub length: usize,
+    pub message_type: u8,
+    /// Absent for Initiation, Termination and unknown types.
+    pub peer: Option<PeerHeader>,
+    pub body: BmpBody,
+}
+
+impl BmpMessage {
+    /// Parse exactly one message.
+    pub fn parse(bytes: &[u8]) -> Result<Self, DecodeError> {
+        Self::parse_with(bytes, &SessionContext::default())
+    }
+
+    /// Parse with the BGP message size limits of `context`.
+    pub fn parse_with(bytes: &[u8], context: &SessionContext) -> Result<Self, DecodeError> {
+        let (length, message_type) = common_header(bytes, 0)?;
+        let mut r = Reader::new(bytes, 0, RFC_BMP_HEADER);
+        r.take(length, "BMP message")?;
+        r.finish("BMP message")?;
+        Self::parse_body(&bytes[..length], 0, message_type, context)
+    }
+
+    fn parse_body(
+        bytes: &[u8],
+        offset: usize,
+        message_type: u8,
+        context: &SessionContext,
+    ) -> Result<Self, DecodeError> {
+        let body = &bytes[BMP_COMMON_HEADER_LEN..];
+        let base = offset + BMP_COMMON_HEADER_LEN;
+        let peer = match message_type {
+            BMP_ROUTE_MONITORING..=BMP_PEER_UP | BMP_ROUTE_MIRRORING => {
+                let mut r = Reader::new(body, base, RFC_PER_PEER_HEADER);
+                Some(PeerHeader::parse(&mut r)?)
+            },
+            _ => None,
+        };
+        let skip = if peer.is_some() { BMP_PER_PEER_HEADER_LEN } else { 0 };
+        let rfc = match message_type {
+            BMP_ROUTE_MONITORING => RFC_ROUTE_MONITORING,
+            BMP_STATISTICS_REPORT => RFC_STATISTICS_REPORT,
+            BMP_PEER_DOWN => RFC_PEER_DOWN,
+            BMP_PEER_UP => RFC_PEER_UP,
+            BMP_INITIATION => RFC_INITIATION,
+            BMP_TERMINATION => RFC_TERMINATION,
+            _ => RFC_BMP_HEADER,
+        };
+        let mut r = Reader::new(&body[skip..], base + skip, rfc);
+
+        let body = match message_type {
+            BMP_ROUTE_MONITORING => {
+                let update = bgp_pdu(&mut r, context, "BGP UPDATE PDU")?;
+                r.finish("Route Monitoring")?;
+                BmpBody::RouteMonitoring(update)
+            },
+            BMP_STATISTICS_REPORT => {
+                let count = r.u32("stats count")?;
+                let stats = (0..count).map(|_| stat(&mut r)).collect::<Result<_, _>>()?;
+                r.finish("Statistics Report")?;
+                BmpBody::StatisticsReport(stats)
+            },
+            BMP_PEER_DOWN => BmpBody::PeerDown(peer_down(&mut r, context)?),
+            BMP_PEER_UP => {
+                let ipv6 = peer.is_some_and(|peer| peer.flags & PEER_FLAG_IPV6 != 0);
+                BmpBody::PeerUp(PeerUp {
+                    local_address: ip(r.take(16, "local address")?, ipv6),
+                    local_port: r.u16("local port")?,
+                    remote_port: r.u16("remote port")?,
+                    sent_open: bgp_pdu(&mut r, context, "sent OPEN")?,
+                    received_open: bgp_pdu(&mut r, context, "received OPEN")?,
+                    information: tlvs(&mut r)?,
+                })
+            },
+            BMP_INITIATION => BmpBody::Initiation(tlvs(&mut r)?),
+            BMP_TERMINATION => BmpBody::Termination(tlvs(&mut r)?),
+            _ => BmpBody::Other(r.rest().to_vec()),
+        };
+        Ok(BmpMessage { offset, length: bytes.len(), message_type, peer, body })
+    }
+}
+
+// Version and length of the message at `offset`; the length covers the
+// common header itself
+fn common_header(bytes: &[u8], offset: usize) -> Result<(usize, u8), DecodeError> {
+    let mut r = Reader::new(bytes, offset, RFC_BMP_HEADER);
+    let version = r.u8("BMP version")?;
+    if version != BMP_VERSION {
+        return Err(DecodeError::UnsupportedBmpVersion { offset, actual: version });
+    }
+    let length = r.u32("BMP message length")? as usize;
+    let message_type = r.u8("BMP message type")?;
+    let min = match message_type {
+        BMP_ROUTE_MONITORING..=BMP_PEER_UP | BMP_ROUTE_MIRRORING => BMP_COMMON_HEADER_LEN + BMP_PER_PEER_HEADER_LEN,
+        _ => BMP_COMMON_HEADER_LEN,
+    };
+    if length < min {
+        return Err(DecodeError::BadBmpLength { offset: offset + 1, declared: length, min });
+    }
+    Ok((length, message_type))
+}
+
+// One BGP message at the reader, framed by its own length field
+fn bgp_pdu(r: &mut Reader<'_>, context: &SessionContext, field: &'static str) -> Result<FramedMessage, DecodeError> {
+    let mut header = r.clone();
+    header.take(16, field)?;
+    let length = usize::from(header.u16(field)?).max(BGP_HEADER_LEN);
+    let offset = r.offset();
+    let bytes = r.take(length, field)?;
+    Ok(FramedMessage {
+        offset,
+        length,
+        message_type: bytes[18],
+        message: Message::parse_with(bytes, context),
+    })
+}
+
+fn peer_down(r: &mut Reader<'_>, context: &SessionContext) -> Result<PeerDown, DecodeError> {
+    let reason = r.u8("reason")?;
+    let (notification, fsm_event) = match reason {
+        BMP_PEER_DOWN_LOCAL_NOTIFICATION | BMP_PEER_DOWN_REMOTE_NOTIFICATION => {
+            (Some(Notification::parse_with(r.rest(), context)), None)
+        },
+        BMP_PEER_DOWN_LOCAL_NO_NOTIFICATION => (None, Some(r.u16("FSM event code")?)),
+        _ => (None, None),
+    };
+    Ok(PeerDown { reason, notification, fsm_event, data: r.rest().to_vec() })
+}
+
+fn stat(r: &mut Reader<'_>) -> Result<BmpStat, DecodeError> {
+    let stat_type = r.u16("stat type")?;
+    let length = usize::from(r.u16("stat length")?);
+    let mut data = r.sub(length, "stat data")?;
+    // The per-AFI/SAFI gauges put the family before the value
+    let family = if length == 11 { Some((data.u16("AFI")?, data.u8("SAFI")?)) } else { None };
+    let value = match data.remaining() {
+        4 => Some(u64::from(data.u32("stat data")?)),
+        8 => Some((u64::from(data.u32("stat data")?) << 32) | u64::from(data.u32("stat data")?)),
+        _ => None,
+    };
+    Ok(BmpStat { stat_type, family, value })
+}
+
+fn tlvs(r: &mut Reader<'_>) -> Result<Vec<BmpTlv>, DecodeError> {
+    let mut tlvs = Vec::new();
+    while !r.is_empty() {
+        let tlv_type = r.u16("information type")?;
+        let length = usize::from(r.u16("information length")?);
+        tlvs.push(BmpTlv { tlv_type, value: r.take(length, "information")?.to_vec() });
+    }
+    Ok(tlvs)
+}
+
+/// Iterates over the messages of a BMP stream as a monitoring station
+/// receives it. A message whose body does not decode is yielded as an error
+/// and skipped; a bad common header is yielded as an error, and ends it.
+pub struct BmpReader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+    context: SessionContext,
+}
+
+impl<'a> BmpReader<'a> {
+    pub fn new(bytes: &'a [u8], context: &SessionContext) -> Self {
+        BmpReader { bytes, pos: 0, context: *context }
+    }
+}
+
+impl Iterator for BmpReader<'_> {
+    type Item = Result<BmpMessage, DecodeError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.pos >= self.bytes.len() {
+            return None;
+        }
+        let rest = &self.bytes[self.pos..];
+        let framed = common_header(rest, self.pos).and_then(|(length, message_type)| {
+            let mut r = Reader::new(rest, self.pos, RFC_BMP_HEADER);
+            r.take(length, "BMP message")?;
+            Ok((length, message_type))
+        });
+        match framed {
+            Ok((length, message_type)) => {
+                let message = BmpMessage::parse_body(&rest[..length], self.pos, message_type, &self.context);
+                self.pos += length;
+                Some(message)
+            },
+            Err(e) => {
+                self.pos = self.bytes.len();
+                Some(Err(e))
+            },
+        }
+    }
+}
+
+/// Every message of a BMP stream, with what could not be decoded.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BmpStream {
+    pub messages: Vec<BmpMessage>,
+    /// Messages that could not be decoded, and where the stream was cut off.
+    pub warnings: Vec<String>,
+}
+
+impl BmpStream {
+    pub fn parse(bytes: &[u8]) -> Result<Self, DecodeError> {
+        Self::parse_with(bytes, &SessionContext::default())
+    }
+
+    /// Parse with the BGP message size limits of `context`. Fails only if
+    /// the first message does not decode.
+    pub fn parse_with(bytes: &[u8], context: &SessionContext) -> Result<Self, DecodeError> {
+        let mut stream = BmpStream { messages: Vec::new(), warnings: Vec::new() };
+        for (i, message) in BmpReader::new(bytes, context).enumerate() {
+            match message {
+                Ok(message) => stream.messages.push(message),
+                // A recording begins at a message boundary, normally an
+                // Initiation; failing there means it is not a BMP feed
+                Err(e) if i == 0 => return Err(e),
+                Err(e) => stream.warnings.push(format!("Message {}: {}", i + 1, e)),
+            }
+        }
+        Ok(stream)
+    }
+
+    /// The Peer Down messages that carry a NOTIFICATION, with the session each ended.
+    pub fn peer_down_notifications(&self) -> impl Iterator<Item = (&BmpMessage, &Notification)> {
+        self.messages.iter().filter_map(|message| match &message.body {
+            BmpBody::PeerDown(down) => Some((message, down.notification()?)),
+            _ => None,
+        })
+    }
+}
+
+fn message_type_name(message_type: u8) -> &'static str {
+    match message_type {
+        BMP_ROUTE_MONITORING => "Route Monitoring",
+        BMP_STATISTICS_REPORT => "Statistics Report",
+        BMP_PEER_DOWN => "Peer Down",
+        BMP_PEER_UP => "Peer Up",
+        BMP_INITIATION => "Initiation",
+        BMP_TERMINATION => "Termination",
+        BMP_ROUTE_MIRRORING => "Route Mirroring",
+        _ => "Unknown",
+    }
+}
+
+fn peer_type_name(peer_type: u8) -> &'static str {
+    match peer_type {
+        0 => "Global Instance Peer",
+        1 => "RD Instance Peer",
+        2 => "Local Instance Peer",
+        3 => "Loc-RIB Instance Peer",
+        _ => "Unknown",
+    }
+}
+
+fn peer_down_reason_name(reason: u8) -> &'static str {
+    match reason {
+        BMP_PEER_DOWN_LOCAL_NOTIFICATION => "Local system closed the session with a NOTIFICATION",
+        BMP_PEER_DOWN_LOCAL_NO_NOTIFICATION => "Local system closed the session without a NOTIFICATION",
+        BMP_PEER_DOWN_REMOTE_NOTIFICATION => "Remote system closed the session with a NOTIFICATION",
+        BMP_PEER_DOWN_REMOTE_NO_NOTIFICATION => "Remote system closed the session without a NOTIFICATION",
+        BMP_PEER_DOWN_DECONFIGURED => "Peer de-configured",
+        _ => "Unknown reason",
+    }
+}
+
+fn stat_name(stat_type: u16) -> &'static str {
+    match stat_type {
+        0 => "Prefixes rejected by inbound policy",
+        1 => "Duplicate prefix advertisements",
+        2 => "Duplicate withdraws",
+        3 => "Updates invalidated by CLUSTER_LIST loop",
+        4 => "Updates invalidated by AS_PATH loop",
+        5 => "Updates invalidated by ORIGINATOR_ID",
+        6 => "Updates invalidated by AS_CONFED loop",
+        7 => "Routes in Adj-RIBs-In",
+        8 => "Routes in Loc-RIB",
+        9 => "Routes in per-AFI/SAFI Adj-RIB-In",
+        10 => "Routes in per-AFI/SAFI Loc-RIB",
+        11 => "Updates treated as withdraw",
+        12 => "Prefixes treated as withdraw",
+        13 => "Duplicate update messages",
+        _ => "Unknown statistic",
+    }
+}
+
+// Initiation and Peer Up TLVs share types 0-2; Termination has its own type 1
+fn tlv_line(message_type: u8, tlv: &BmpTlv) -> String {
+    match (message_type, tlv.tlv_type) {
+        (BMP_TERMINATION, 1) if tlv.value.len() == 2 => {
+            let reason = u16::from_be_bytes([tlv.value[0], tlv.value[1]]);
+            let name = match reason {
+                0 => "Session administratively closed",
+                1 => "Unspecified reason",
+                2 => "Out of resources",
+                3 => "Redundant connection",
+                4 => "Session permanently administratively closed",
+                _ => "Unknown reason",
+            };
+            format!("Reason: {} ({})", name, reason)
+        },
+        (_, 0) => format!("String: {}", tlv.text()),
+        (BMP_INITIATION, 1) => format!("sysDescr: {}", tlv.text()),
+        (BMP_INITIATION, 2) => format!("sysName: {}", tlv.text()),
+        (_, tlv_type) => format!("TLV {}: {} bytes", tlv_type, tlv.value.len()),
+    }
+}
+
+// Route distinguisher notation (RFC 4364 4.2) for the peer distinguisher
+fn distinguisher(value: u64) -> String {
+    let bytes = value.to_be_bytes();
+    match u16::from_be_bytes([bytes[0], bytes[1]]) {
+        0 => format!("{}:{}", u16::from_be_bytes([bytes[2], bytes[3]]), value as u32),
+        1 => format!("{}:{}", Ipv4Addr::new(bytes[2], bytes[3], bytes[4], bytes[5]), value as u16),
+        2 => format!("{}:{}", u32::from_be_bytes([bytes[2], bytes[3], bytes[4], bytes[5]]), value as u16),
+        _ => format!("{:016x}", value),
+    }
+}
+
+impl BmpResponse {
+    /// Describe `stream` as decoded with `context`.
+    pub fn with_context(stream: &BmpStream, context: &SessionContext) -> Self {
+        BmpResponse {
+            messages: stream.messages.iter()
+                .map(|message| BmpMessageResponse::with_context(message, context))
+                .collect(),
+            warnings: stream.warnings.clone(),
+        }
+    }
+}
+
+impl From<&BmpStream> for BmpResponse {
+    fn from(stream: &BmpStream) -> Self {
+        Self::with_context(stream, &SessionContext::default())
+    }
+}
+
+impl From<&PeerHeader> for BmpPeerResponse {
+    fn from(peer: &PeerHeader) -> Self {
+        BmpPeerResponse {
+            peer_type: peer.peer_type,
+            peer_type_name: peer_type_name(peer.peer_type).to_string(),
+            flags: peer.flags,
+            post_policy: peer.is_post_policy(),
+            distinguisher: (peer.distinguisher != 0).then(|| distinguisher(peer.distinguisher)),
+            address: peer.address.to_string(),
+            peer_as: peer.peer_as,
+            bgp_id: peer.bgp_id.to_string(),
+            timestamp: peer.timestamp,
+            time: peer.timestamp.to_string(),
+        }
+    }
+}
+
+impl BmpMessageResponse {
+    /// Describe `message` as decoded with `context`; a Peer Down
+    /// NOTIFICATION is described as in universal mode.
+    pub fn with_context(message: &BmpMessage, context: &SessionContext) -> Self {
+        let mut summary = Vec::new();
+        let mut messages = Vec::new();
+        let mut peer_down = None;
+        match &message.body {
+            BmpBody::RouteMonitoring(update) => messages.push(StreamMessageResponse::with_context(update, context)),
+            BmpBody::StatisticsReport(stats) => {
+                summary.extend(stats.iter().map(|stat| {
+                    let family = stat.family
+                        .map_or(String::new(), |(afi, safi)| format!(" ({})", family_name(afi, safi)));
+                    let value = stat.value.map_or("?".to_string(), |value| value.to_string());
+                    format!("{}{}: {}", stat_name(stat.stat_type), family, value)
+                }));
+            },
+            BmpBody::PeerDown(down) => {
+                let (notification, error) = match &down.notification {
+                    Some(Ok(notification)) => {
+                        (Some(UniversalDecodeResponse::with_context(notification, context)), None)
+                    },
+                    Some(Err(e)) => (None, Some(e.to_string())),
+                    None => (None, None),
+                };
+                peer_down = Some(PeerDownResponse {
+                    reason: down.reason,
+                    reason_name: peer_down_reason_name(down.reason).to_string(),
+                    notification,
+                    error,
+                    fsm_event: down.fsm_event,
+                });
+            },
+            BmpBody::PeerUp(up) => {
+                summary.push(format!(
+                    "Local {} port {}, remote port {}",
+                    up.local_address, up.local_port, up.remote_port
+                ));
+                summary.extend(up.information.iter().map(|tlv| tlv_line(message.message_type, tlv)));
+                messages.push(StreamMessageResponse::with_context(&up.sent_open, context));
+                messages.push(StreamMessageResponse::with_context(&up.received_open, context));
+            },
+            BmpBody::Initiation(tlvs) | BmpBody::Termination(tlvs) => {
+                summary.extend(tlvs.iter().map(|tlv| tlv_line(message.message_type, tlv)));
+            },
+            BmpBody::Other(data) => summary.push(format!("{} bytes not decoded", data.len())),
+        }
+
+        BmpMessageResponse {
+            offset: message.offset,
+            length: message.length,
+            message_type: message.message_type,
+            type_name: message_type_name(message.message_type).to_string(),
+            peer: message.peer.as_ref().map(BmpPeerResponse::from),
+            summary,
+            messages,
+            peer_down,
+        }
+    }
+}
+
+impl From<&BmpMessage> for BmpMessageResponse {
+    fn from(message: &BmpMessage) -> Self {
+        Self::with_context(message, &SessionContext::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{parse_hex, BGP_CEASE_ADMIN_SHUTDOWN, BGP_ERROR_CEASE, BGP_UPDATE};
+
+    const SHUTDOWN: &str = "ffffffffffffffffffffffffffffffff 0021 03 06 02 0b 4d61696e74656e616e6365";
+
+    // A message of `message_type` around `body`, with a per-peer header for
+    // 192.0.2.1 AS65001 where the type has one
+    fn bmp(message_type: u8, body: &[u8]) -> Vec<u8> {
+        let mut message = vec![BMP_VERSION, 0, 0, 0, 0, message_type];
+        if !matches!(message_type, BMP_INITIATION | BMP_TERMINATION) {
+            message.extend_from_slice(&[0, 0]);
+            message.extend_from_slice(&[0; 8]);
+            message.extend_from_slice(&[0; 12]);
+            message.extend_from_slice(&[192, 0, 2, 1]);
+            message.extend_from_slice(&65001u32.to_be_bytes());
+            message.extend_from_slice(&[192, 0, 2, 1]);
+            message.extend_from_slice(&1_700_000_000u32.to_be_bytes());
+            message.extend_from_slice(&250_000u32.to_be_bytes());
+        }
+        message.extend_from_slice(body);
+        let length = (message.len() as u32).to_be_bytes();
+        message[1..5].copy_from_slice(&length);
+        message
+    }
+
+    fn tlv(tlv_type: u16, value: &[u8]) -> Vec<u8> {
+        [&tlv_type.to_be_bytes()[..], &(value.len() as u16).to_be_bytes(), value].concat()
+    }
+
+    #[test]
+    fn test_peer_down_notification() {
+        let shutdown = parse_hex(SHUTDOWN).unwrap();
+        let message = BmpMessage::parse(&bmp(BMP_PEER_DOWN, &[&[3][..], &shutdown].concat())).unwrap();
+        let peer = message.peer.unwrap();
+        assert_eq!((peer.address, peer.peer_as), (IpAddr::V4(Ipv4Addr::new(192, 0, 2, 1)), 65001));
+        assert_eq!(peer.timestamp.to_string(), "2023-11-14T22:13:20.250000Z");
+        let BmpBody::PeerDown(down) = &message.body else { panic!("not a Peer Down") };
+        let notification = down.notification().unwrap();
+        assert_eq!((notification.error_code, notification.subcode), (BGP_ERROR_CEASE, BGP_CEASE_ADMIN_SHUTDOWN));
+
+        // The same description as universal mode gives
+        let response = BmpMessageResponse::from(&message);
+        let peer_down = response.peer_down.unwrap();
+        assert_eq!(peer_down.reason_name, "Remote system closed the session with a NOTIFICATION");
+        let universal = UniversalDecodeResponse::from(&Notification::parse(&shutdown).unwrap());
+        assert_eq!(peer_down.notification.unwrap().interpretation, universal.interpretation);
+
+        // Reason 2 has an FSM event instead; a damaged PDU is reported, not fatal
+        let message = BmpMessage::parse(&bmp(BMP_PEER_DOWN, &[2, 0, 7])).unwrap();
+        let BmpBody::PeerDown(down) = &message.body else { panic!("not a Peer Down") };
+        assert_eq!((down.fsm_event, down.notification()), (Some(7), None));
+        let message = BmpMessage::parse(&bmp(BMP_PEER_DOWN, &[&[1][..], &shutdown[..20]].concat())).unwrap();
+        assert!(BmpMessageResponse::from(&message).peer_down.unwrap().error.is_some());
+    }
+
+    #[test]
+    fn test_bmp_stream() {
+        let update = parse_hex("ffffffffffffffffffffffffffffffff 0017 02 0000 0000").unwrap();
+        let open = parse_hex("ffffffffffffffffffffffffffffffff 001d 01 04 fde9 00b4 c0000201 00").unwrap();
+        let stats = [
+            &2u32.to_be_bytes()[..],
+            &tlv(0, &3u32.to_be_bytes()),
+            &tlv(9, &[&[0, 2, 1][..], &42u64.to_be_bytes()].concat()),
+        ].concat();
+        let ports = [179u16, 49152].map(u16::to_be_bytes).concat();
+        let peer_up = [&[0; 12][..], &[192, 0, 2, 2], &ports, &open, &open].concat();
+        let stream = [
+            bmp(BMP_INITIATION, &[tlv(1, b"Junos"), tlv(2, b"r1")].concat()),
+            bmp(BMP_PEER_UP, &peer_up),
+            bmp(BMP_ROUTE_MONITORING, &update),
+            bmp(BMP_STATISTICS_REPORT, &stats),
+            bmp(BMP_PEER_DOWN, &[&[1][..], &parse_hex(SHUTDOWN).unwrap()].concat()),
+            bmp(BMP_TERMINATION, &tlv(1, &[0, 0])),
+        ].concat();
+
+        let parsed = BmpStream::parse(&stream).unwrap();
+        assert_eq!(parsed.messages.len(), 6);
+        assert!(parsed.warnings.is_empty());
+        let types: Vec<_> = parsed.messages.iter().map(|m| m.message_type).collect();
+        assert_eq!(types, [4, 3, 0, 1, 2, 5]);
+        let BmpBody::RouteMonitoring(framed) = &parsed.messages[2].body else { panic!("not Route Monitoring") };
+        assert_eq!((framed.offset, framed.message_type), (parsed.messages[2].offset + 48, BGP_UPDATE));
+        assert_eq!(parsed.peer_down_notifications().count(), 1);
+
+        let response = BmpResponse::from(&parsed);
+        assert_eq!(response.messages[0].summary, ["sysDescr: Junos", "sysName: r1"]);
+        assert_eq!(response.messages[1].messages.len(), 2);
+        assert_eq!(response.messages[1].summary[0], "Local 192.0.2.2 port 179, remote port 49152");
+        assert_eq!(response.messages[3].summary, [
+            "Prefixes rejected by inbound policy: 3",
+            "Routes in per-AFI/SAFI Adj-RIB-In (IPv6 unicast): 42",
+        ]);
+        assert_eq!(response.messages[5].summary, ["Reason: Session administratively closed (0)"]);
+    }
+
+    #[test]
+    fn test_bmp_damage() {
+        let mut bad = bmp(BMP_ROUTE_MONITORING, &[0xff; 4]);
+        let good = bmp(BMP_TERMINATION, &[]);
+        let parsed = BmpStream::parse(&[good.clone(), bad.clone(), good.clone()].concat()).unwrap();
+        assert_eq!(parsed.messages.len(), 2);
+        assert!(parsed.warnings[0].starts_with("Message 2: Truncated BGP UPDATE PDU"));
+
+        bad[0] = 1;
+        assert!(matches!(BmpStream::parse(&bad), Err(DecodeError::UnsupportedBmpVersion { actual: 1, .. })));
+        let short = [BMP_VERSION, 0, 0, 0, 6, BMP_PEER_DOWN];
+        assert!(matches!(BmpMessage::parse(&short), Err(DecodeError::BadBmpLength { declared: 6, min: 48, .. })));
+        let mut cut = good.clone();
+        cut.push(0);
+        assert!(matches!(BmpMessage::parse(&cut), Err(DecodeError::TrailingData { .. })));
+    }
+}
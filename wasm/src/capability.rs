@@ -0,0 +1,316 @@
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::reader::Reader;
+use crate::{family_name, DecodeError, EncodeError};
+
+const RFC_CAPABILITIES: &str = "RFC 5492 4";
+
+const CAP_MULTIPROTOCOL: u8 = 1;
+const CAP_ROUTE_REFRESH: u8 = 2;
+const CAP_EXTENDED_MESSAGE: u8 = 6;
+const CAP_ROLE: u8 = 9;
+const CAP_GRACEFUL_RESTART: u8 = 64;
+const CAP_FOUR_OCTET_AS: u8 = 65;
+const CAP_ADD_PATH: u8 = 69;
+const CAP_FQDN: u8 = 73;
+
+/// A BGP capability advertised in an OPEN message (RFC 5492).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Capability {
+    /// Multiprotocol Extensions (RFC 4760).
+    Multiprotocol { afi: u16, safi: u8 },
+    /// Route Refresh (RFC 2918).
+    RouteRefresh,
+    /// BGP Extended Message (RFC 8654).
+    ExtendedMessage,
+    /// BGP Role (RFC 9234).
+    Role { role: u8 },
+    /// Graceful Restart (RFC 4724); `flags` holds the upper four bits of
+    /// the restart flags field (R = 0x8, N = 0x4 per RFC 8538).
+    GracefulRestart { flags: u8, restart_time: u16, families: Vec<GracefulRestartFamily> },
+    /// Support for 4-octet AS numbers (RFC 6793).
+    FourOctetAs { asn: u32 },
+    /// ADD-PATH (RFC 7911).
+    AddPath { families: Vec<AddPathFamily> },
+    /// FQDN (draft-walton-bgp-hostname-capability).
+    Fqdn { hostname: String, domain: String },
+    /// Any capability this crate does not decode, kept verbatim.
+    Unknown { code: u8, value: Vec<u8> },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GracefulRestartFamily {
+    pub afi: u16,
+    pub safi: u8,
+    /// Per-family flags; 0x80 means forwarding state was preserved.
+    pub flags: u8,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AddPathFamily {
+    pub afi: u16,
+    pub safi: u8,
+    /// 1 = receive, 2 = send, 3 = both.
+    pub send_receive: u8,
+}
+
+impl Capability {
+    /// Decode one capability value; `base` is its offset in the message.
+    pub(crate) fn parse(code: u8, value: &[u8], base: usize) -> Result<Self, DecodeError> {
+        let mut r = Reader::new(value, base, RFC_CAPABILITIES);
+
+        let capability = match code {
+            CAP_MULTIPROTOCOL => {
+                let afi = r.u16("multiprotocol AFI")?;
+                r.u8("multiprotocol reserved octet")?;
+                let safi = r.u8("multiprotocol SAFI")?;
+                Capability::Multiprotocol { afi, safi }
+            },
+            CAP_ROUTE_REFRESH => Capability::RouteRefresh,
+            CAP_EXTENDED_MESSAGE => Capability::ExtendedMessage,
+            CAP_ROLE => Capability::Role { role: r.u8("BGP role")? },
+            CAP_GRACEFUL_RESTART => {
+                let field = r.u16("graceful restart flags")?;
+                let mut families = Vec::new();
+                while !r.is_empty() {
+                    families.push(GracefulRestartFamily {
+                        afi: r.u16("graceful restart AFI")?,
+                        safi: r.u8("graceful restart SAFI")?,
+                        flags: r.u8("graceful restart family flags")?,
+                    });
+                }
+                Capability::GracefulRestart {
+                    flags: (field >> 12) as u8,
+                    restart_time: field & 0x0fff,
+                    families,
+                }
+            },
+            CAP_FOUR_OCTET_AS => Capability::FourOctetAs { asn: r.u32("4-octet AS number")? },
+            CAP_ADD_PATH => {
+                let mut families = Vec::new();
+                while !r.is_empty() {
+                    families.push(AddPathFamily {
+                        afi: r.u16("ADD-PATH AFI")?,
+                        safi: r.u8("ADD-PATH SAFI")?,
+                        send_receive: r.u8("ADD-PATH send/receive")?,
+                    });
+                }
+                Capability::AddPath { families }
+            },
+            CAP_FQDN => {
+                let len = r.u8("FQDN hostname length")? as usize;
+                let hostname = String::from_utf8_lossy(r.take(len, "FQDN hostname")?).into_owned();
+                let len = r.u8("FQDN domain length")? as usize;
+                let domain = String::from_utf8_lossy(r.take(len, "FQDN domain")?).into_owned();
+                Capability::Fqdn { hostname, domain }
+            },
+            _ => return Ok(Capability::Unknown { code, value: value.to_vec() }),
+        };
+
+        // Trailing bytes after a fixed-size value: keep them rather than drop them
+        if !r.is_empty() {
+            return Ok(Capability::Unknown { code, value: value.to_vec() });
+        }
+
+        Ok(capability)
+    }
+
+    pub fn code(&self) -> u8 {
+        match self {
+            Capability::Multiprotocol { .. } => CAP_MULTIPROTOCOL,
+            Capability::RouteRefresh => CAP_ROUTE_REFRESH,
+            Capability::ExtendedMessage => CAP_EXTENDED_MESSAGE,
+            Capability::Role { .. } => CAP_ROLE,
+            Capability::GracefulRestart { .. } => CAP_GRACEFUL_RESTART,
+            Capability::FourOctetAs { .. } => CAP_FOUR_OCTET_AS,
+            Capability::AddPath { .. } => CAP_ADD_PATH,
+            Capability::Fqdn { .. } => CAP_FQDN,
+            Capability::Unknown { code, .. } => *code,
+        }
+    }
+
+    /// Capability value, without the code and length octets.
+    pub fn value(&self) -> Result<Vec<u8>, EncodeError> {
+        let mut value = Vec::new();
+
+        match self {
+            Capability::Multiprotocol { afi, safi } => {
+                value.extend_from_slice(&afi.to_be_bytes());
+                value.push(0);
+                value.push(*safi);
+            },
+            Capability::RouteRefresh | Capability::ExtendedMessage => {},
+            Capability::Role { role } => value.push(*role),
+            Capability::GracefulRestart { flags, restart_time, families } => {
+                if *restart_time > 0x0fff {
+                    return Err(EncodeError::OutOfRange {
+                        field: "graceful restart time",
+                        actual: *restart_time as u64,
+                        min: 0,
+                        max: 0x0fff,
+                    });
+                }
+                let field = ((*flags as u16 & 0x0f) << 12) | restart_time;
+                value.extend_from_slice(&field.to_be_bytes());
+                for family in families {
+                    value.extend_from_slice(&family.afi.to_be_bytes());
+                    value.push(family.safi);
+                    value.push(family.flags);
+                }
+            },
+            Capability::FourOctetAs { asn } => value.extend_from_slice(&asn.to_be_bytes()),
+            Capability::AddPath { families } => {
+                for family in families {
+                    value.extend_from_slice(&family.afi.to_be_bytes());
+                    value.push(family.safi);
+                    value.push(family.send_receive);
+                }
+            },
+            Capability::Fqdn { hostname, domain } => {
+                for (field, text) in [("FQDN hostname", hostname), ("FQDN domain", domain)] {
+                    if text.len() > 255 {
+                        return Err(EncodeError::FieldTooLong { field, actual: text.len(), max: 255 });
+                    }
+                    value.push(text.len() as u8);
+                    value.extend_from_slice(text.as_bytes());
+                }
+            },
+            Capability::Unknown { value: raw, .. } => value.extend_from_slice(raw),
+        }
+
+        Ok(value)
+    }
+
+    /// Capability TLV: code, length and value.
+    pub fn to_bytes(&self) -> Result<Vec<u8>, EncodeError> {
+        let value = self.value()?;
+        if value.len() > 255 {
+            return Err(EncodeError::FieldTooLong { field: "capability value", actual: value.len(), max: 255 });
+        }
+
+        let mut tlv = Vec::with_capacity(2 + value.len());
+        tlv.push(self.code());
+        tlv.push(value.len() as u8);
+        tlv.extend_from_slice(&value);
+        Ok(tlv)
+    }
+}
+
+/// IANA name of a capability code.
+pub fn capability_name(code: u8) -> &'static str {
+    match code {
+        1 => "Multiprotocol Extensions",
+        2 => "Route Refresh",
+        3 => "Outbound Route Filtering",
+        5 => "Extended Next Hop Encoding",
+        6 => "BGP Extended Message",
+        7 => "BGPsec",
+        8 => "Multiple Labels",
+        9 => "BGP Role",
+        64 => "Graceful Restart",
+        65 => "Support for 4-octet AS number",
+        69 => "ADD-PATH",
+        70 => "Enhanced Route Refresh",
+        71 => "Long-Lived Graceful Restart",
+        73 => "FQDN",
+        128 => "Route Refresh (pre-standard)",
+        _ => "Unknown",
+    }
+}
+
+/// Name of a BGP Role value (RFC 9234 section 4.1).
+pub fn role_name(role: u8) -> &'static str {
+    match role {
+        0 => "Provider",
+        1 => "RS",
+        2 => "RS-Client",
+        3 => "Customer",
+        4 => "Peer",
+        _ => "Unknown",
+    }
+}
+
+impl fmt::Display for Capability {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = capability_name(self.code());
+        match self {
+            Capability::Multiprotocol { afi, safi } => write!(f, "{}: {}", name, family_name(*afi, *safi)),
+            Capability::RouteRefresh | Capability::ExtendedMessage => write!(f, "{}", name),
+            Capability::Role { role } => write!(f, "{}: {} ({})", name, role_name(*role), role),
+            Capability::GracefulRestart { flags, restart_time, families } => {
+                write!(f, "{}: restart time {}s", name, restart_time)?;
+                if flags & 0x8 != 0 {
+                    write!(f, ", restarting")?;
+                }
+                if flags & 0x4 != 0 {
+                    write!(f, ", notification support")?;
+                }
+                for family in families {
+                    let preserved = if family.flags & 0x80 != 0 { " (forwarding preserved)" } else { "" };
+                    write!(f, "; {}{}", family_name(family.afi, family.safi), preserved)?;
+                }
+                Ok(())
+            },
+            Capability::FourOctetAs { asn } => write!(f, "{}: AS{}", name, asn),
+            Capability::AddPath { families } => {
+                let list: Vec<String> = families.iter().map(|family| {
+                    let mode = match family.send_receive {
+                        1 => "receive",
+                        2 => "send",
+                        3 => "send/receive",
+                        _ => "invalid",
+                    };
+                    format!("{} {}", family_name(family.afi, family.safi), mode)
+                }).collect();
+                write!(f, "{}: {}", name, list.join(", "))
+            },
+            Capability::Fqdn { hostname, domain } if domain.is_empty() => write!(f, "{}: {}", name, hostname),
+            Capability::Fqdn { hostname, domain } => write!(f, "{}: {}.{}", name, hostname, domain),
+            Capability::Unknown { code, value } => {
+                write!(f, "Capability {} ({}): {} bytes", code, name, value.len())
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_capability_round_trip() {
+        let capabilities = vec![
+            Capability::Multiprotocol { afi: 2, safi: 1 },
+            Capability::RouteRefresh,
+            Capability::Role { role: 3 },
+            Capability::GracefulRestart {
+                flags: 0x4,
+                restart_time: 120,
+                families: vec![GracefulRestartFamily { afi: 1, safi: 1, flags: 0x80 }],
+            },
+            Capability::FourOctetAs { asn: 4_200_000_000 },
+            Capability::AddPath { families: vec![AddPathFamily { afi: 1, safi: 1, send_receive: 3 }] },
+            Capability::Fqdn { hostname: "edge1".to_string(), domain: "example.net".to_string() },
+            Capability::Unknown { code: 71, value: vec![0, 1, 1, 0, 0, 0, 0] },
+        ];
+
+        for capability in capabilities {
+            let tlv = capability.to_bytes().unwrap();
+            let parsed = Capability::parse(tlv[0], &tlv[2..], 0).unwrap();
+            assert_eq!(parsed, capability);
+        }
+    }
+
+    #[test]
+    fn test_capability_display() {
+        assert_eq!(
+            Capability::Multiprotocol { afi: 2, safi: 1 }.to_string(),
+            "Multiprotocol Extensions: IPv6 unicast"
+        );
+        assert_eq!(Capability::Role { role: 3 }.to_string(), "BGP Role: Customer (3)");
+        assert!(Capability::parse(CAP_FOUR_OCTET_AS, &[0, 1], 30).is_err());
+    }
+}
@@ -0,0 +1,290 @@
+// Structured content in shutdown communications: ticket IDs, URLs, contacts,
+// AS numbers, restore times and exchange references. Operators write these
+// in free text, so each is recognized token by token rather than by one
+// fixed format.
+
+use serde::{Deserialize, Serialize};
+
+use crate::template::{iso_duration, parse_duration, parse_rfc3339};
+
+/// What an [`Entity`] refers to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EntityKind {
+    /// A change or incident ID such as `CHG-1234`, `INC0012345` or `#4711`.
+    Ticket,
+    Url,
+    Email,
+    /// `AS65000`, `ASN 65000`.
+    Asn,
+    /// A full RFC 3339 date and time.
+    Timestamp,
+    /// A time of day such as `15:00 UTC`.
+    Time,
+    /// A relative time such as `30min` or `2 hours`.
+    Duration,
+    /// A PeeringDB link.
+    PeeringDb,
+    /// An Internet Exchange name such as `AMS-IX` or `DE-CIX`.
+    Ix,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Entity {
+    pub kind: EntityKind,
+    /// Byte offset and length within the text.
+    pub offset: usize,
+    pub length: usize,
+    /// Normalized form: the AS number's digits, an RFC 3339 timestamp,
+    /// `HH:MM:SS` with `Z` when the zone is UTC, an ISO 8601 duration, the
+    /// PeeringDB object path (`net/694`), otherwise the text itself.
+    pub value: String,
+    /// Length of a duration, in seconds.
+    pub seconds: Option<u64>,
+}
+
+// Words that introduce a ticket ID whatever its format
+const TICKET_KEYWORDS: &[&str] = &["ticket", "case", "ref", "change", "incident", "issue"];
+
+// ServiceNow record prefixes, written without a separator
+const SERVICENOW_PREFIXES: &[&str] = &["INC", "CHG", "RITM", "PRB", "TASK", "REQ"];
+
+/// Find tickets, links, contacts, AS numbers and times in `text`.
+pub fn extract_entities(text: &str) -> Vec<Entity> {
+    let tokens = tokens(text);
+    let mut entities = Vec::new();
+
+    let mut i = 0;
+    while i < tokens.len() {
+        let (offset, token) = tokens[i];
+        let next = tokens.get(i + 1).map(|&(_, token)| token);
+        let previous = i.checked_sub(1).map(|i| tokens[i].1.to_ascii_lowercase());
+        let entity = |kind, length: usize, value: String| Entity { kind, offset, length, value, seconds: None };
+        // Length of this token plus the gap and the next one
+        let span = |next: usize| tokens[next].0 + tokens[next].1.len() - offset;
+
+        let (found, consumed) = if let Some((kind, value)) = url(token) {
+            (Some(entity(kind, token.len(), value)), 1)
+        } else if is_email(token) {
+            (Some(entity(EntityKind::Email, token.len(), token.to_string())), 1)
+        } else if let Some(timestamp) = parse_rfc3339(token) {
+            (Some(entity(EntityKind::Timestamp, token.len(), timestamp)), 1)
+        } else if let Some(asn) = asn(token) {
+            (Some(entity(EntityKind::Asn, token.len(), asn.to_string())), 1)
+        } else if let (true, Some(asn)) = (matches!(token, "AS" | "as" | "ASN" | "asn"), next.and_then(|n| n.parse::<u32>().ok())) {
+            (Some(entity(EntityKind::Asn, span(i + 1), asn.to_string())), 2)
+        } else if is_ticket(token, previous.as_deref()) {
+            (Some(entity(EntityKind::Ticket, token.len(), token.trim_start_matches('#').to_string())), 1)
+        } else if let Some((seconds, consumed)) = duration(token, next) {
+            let length = if consumed == 2 { span(i + 1) } else { token.len() };
+            (Some(Entity { seconds: Some(seconds), ..entity(EntityKind::Duration, length, iso_duration(seconds)) }), consumed)
+        } else if let Some((time, consumed)) = time_of_day(token, next) {
+            let length = if consumed == 2 { span(i + 1) } else { token.len() };
+            (Some(entity(EntityKind::Time, length, time)), consumed)
+        } else if is_ix(token) {
+            (Some(entity(EntityKind::Ix, token.len(), token.to_string())), 1)
+        } else {
+            (None, 1)
+        };
+
+        entities.extend(found);
+        i += consumed;
+    }
+
+    entities
+}
+
+// Whitespace-separated words with their byte offsets, stripped of the
+// brackets, quotes and punctuation prose puts around them
+fn tokens(text: &str) -> Vec<(usize, &str)> {
+    let mut tokens = Vec::new();
+    let mut offset = 0;
+    for word in text.split(char::is_whitespace) {
+        let trimmed = word.trim_start_matches(['(', '[', '<', '"', '\'']);
+        let start = offset + word.len() - trimmed.len();
+        let trimmed = trimmed.trim_end_matches([')', ']', '>', '"', '\'', ',', '.', ';', ':', '!', '?']);
+        if !trimmed.is_empty() {
+            tokens.push((start, trimmed));
+        }
+        offset += word.len();
+        offset += text[offset..].chars().next().map_or(0, char::len_utf8);
+    }
+    tokens
+}
+
+fn url(token: &str) -> Option<(EntityKind, String)> {
+    let lower = token.to_ascii_lowercase();
+    let rest = ["https://", "http://"]
+        .iter()
+        .find_map(|scheme| lower.strip_prefix(scheme))
+        .or_else(|| lower.starts_with("www.").then_some(lower.as_str()))
+        .or_else(|| lower.starts_with("peeringdb.com").then_some(lower.as_str()))?;
+    if rest.is_empty() {
+        return None;
+    }
+
+    let host = rest.split(['/', '?', '#']).next().unwrap_or_default();
+    if host == "peeringdb.com" || host.ends_with(".peeringdb.com") {
+        let path = rest[host.len()..].trim_matches('/');
+        let value = if path.is_empty() { token.to_string() } else { path.to_string() };
+        return Some((EntityKind::PeeringDb, value));
+    }
+    Some((EntityKind::Url, token.to_string()))
+}
+
+fn is_email(token: &str) -> bool {
+    let Some((local, domain)) = token.split_once('@') else {
+        return false;
+    };
+    let valid = |c: char| c.is_alphanumeric() || "._%+-".contains(c);
+    !local.is_empty()
+        && local.chars().all(valid)
+        && domain.contains('.')
+        && !domain.starts_with('.')
+        && !domain.ends_with('.')
+        && domain.chars().all(|c| c.is_alphanumeric() || c == '.' || c == '-')
+}
+
+fn asn(token: &str) -> Option<u32> {
+    let upper = token.to_ascii_uppercase();
+    let digits = upper.strip_prefix("ASN").or_else(|| upper.strip_prefix("AS"))?;
+    if digits.is_empty() || !digits.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    digits.parse().ok()
+}
+
+fn is_ticket(token: &str, previous: Option<&str>) -> bool {
+    let id_chars = |s: &str| s.chars().all(|c| c.is_ascii_alphanumeric() || "-_/#".contains(c));
+    if previous.is_some_and(|word| TICKET_KEYWORDS.contains(&word)) {
+        return id_chars(token) && token.bytes().any(|b| b.is_ascii_digit());
+    }
+
+    if let Some(number) = token.strip_prefix('#') {
+        return number.len() >= 3 && number.bytes().all(|b| b.is_ascii_digit());
+    }
+    // JIRA style: PROJECT-123
+    if let Some((project, number)) = token.split_once('-') {
+        return (2..=10).contains(&project.len())
+            && project.starts_with(|c: char| c.is_ascii_uppercase())
+            && project.chars().all(|c| c.is_ascii_uppercase() || c.is_ascii_digit())
+            && !number.is_empty()
+            && number.bytes().all(|b| b.is_ascii_digit());
+    }
+    SERVICENOW_PREFIXES.iter().any(|prefix| {
+        token.strip_prefix(prefix).is_some_and(|number| number.len() >= 5 && number.bytes().all(|b| b.is_ascii_digit()))
+    })
+}
+
+// `30min`, `1h30m`, `PT2H`, or a number followed by a unit word
+fn duration(token: &str, next: Option<&str>) -> Option<(u64, usize)> {
+    if token.bytes().any(|b| b.is_ascii_alphabetic()) {
+        if let Some(seconds) = parse_duration(token).filter(|&seconds| seconds > 0) {
+            return Some((seconds, 1));
+        }
+    }
+
+    let split = token.find(|c: char| !c.is_ascii_digit()).unwrap_or(token.len());
+    let number: u64 = token[..split].parse().ok()?;
+    let (unit, consumed) = match &token[split..] {
+        "" => (next?, 2),
+        unit => (unit, 1),
+    };
+    let scale = match unit.to_ascii_lowercase().as_str() {
+        "s" | "sec" | "secs" | "second" | "seconds" => 1,
+        "m" | "min" | "mins" | "minute" | "minutes" => 60,
+        "h" | "hr" | "hrs" | "hour" | "hours" => 3_600,
+        "d" | "day" | "days" => 86_400,
+        _ => return None,
+    };
+    Some((number.checked_mul(scale).filter(|&seconds| seconds > 0)?, consumed))
+}
+
+// `15:00`, `15:00:30`, with a `Z`, `UTC` or `GMT` zone attached or following
+fn time_of_day(token: &str, next: Option<&str>) -> Option<(String, usize)> {
+    let upper = token.to_ascii_uppercase();
+    let (clock, zone) = ["UTC", "GMT", "Z"]
+        .iter()
+        .find_map(|zone| upper.strip_suffix(zone).map(|clock| (clock, true)))
+        .unwrap_or((upper.as_str(), false));
+
+    let fields: Vec<&str> = clock.split(':').collect();
+    if !(2..=3).contains(&fields.len()) || fields.iter().any(|f| f.len() != 2 || !f.bytes().all(|b| b.is_ascii_digit())) {
+        return None;
+    }
+    let numbers: Vec<u32> = fields.iter().map(|f| f.parse().unwrap_or(u32::MAX)).collect();
+    if numbers[0] > 23 || numbers[1] > 59 || numbers.get(2).is_some_and(|&s| s > 60) {
+        return None;
+    }
+
+    let zone_follows = !zone && next.is_some_and(|n| ["UTC", "GMT", "Z"].contains(&n.to_ascii_uppercase().as_str()));
+    let time = format!("{}:{}:{}", fields[0], fields[1], fields.get(2).unwrap_or(&"00"));
+    match (zone, zone_follows) {
+        (true, _) => Some((time + "Z", 1)),
+        (false, true) => Some((time + "Z", 2)),
+        (false, false) => Some((time, 1)),
+    }
+}
+
+// AMS-IX, DE-CIX, NL-ix, or an all-capitals name ending in IX (SIX, VIX)
+fn is_ix(token: &str) -> bool {
+    let upper = token.to_ascii_uppercase();
+    token.len() >= 3
+        && upper.ends_with("IX")
+        && token.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+        && (token.contains('-') || token == upper)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn found(text: &str) -> Vec<(EntityKind, &str, String)> {
+        extract_entities(text)
+            .into_iter()
+            .map(|e| (e.kind, &text[e.offset..e.offset + e.length], e.value))
+            .collect()
+    }
+
+    #[test]
+    fn test_maintenance_notice() {
+        let text = "AS64500 maintenance until 2026-10-17T22:00:00Z; ticket 88123; contact noc@example.net (https://status.example.net/)";
+        assert_eq!(found(text), vec![
+            (EntityKind::Asn, "AS64500", "64500".to_string()),
+            (EntityKind::Timestamp, "2026-10-17T22:00:00Z", "2026-10-17T22:00:00Z".to_string()),
+            (EntityKind::Ticket, "88123", "88123".to_string()),
+            (EntityKind::Email, "noc@example.net", "noc@example.net".to_string()),
+            (EntityKind::Url, "https://status.example.net/", "https://status.example.net/".to_string()),
+        ]);
+    }
+
+    #[test]
+    fn test_times_and_durations() {
+        let entities = extract_entities("Back in 30min, or 2 hours at worst. Window ends 15:00 UTC");
+        let seconds: Vec<_> = entities.iter().map(|e| (e.kind, e.value.as_str(), e.seconds)).collect();
+        assert_eq!(seconds, vec![
+            (EntityKind::Duration, "PT30M", Some(1800)),
+            (EntityKind::Duration, "PT2H", Some(7200)),
+            (EntityKind::Time, "15:00:00Z", None),
+        ]);
+        assert_eq!((entities[1].offset, entities[1].length), (18, 7));
+        assert_eq!(found("Übung 1h30m")[0].2, "PT1H30M");
+    }
+
+    #[test]
+    fn test_tickets_and_exchanges() {
+        let text = "CHG-1234 / INC0012345 / #4711: moving AMS-IX and DE-CIX ports, see peeringdb.com/net/694 or ASN 65000";
+        let kinds: Vec<_> = found(text).into_iter().map(|(kind, _, value)| (kind, value)).collect();
+        assert_eq!(kinds, vec![
+            (EntityKind::Ticket, "CHG-1234".to_string()),
+            (EntityKind::Ticket, "INC0012345".to_string()),
+            (EntityKind::Ticket, "4711".to_string()),
+            (EntityKind::Ix, "AMS-IX".to_string()),
+            (EntityKind::Ix, "DE-CIX".to_string()),
+            (EntityKind::PeeringDb, "net/694".to_string()),
+            (EntityKind::Asn, "65000".to_string()),
+        ]);
+        // Plain words and numbers are not entities
+        assert!(extract_entities("ASAP 42 times, Mix and match").is_empty());
+    }
+}
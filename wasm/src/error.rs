@@ -0,0 +1,444 @@
+use std::fmt;
+
+use crate::{max_error_code, Diagnostic, RFC8203_MAX_SHUTDOWN_MSG_LEN};
+
+/// Error returned when a byte buffer or hex dump is not a valid message.
+///
+/// Each variant carries the values needed to point at the problem; the
+/// accessor methods expose them uniformly so callers (and the JS error
+/// object built by the wasm layer) do not have to match on every variant.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DecodeError {
+    /// The hex input contains an odd number of digits.
+    OddHexLength { digits: usize },
+    /// A character in the hex input is not a hex digit.
+    InvalidHexDigit { offset: usize, found: char },
+    /// The hex input decodes to more bytes than allowed.
+    HexTooLong { actual: usize, max: usize },
+    /// The buffer is shorter than the smallest valid message.
+    MessageTooShort { actual: usize, min: usize },
+    /// The buffer is longer than the maximum message size.
+    MessageTooLong { actual: usize, max: usize },
+    /// The 16-byte marker is not all ones; `offset` is the first bad byte.
+    BadMarker { offset: usize },
+    /// The header length field does not match the buffer length.
+    LengthMismatch { declared: usize, received: usize },
+    /// The header length field is outside the permitted range.
+    BadDeclaredLength { declared: usize, min: usize, max: usize },
+    /// The header type field is not the expected message type.
+    UnexpectedMessageType { expected: u8, actual: u8 },
+    /// The notification error code is not Cease.
+    NotCease { actual: u8 },
+    /// The Cease subcode does not carry a shutdown communication.
+    UnknownCeaseSubcode { actual: u8 },
+    /// The notification has no shutdown communication length byte.
+    MissingShutdownLength,
+    /// The shutdown communication length byte disagrees with the data.
+    ShutdownLengthMismatch { expected: usize, actual: usize },
+    /// The shutdown communication is not valid UTF-8.
+    InvalidUtf8 { offset: usize },
+    /// Bytes are left over after the last field of a message or TLV.
+    TrailingData { field: &'static str, offset: usize, actual: usize, rfc: &'static str },
+    /// A prefix length exceeds the address family's bit width.
+    InvalidPrefixLength { offset: usize, actual: u8, max: u8 },
+    /// A field runs past the end of its enclosing message or TLV.
+    Truncated {
+        field: &'static str,
+        offset: usize,
+        expected: usize,
+        actual: usize,
+        rfc: &'static str,
+    },
+    /// The file is neither a pcap nor a pcapng capture.
+    UnknownCaptureFormat { magic: u32 },
+    /// An address family other than IPv4 or IPv6 where one of them is required.
+    UnknownAfi { offset: usize, actual: u16 },
+    /// Compressed input could not be decompressed.
+    Decompression { format: &'static str, reason: String },
+    /// A BMP common header of a version other than 3.
+    UnsupportedBmpVersion { offset: usize, actual: u8 },
+    /// A BMP message length shorter than the headers it must hold.
+    BadBmpLength { offset: usize, declared: usize, min: usize },
+}
+
+/// Error returned when a message cannot be encoded within protocol limits.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EncodeError {
+    /// The error code is not assigned in the IANA registry.
+    InvalidErrorCode { actual: u8 },
+    /// The Cease subcode cannot carry a shutdown communication.
+    InvalidShutdownSubcode { actual: u8 },
+    /// The shutdown communication exceeds its length limit.
+    ShutdownMessageTooLong { actual: usize, max: usize },
+    /// A length-prefixed field does not fit its length octet(s).
+    FieldTooLong { field: &'static str, actual: usize, max: usize },
+    /// The data field does not fit in a single BGP message.
+    DataTooLong { actual: usize, max: usize },
+    /// A typed data value is not a number.
+    InvalidNumber { field: &'static str, value: String },
+    /// A typed data value does not have the expected shape.
+    InvalidFormat { field: &'static str, expected: &'static str },
+    /// A typed data value is outside its permitted range.
+    OutOfRange { field: &'static str, actual: u64, min: u64, max: u64 },
+    /// The error/subcode combination takes no data of the requested type.
+    UnsupportedData { error_code: u8, subcode: u8 },
+    /// A template uses a placeholder that does not exist.
+    UnknownPlaceholder { name: String, offset: usize },
+    /// A template uses a placeholder no value was given for.
+    MissingPlaceholder { name: &'static str },
+    /// Raw hex data could not be parsed.
+    InvalidHex(DecodeError),
+}
+
+impl DecodeError {
+    /// Stable machine-readable identifier, e.g. `"bad_marker"`.
+    pub fn code(&self) -> &'static str {
+        match self {
+            DecodeError::OddHexLength { .. } => "odd_hex_length",
+            DecodeError::InvalidHexDigit { .. } => "invalid_hex_digit",
+            DecodeError::HexTooLong { .. } => "hex_too_long",
+            DecodeError::MessageTooShort { .. } => "message_too_short",
+            DecodeError::MessageTooLong { .. } => "message_too_long",
+            DecodeError::BadMarker { .. } => "bad_marker",
+            DecodeError::LengthMismatch { .. } => "length_mismatch",
+            DecodeError::BadDeclaredLength { .. } => "bad_declared_length",
+            DecodeError::UnexpectedMessageType { .. } => "unexpected_message_type",
+            DecodeError::NotCease { .. } => "not_cease",
+            DecodeError::UnknownCeaseSubcode { .. } => "unknown_cease_subcode",
+            DecodeError::MissingShutdownLength => "missing_shutdown_length",
+            DecodeError::ShutdownLengthMismatch { .. } => "shutdown_length_mismatch",
+            DecodeError::InvalidUtf8 { .. } => "invalid_utf8",
+            DecodeError::TrailingData { .. } => "trailing_data",
+            DecodeError::InvalidPrefixLength { .. } => "invalid_prefix_length",
+            DecodeError::Truncated { .. } => "truncated",
+            DecodeError::UnknownCaptureFormat { .. } => "unknown_capture_format",
+            DecodeError::UnknownAfi { .. } => "unknown_afi",
+            DecodeError::Decompression { .. } => "decompression",
+            DecodeError::UnsupportedBmpVersion { .. } => "unsupported_bmp_version",
+            DecodeError::BadBmpLength { .. } => "bad_bmp_length",
+        }
+    }
+
+    /// Offset of the offending byte in the message (or character in the hex input).
+    pub fn offset(&self) -> Option<usize> {
+        match self {
+            DecodeError::InvalidHexDigit { offset, .. }
+            | DecodeError::BadMarker { offset }
+            | DecodeError::InvalidUtf8 { offset }
+            | DecodeError::TrailingData { offset, .. }
+            | DecodeError::InvalidPrefixLength { offset, .. }
+            | DecodeError::Truncated { offset, .. }
+            | DecodeError::UnknownAfi { offset, .. }
+            | DecodeError::UnsupportedBmpVersion { offset, .. }
+            | DecodeError::BadBmpLength { offset, .. } => Some(*offset),
+            DecodeError::UnknownCaptureFormat { .. } | DecodeError::Decompression { .. } => Some(0),
+            DecodeError::LengthMismatch { .. } | DecodeError::BadDeclaredLength { .. } => Some(16),
+            DecodeError::UnexpectedMessageType { .. } => Some(18),
+            DecodeError::NotCease { .. } => Some(19),
+            DecodeError::UnknownCeaseSubcode { .. } => Some(20),
+            DecodeError::MissingShutdownLength | DecodeError::ShutdownLengthMismatch { .. } => Some(21),
+            _ => None,
+        }
+    }
+
+    /// The value or limit the input should have matched.
+    pub fn expected(&self) -> Option<usize> {
+        match self {
+            DecodeError::MessageTooShort { min, .. } => Some(*min),
+            DecodeError::MessageTooLong { max, .. } | DecodeError::HexTooLong { max, .. } => Some(*max),
+            DecodeError::LengthMismatch { received, .. } => Some(*received),
+            DecodeError::UnexpectedMessageType { expected, .. } => Some(*expected as usize),
+            DecodeError::InvalidPrefixLength { max, .. } => Some(*max as usize),
+            DecodeError::UnsupportedBmpVersion { .. } => Some(crate::BMP_VERSION as usize),
+            DecodeError::BadBmpLength { min, .. } => Some(*min),
+            DecodeError::NotCease { .. } => Some(crate::BGP_ERROR_CEASE as usize),
+            DecodeError::ShutdownLengthMismatch { expected, .. }
+            | DecodeError::Truncated { expected, .. } => Some(*expected),
+            _ => None,
+        }
+    }
+
+    /// The value actually found in the input.
+    pub fn actual(&self) -> Option<usize> {
+        match self {
+            DecodeError::OddHexLength { digits } => Some(*digits),
+            DecodeError::HexTooLong { actual, .. }
+            | DecodeError::MessageTooShort { actual, .. }
+            | DecodeError::MessageTooLong { actual, .. }
+            | DecodeError::ShutdownLengthMismatch { actual, .. }
+            | DecodeError::TrailingData { actual, .. }
+            | DecodeError::Truncated { actual, .. } => Some(*actual),
+            DecodeError::LengthMismatch { declared, .. }
+            | DecodeError::BadDeclaredLength { declared, .. }
+            | DecodeError::BadBmpLength { declared, .. } => Some(*declared),
+            DecodeError::UnexpectedMessageType { actual, .. }
+            | DecodeError::NotCease { actual }
+            | DecodeError::UnknownCeaseSubcode { actual }
+            | DecodeError::InvalidPrefixLength { actual, .. } => Some(*actual as usize),
+            DecodeError::UnknownCaptureFormat { magic } => Some(*magic as usize),
+            DecodeError::UnknownAfi { actual, .. } => Some(*actual as usize),
+            DecodeError::UnsupportedBmpVersion { actual, .. } => Some(*actual as usize),
+            _ => None,
+        }
+    }
+
+    /// The RFC section whose requirement the input violates.
+    pub fn rfc(&self) -> Option<&'static str> {
+        match self {
+            DecodeError::OddHexLength { .. }
+            | DecodeError::InvalidHexDigit { .. }
+            | DecodeError::HexTooLong { .. } => None,
+            DecodeError::MessageTooShort { .. }
+            | DecodeError::MessageTooLong { .. }
+            | DecodeError::BadMarker { .. }
+            | DecodeError::LengthMismatch { .. }
+            | DecodeError::BadDeclaredLength { .. }
+            | DecodeError::UnexpectedMessageType { .. } => Some("RFC 4271 4.1"),
+            DecodeError::NotCease { .. } | DecodeError::UnknownCeaseSubcode { .. } => Some("RFC 4486 4"),
+            DecodeError::MissingShutdownLength
+            | DecodeError::ShutdownLengthMismatch { .. }
+            | DecodeError::InvalidUtf8 { .. } => Some("RFC 9003 2"),
+            DecodeError::InvalidPrefixLength { .. } => Some("RFC 4271 4.3"),
+            DecodeError::TrailingData { rfc, .. } | DecodeError::Truncated { rfc, .. } => Some(rfc),
+            DecodeError::UnknownCaptureFormat { .. } => Some("draft-ietf-opsawg-pcapng 4"),
+            DecodeError::UnknownAfi { .. } => Some("RFC 6396 4.4.2"),
+            DecodeError::Decompression { .. } => None,
+            DecodeError::UnsupportedBmpVersion { .. } | DecodeError::BadBmpLength { .. } => Some("RFC 7854 4.1"),
+        }
+    }
+}
+
+impl EncodeError {
+    /// Stable machine-readable identifier, e.g. `"data_too_long"`.
+    pub fn code(&self) -> &'static str {
+        match self {
+            EncodeError::InvalidErrorCode { .. } => "invalid_error_code",
+            EncodeError::InvalidShutdownSubcode { .. } => "invalid_shutdown_subcode",
+            EncodeError::ShutdownMessageTooLong { .. } => "shutdown_message_too_long",
+            EncodeError::FieldTooLong { .. } => "field_too_long",
+            EncodeError::DataTooLong { .. } => "data_too_long",
+            EncodeError::InvalidNumber { .. } => "invalid_number",
+            EncodeError::InvalidFormat { .. } => "invalid_format",
+            EncodeError::OutOfRange { .. } => "out_of_range",
+            EncodeError::UnsupportedData { .. } => "unsupported_data",
+            EncodeError::UnknownPlaceholder { .. } => "unknown_placeholder",
+            EncodeError::MissingPlaceholder { .. } => "missing_placeholder",
+            EncodeError::InvalidHex(e) => e.code(),
+        }
+    }
+
+    /// Character offset in the hex input, for raw hex data, or byte
+    /// offset in the template.
+    pub fn offset(&self) -> Option<usize> {
+        match self {
+            EncodeError::InvalidHex(e) => e.offset(),
+            EncodeError::UnknownPlaceholder { offset, .. } => Some(*offset),
+            _ => None,
+        }
+    }
+
+    /// The limit the value should have stayed within.
+    pub fn expected(&self) -> Option<usize> {
+        match self {
+            EncodeError::ShutdownMessageTooLong { max, .. }
+            | EncodeError::FieldTooLong { max, .. }
+            | EncodeError::DataTooLong { max, .. } => Some(*max),
+            EncodeError::OutOfRange { max, .. } => Some(*max as usize),
+            EncodeError::InvalidHex(e) => e.expected(),
+            _ => None,
+        }
+    }
+
+    /// The value that was supplied.
+    pub fn actual(&self) -> Option<usize> {
+        match self {
+            EncodeError::InvalidErrorCode { actual }
+            | EncodeError::InvalidShutdownSubcode { actual } => Some(*actual as usize),
+            EncodeError::ShutdownMessageTooLong { actual, .. }
+            | EncodeError::FieldTooLong { actual, .. }
+            | EncodeError::DataTooLong { actual, .. } => Some(*actual),
+            EncodeError::OutOfRange { actual, .. } => Some(*actual as usize),
+            EncodeError::InvalidHex(e) => e.actual(),
+            _ => None,
+        }
+    }
+
+    /// The RFC section whose limit the value exceeds.
+    pub fn rfc(&self) -> Option<&'static str> {
+        match self {
+            EncodeError::InvalidErrorCode { .. } => Some("RFC 4271 4.5"),
+            EncodeError::InvalidShutdownSubcode { .. } => Some("RFC 9003 2"),
+            EncodeError::ShutdownMessageTooLong { max, .. } if *max == RFC8203_MAX_SHUTDOWN_MSG_LEN => {
+                Some("RFC 8203 2")
+            },
+            EncodeError::ShutdownMessageTooLong { .. } => Some("RFC 9003 2"),
+            EncodeError::DataTooLong { .. } => Some("RFC 4271 4.1"),
+            EncodeError::InvalidNumber { .. }
+            | EncodeError::InvalidFormat { .. }
+            | EncodeError::FieldTooLong { .. }
+            | EncodeError::OutOfRange { .. }
+            | EncodeError::UnsupportedData { .. }
+            | EncodeError::UnknownPlaceholder { .. }
+            | EncodeError::MissingPlaceholder { .. } => None,
+            EncodeError::InvalidHex(e) => e.rfc(),
+        }
+    }
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DecodeError::OddHexLength { .. } => {
+                write!(f, "Hex string must have even number of characters")
+            },
+            DecodeError::InvalidHexDigit { offset, found } => {
+                write!(f, "Invalid hex character '{}' at position {}", found, offset)
+            },
+            DecodeError::HexTooLong { actual, max } => {
+                write!(f, "Hex data too long: {} bytes (max {})", actual, max)
+            },
+            DecodeError::MessageTooShort { actual, min } => {
+                write!(f, "Message too short: {} bytes (minimum {})", actual, min)
+            },
+            DecodeError::MessageTooLong { actual, max } => {
+                write!(f, "Message too long: {} bytes (maximum {})", actual, max)
+            },
+            DecodeError::BadMarker { offset } => {
+                write!(f, "Invalid BGP marker: must be 16 bytes of 0xFF (byte {} differs)", offset)
+            },
+            DecodeError::LengthMismatch { declared, received } => {
+                write!(f, "Length mismatch: header declares {} bytes, got {}", declared, received)
+            },
+            DecodeError::BadDeclaredLength { declared, min, max } => {
+                write!(f, "Invalid declared length: {} (must be {}-{})", declared, min, max)
+            },
+            DecodeError::UnexpectedMessageType { expected, actual } => {
+                write!(f, "Expected {} message (type {}), got type {} ({})",
+                       crate::message_type_name(*expected), expected,
+                       actual, crate::message_type_name(*actual))
+            },
+            DecodeError::NotCease { actual } => write!(f, "Not a Cease error (code={})", actual),
+            DecodeError::UnknownCeaseSubcode { actual } => write!(f, "Unknown subcode: {}", actual),
+            DecodeError::MissingShutdownLength => write!(f, "Missing shutdown message length byte"),
+            DecodeError::ShutdownLengthMismatch { expected, actual } => {
+                write!(f, "Length mismatch: expected {} bytes, got {}", expected, actual)
+            },
+            DecodeError::InvalidUtf8 { offset } => {
+                write!(f, "Invalid UTF-8 in message at byte {}", offset)
+            },
+            DecodeError::TrailingData { field, offset, actual, .. } => {
+                write!(f, "{} unexpected bytes after {} at byte {}", actual, field, offset)
+            },
+            DecodeError::InvalidPrefixLength { offset, actual, max } => {
+                write!(f, "Invalid prefix length /{} at byte {}: maximum is /{}", actual, offset, max)
+            },
+            DecodeError::Truncated { field, offset, expected, actual, .. } => {
+                write!(f, "Truncated {} at byte {}: needs {} bytes, {} available",
+                       field, offset, expected, actual)
+            },
+            DecodeError::UnknownCaptureFormat { magic } => {
+                write!(f, "Not a pcap or pcapng file (magic number {:08x})", magic)
+            },
+            DecodeError::UnknownAfi { offset, actual } => {
+                write!(f, "Unknown address family {} at byte {}: expected 1 (IPv4) or 2 (IPv6)", actual, offset)
+            },
+            DecodeError::Decompression { format, reason } => write!(f, "Cannot decompress {} input: {}", format, reason),
+            DecodeError::UnsupportedBmpVersion { offset, actual } => {
+                write!(f, "Unsupported BMP version {} at byte {}: expected 3", actual, offset)
+            },
+            DecodeError::BadBmpLength { offset, declared, min } => {
+                write!(f, "Invalid BMP message length {} at byte {}: must be at least {}", declared, offset, min)
+            },
+        }
+    }
+}
+
+impl fmt::Display for EncodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EncodeError::InvalidErrorCode { .. } => {
+                write!(f, "Invalid error code: must be 1-{}", max_error_code())
+            },
+            EncodeError::InvalidShutdownSubcode { .. } => {
+                write!(f, "Invalid subcode: must be 2 or 4")
+            },
+            EncodeError::ShutdownMessageTooLong { actual, max } => {
+                write!(f, "Message exceeds {} bytes (got {})", max, actual)
+            },
+            EncodeError::FieldTooLong { field, actual, max } => {
+                write!(f, "{} too long: {} bytes (max {})", field, actual, max)
+            },
+            EncodeError::DataTooLong { actual, max } => {
+                write!(f, "Data too large for BGP message: {} bytes (max {})", actual, max)
+            },
+            EncodeError::InvalidNumber { field, .. } => {
+                write!(f, "Invalid {} value: must be a number", field)
+            },
+            EncodeError::InvalidFormat { field, expected } => {
+                write!(f, "Invalid {} value: expected {}", field, expected)
+            },
+            EncodeError::OutOfRange { field, min, max, .. } => {
+                write!(f, "{} must be between {} and {}", field, min, max)
+            },
+            EncodeError::UnsupportedData { .. } => {
+                write!(f, "This error/subcode combination doesn't support additional data")
+            },
+            EncodeError::UnknownPlaceholder { name, offset } => {
+                write!(f, "Unknown placeholder {{{}}} at byte {}: use one of {}", name, offset,
+                       crate::PLACEHOLDERS.join(", "))
+            },
+            EncodeError::MissingPlaceholder { name } => {
+                write!(f, "Template needs a value for {{{}}}", name)
+            },
+            EncodeError::InvalidHex(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl std::error::Error for DecodeError {}
+
+impl From<&DecodeError> for Diagnostic {
+    fn from(error: &DecodeError) -> Self {
+        Diagnostic {
+            code: error.code().to_string(),
+            message: error.to_string(),
+            offset: error.offset(),
+            rfc: error.rfc().map(str::to_string),
+        }
+    }
+}
+
+impl std::error::Error for EncodeError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            EncodeError::InvalidHex(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<DecodeError> for EncodeError {
+    fn from(error: DecodeError) -> Self {
+        EncodeError::InvalidHex(error)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_error_details() {
+        let err = DecodeError::LengthMismatch { declared: 40, received: 21 };
+        assert_eq!(err.code(), "length_mismatch");
+        assert_eq!(err.offset(), Some(16));
+        assert_eq!(err.expected(), Some(21));
+        assert_eq!(err.actual(), Some(40));
+        assert_eq!(err.rfc(), Some("RFC 4271 4.1"));
+
+        let err = EncodeError::from(DecodeError::InvalidHexDigit { offset: 3, found: 'z' });
+        assert_eq!(err.code(), "invalid_hex_digit");
+        assert_eq!(err.offset(), Some(3));
+        assert!(std::error::Error::source(&err).is_some());
+    }
+}
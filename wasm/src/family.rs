@@ -0,0 +1,65 @@
+// Address Family and Subsequent Address Family names (IANA registries,
+// RFC 4760). Only the families operators commonly see are named.
+
+const AFI_NAMES: &[(u16, &str)] = &[(1, "IPv4"), (2, "IPv6"), (25, "L2VPN"), (16388, "BGP-LS")];
+
+const SAFI_NAMES: &[(u8, &str)] = &[
+    (1, "unicast"),
+    (2, "multicast"),
+    (4, "labeled unicast"),
+    (5, "MCAST-VPN"),
+    (65, "VPLS"),
+    (70, "EVPN"),
+    (71, "BGP-LS"),
+    (73, "SR TE Policy"),
+    (128, "VPN unicast"),
+    (129, "VPN multicast"),
+    (132, "Route Target constraint"),
+    (133, "flowspec"),
+    (134, "VPN flowspec"),
+];
+
+/// Name of an Address Family Identifier.
+pub fn afi_name(afi: u16) -> Option<&'static str> {
+    AFI_NAMES.iter().find(|&&(code, _)| code == afi).map(|&(_, name)| name)
+}
+
+/// Name of a Subsequent Address Family Identifier.
+pub fn safi_name(safi: u8) -> Option<&'static str> {
+    SAFI_NAMES.iter().find(|&&(code, _)| code == safi).map(|&(_, name)| name)
+}
+
+/// AFI from its number or name, e.g. "2" or "ipv6" (case-insensitive).
+pub fn parse_afi(s: &str) -> Option<u16> {
+    s.parse().ok().or_else(|| AFI_NAMES.iter().find(|(_, name)| name.eq_ignore_ascii_case(s)).map(|&(afi, _)| afi))
+}
+
+/// SAFI from its number or name, e.g. "1" or "unicast" (case-insensitive).
+pub fn parse_safi(s: &str) -> Option<u8> {
+    s.parse().ok().or_else(|| SAFI_NAMES.iter().find(|(_, name)| name.eq_ignore_ascii_case(s)).map(|&(safi, _)| safi))
+}
+
+/// Human-readable family such as "IPv6 unicast".
+pub fn family_name(afi: u16, safi: u8) -> String {
+    let afi = afi_name(afi).map(str::to_string).unwrap_or_else(|| format!("AFI {}", afi));
+    let safi = safi_name(safi).map(str::to_string).unwrap_or_else(|| format!("SAFI {}", safi));
+    format!("{} {}", afi, safi)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_family_name() {
+        assert_eq!(family_name(2, 1), "IPv6 unicast");
+        assert_eq!(family_name(1, 128), "IPv4 VPN unicast");
+        assert_eq!(family_name(99, 7), "AFI 99 SAFI 7");
+
+        assert_eq!(parse_afi("IPv6"), Some(2));
+        assert_eq!(parse_afi("16388"), Some(16388));
+        assert_eq!(parse_safi("flowspec"), Some(133));
+        assert_eq!(parse_safi("Labeled Unicast"), Some(4));
+        assert_eq!(parse_safi("ipv9"), None);
+    }
+}
@@ -0,0 +1,45 @@
+use crate::reader::Reader;
+use crate::{
+    frame_message, parse_hex, validate_message, DecodeError, EncodeError, BGP_HEADER_LEN,
+    BGP_KEEPALIVE, MAX_BGP_MESSAGE_LEN,
+};
+
+const RFC_KEEPALIVE: &str = "RFC 4271 4.4";
+
+/// A BGP KEEPALIVE message (RFC 4271 section 4.4): the header alone.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Keepalive;
+
+impl Keepalive {
+    /// Parse a KEEPALIVE; anything but exactly 19 bytes is rejected.
+    pub fn parse(bytes: &[u8]) -> Result<Self, DecodeError> {
+        validate_message(bytes, BGP_KEEPALIVE, BGP_HEADER_LEN, MAX_BGP_MESSAGE_LEN)?;
+        Reader::new(&bytes[BGP_HEADER_LEN..], BGP_HEADER_LEN, RFC_KEEPALIVE).finish("KEEPALIVE header")?;
+        Ok(Keepalive)
+    }
+
+    /// Parse a hex dump; whitespace, `:` and `-` separators are ignored.
+    pub fn from_hex(hex_input: &str) -> Result<Self, DecodeError> {
+        Self::parse(&parse_hex(hex_input)?)
+    }
+
+    pub fn to_bytes(&self) -> Result<Vec<u8>, EncodeError> {
+        frame_message(BGP_KEEPALIVE, &[], MAX_BGP_MESSAGE_LEN)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_keepalive() {
+        let bytes = Keepalive.to_bytes().unwrap();
+        assert_eq!(bytes.len(), 19);
+        assert_eq!(Keepalive::parse(&bytes), Ok(Keepalive));
+
+        let err = Keepalive::from_hex("ffffffffffffffffffffffffffffffff 0014 04 00").unwrap_err();
+        assert_eq!(err.code(), "trailing_data");
+        assert_eq!(err.offset(), Some(19));
+    }
+}
@@ -1,20 +1,133 @@
+//! BGP NOTIFICATION and RFC 9003 Shutdown Communication codec.
+//!
+//! The core API ([`Notification`], [`ShutdownCommunication`], [`Open`]) is plain Rust
+//! and builds on any target. The `wasm` feature (enabled by default) adds the
+//! `#[wasm_bindgen]` surface used by the frontend; native consumers can depend
+//! on the crate with `default-features = false` to leave wasm-bindgen out.
+
+#[cfg(feature = "wasm")]
 use wasm_bindgen::prelude::*;
 use serde::{Deserialize, Serialize};
 
+mod bmp;
+mod capability;
+mod entity;
+mod error;
+mod family;
+mod keepalive;
+mod message;
+mod mrt;
+mod notification;
+mod open;
+mod pcap;
+mod reader;
+mod registry;
+mod route_refresh;
+mod safety;
+mod shutdown;
+mod stream;
+mod template;
+mod update;
+#[cfg(feature = "wasm")]
+mod wasm;
+
+pub use bmp::{
+    BmpBody, BmpMessage, BmpReader, BmpStat, BmpStream, BmpTlv, PeerDown, PeerHeader, PeerUp,
+    BMP_COMMON_HEADER_LEN, BMP_INITIATION, BMP_PEER_DOWN, BMP_PEER_DOWN_DECONFIGURED,
+    BMP_PEER_DOWN_LOCAL_NOTIFICATION, BMP_PEER_DOWN_LOCAL_NO_NOTIFICATION, BMP_PEER_DOWN_REMOTE_NOTIFICATION,
+    BMP_PEER_DOWN_REMOTE_NO_NOTIFICATION, BMP_PEER_UP, BMP_PER_PEER_HEADER_LEN, BMP_ROUTE_MIRRORING,
+    BMP_ROUTE_MONITORING, BMP_STATISTICS_REPORT, BMP_TERMINATION, BMP_VERSION,
+};
+pub use capability::{capability_name, role_name, AddPathFamily, Capability, GracefulRestartFamily};
+pub use entity::{extract_entities, Entity, EntityKind};
+pub use error::{DecodeError, EncodeError};
+pub use family::{afi_name, family_name, parse_afi, parse_safi, safi_name};
+pub use keepalive::Keepalive;
+pub use message::Message;
+pub use mrt::{
+    decompress, Bgp4mpMessage, MrtCease, MrtCeases, MrtReader, MrtRecord, MrtSession, BGP4MP_MESSAGE,
+    BGP4MP_MESSAGE_ADDPATH, BGP4MP_MESSAGE_AS4, BGP4MP_MESSAGE_AS4_ADDPATH, BGP4MP_MESSAGE_AS4_LOCAL,
+    BGP4MP_MESSAGE_AS4_LOCAL_ADDPATH, BGP4MP_MESSAGE_LOCAL, BGP4MP_MESSAGE_LOCAL_ADDPATH, MRT_BGP4MP,
+    MRT_BGP4MP_ET, MRT_HEADER_LEN,
+};
+pub use notification::Notification;
+pub use open::{Open, OptionalParameter, AS_TRANS, MIN_OPEN_LEN};
+pub use pcap::{
+    Capture, CaptureFormat, CaptureTime, CapturedMessage, SyntheticSession, TcpFlow, BGP_PORT,
+};
+pub use registry::{
+    error_code_entry, max_error_code, subcode_entry, ErrorCodeEntry, SubcodeEntry, ERROR_CODES,
+};
+pub use route_refresh::{
+    route_refresh_subtype_name, RouteRefresh, ROUTE_REFRESH_BORR, ROUTE_REFRESH_EORR,
+    ROUTE_REFRESH_LEN, ROUTE_REFRESH_NORMAL,
+};
+pub use safety::{analyze_text, SafetyIssue, SafetyIssueKind, TextSafety};
+pub use shutdown::{
+    message_budget, truncate_message, BudgetOverflow, LenientShutdown, MessageBudget,
+    ShutdownCommunication, ShutdownCompatibility, DEFAULT_ELLIPSIS,
+};
+pub use stream::{
+    FramedMessage, FramingError, MessageDecoder, MessageStream, PartialMessage, StreamItem,
+    MAX_STREAM_LEN,
+};
+pub use template::{
+    expand_template, find_template, format_until, ShutdownTemplate, TemplateValues, PLACEHOLDERS,
+    TEMPLATES,
+};
+pub use update::{
+    attribute_name, AsPathSegment, AttributeValue, LargeCommunity, PathAttribute, Prefix, Update,
+    MIN_UPDATE_LEN,
+};
+#[cfg(feature = "wasm")]
+pub use wasm::*;
+
 // Constants - use explicit typing to prevent integer overflow
-const BGP_MARKER: [u8; 16] = [0xff; 16];
-const BGP_HEADER_LEN: usize = 19;
-const BGP_NOTIFICATION: u8 = 3;
-const BGP_ERROR_CEASE: u8 = 6;
-const BGP_CEASE_ADMIN_SHUTDOWN: u8 = 2;
-const BGP_CEASE_ADMIN_RESET: u8 = 4;
-const MAX_SHUTDOWN_MSG_LEN: usize = 255;
-const MIN_NOTIFICATION_LEN: usize = 21;
-const MAX_BGP_MESSAGE_LEN: usize = 4096; // RFC 4271 limit
+pub const BGP_MARKER: [u8; 16] = [0xff; 16];
+pub const BGP_HEADER_LEN: usize = 19;
+pub const BGP_OPEN: u8 = 1;
+pub const BGP_UPDATE: u8 = 2;
+pub const BGP_NOTIFICATION: u8 = 3;
+pub const BGP_KEEPALIVE: u8 = 4;
+pub const BGP_ROUTE_REFRESH: u8 = 5;
+pub const BGP_ERROR_CEASE: u8 = 6;
+pub const BGP_CEASE_ADMIN_SHUTDOWN: u8 = 2;
+pub const BGP_CEASE_ADMIN_RESET: u8 = 4;
+pub const BGP_CEASE_HARD_RESET: u8 = 9;
+pub const MAX_SHUTDOWN_MSG_LEN: usize = 255;
+/// Shutdown Communication limit before RFC 9003 raised it (RFC 8203 section 2).
+pub const RFC8203_MAX_SHUTDOWN_MSG_LEN: usize = 128;
+pub const MIN_NOTIFICATION_LEN: usize = 21;
+pub const MAX_BGP_MESSAGE_LEN: usize = 4096; // RFC 4271 limit
+pub const MAX_EXTENDED_MESSAGE_LEN: usize = 65535; // RFC 8654 limit
+
+/// Session state that changes how messages are framed.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SessionContext {
+    /// Both speakers advertised the Extended Message capability (RFC 8654),
+    /// allowing UPDATE, NOTIFICATION and ROUTE-REFRESH up to 65535 bytes.
+    #[serde(default)]
+    pub extended_message: bool,
+}
+
+impl SessionContext {
+    pub fn new(extended_message: bool) -> Self {
+        SessionContext { extended_message }
+    }
+
+    pub fn max_message_len(&self) -> usize {
+        if self.extended_message { MAX_EXTENDED_MESSAGE_LEN } else { MAX_BGP_MESSAGE_LEN }
+    }
+
+    /// Parse a hex dump of at most one maximum-size message for this session.
+    pub fn parse_hex(&self, hex_input: &str) -> Result<Vec<u8>, DecodeError> {
+        parse_hex_bounded(hex_input, self.max_message_len())
+    }
+}
 
 // Existing structures (unchanged for compatibility)
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
-#[wasm_bindgen]
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
 pub enum BgpCeaseSubcode {
     AdminShutdown = 2,
     AdminReset = 4,
@@ -22,7 +135,7 @@ pub enum BgpCeaseSubcode {
 
 impl BgpCeaseSubcode {
     #[inline(always)]
-    fn from_u8(value: u8) -> Option<Self> {
+    pub fn from_u8(value: u8) -> Option<Self> {
         match value {
             BGP_CEASE_ADMIN_SHUTDOWN => Some(BgpCeaseSubcode::AdminShutdown),
             BGP_CEASE_ADMIN_RESET => Some(BgpCeaseSubcode::AdminReset),
@@ -31,7 +144,7 @@ impl BgpCeaseSubcode {
     }
 
     #[inline(always)]
-    fn as_str(&self) -> &'static str {
+    pub fn as_str(&self) -> &'static str {
         match self {
             BgpCeaseSubcode::AdminShutdown => "Administrative Shutdown",
             BgpCeaseSubcode::AdminReset => "Administrative Reset",
@@ -44,6 +157,14 @@ impl BgpCeaseSubcode {
 pub struct EncodeRequest {
     pub message: String,
     pub subcode: u8,
+    #[serde(default)]
+    pub compatibility: ShutdownCompatibility,
+    /// Cut an over-long message at a character boundary instead of failing.
+    #[serde(default)]
+    pub truncate: bool,
+    /// Appended when truncating; defaults to "…".
+    #[serde(default)]
+    pub ellipsis: Option<String>,
 }
 
 #[derive(Serialize, Deserialize)]
@@ -51,6 +172,16 @@ pub struct EncodeResponse {
     pub hex: String,
     pub total_bytes: usize,
     pub message_bytes: usize,
+    #[serde(default)]
+    pub warnings: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct TemplateResponse {
+    pub message: String,
+    pub message_bytes: usize,
+    /// The built-in template's subcode; `None` for an ad-hoc template.
+    pub subcode: Option<u8>,
 }
 
 #[derive(Serialize, Deserialize)]
@@ -58,6 +189,27 @@ pub struct DecodeResponse {
     pub subcode: String,
     pub subcode_value: u8,
     pub message: String,
+    /// `message` with control, bidi and zero-width characters made visible.
+    pub sanitized: String,
+    pub safety: Vec<SafetyIssue>,
+    /// Caveats such as a length RFC 8203 speakers may reject.
+    pub notes: Vec<String>,
+    /// The length octet; absent if the data field was empty.
+    pub declared_length: Option<usize>,
+    pub actual_length: usize,
+    /// Everything the strict decoder would reject, decoded around.
+    pub diagnostics: Vec<Diagnostic>,
+    /// Tickets, links, AS numbers and times found in `message`.
+    pub entities: Vec<Entity>,
+}
+
+/// A decode problem that did not stop decoding.
+#[derive(Serialize, Deserialize)]
+pub struct Diagnostic {
+    pub code: String,
+    pub message: String,
+    pub offset: Option<usize>,
+    pub rfc: Option<String>,
 }
 
 #[derive(Serialize, Deserialize)]
@@ -67,6 +219,151 @@ pub struct UniversalEncodeRequest {
     pub data: Vec<u8>,
 }
 
+#[derive(Serialize, Deserialize)]
+pub struct OpenDecodeResponse {
+    pub open: Open,
+    pub asn: u32,
+    pub summary: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct RouteRefreshDecodeResponse {
+    pub route_refresh: RouteRefresh,
+    pub family: String,
+    pub subtype_name: String,
+    pub summary: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct UpdateDecodeResponse {
+    pub withdrawn_routes_length: usize,
+    pub withdrawn_routes: Vec<String>,
+    pub total_path_attribute_length: usize,
+    pub path_attributes: Vec<PathAttributeResponse>,
+    pub nlri: Vec<String>,
+    pub notes: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct PathAttributeResponse {
+    pub flags: u8,
+    pub flags_description: String,
+    pub type_code: u8,
+    pub type_name: String,
+    pub length: usize,
+    pub data_hex: String,
+    pub interpretation: String,
+    pub value: AttributeValue,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct StreamDecodeResponse {
+    pub messages: Vec<StreamMessageResponse>,
+    pub partial: Option<PartialMessage>,
+    pub framing_error: Option<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct StreamMessageResponse {
+    pub offset: usize,
+    pub length: usize,
+    pub message_type: u8,
+    pub type_name: String,
+    pub summary: Vec<String>,
+    pub error: Option<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct CaptureResponse {
+    pub format: CaptureFormat,
+    pub packets: usize,
+    pub flows: Vec<TcpFlow>,
+    pub messages: Vec<CapturedMessageResponse>,
+    pub warnings: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct CapturedMessageResponse {
+    pub flow: usize,
+    pub frame: usize,
+    pub timestamp: CaptureTime,
+    /// `timestamp` as RFC 3339.
+    pub time: String,
+    pub source: String,
+    pub destination: String,
+    pub message: StreamMessageResponse,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct MrtResponse {
+    pub records: usize,
+    pub messages: usize,
+    pub ceases: Vec<MrtCeaseResponse>,
+    pub warnings: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct MrtCeaseResponse {
+    pub offset: usize,
+    pub timestamp: CaptureTime,
+    /// `timestamp` as RFC 3339.
+    pub time: String,
+    pub sender_as: u32,
+    pub sender_ip: String,
+    pub receiver_as: u32,
+    pub receiver_ip: String,
+    pub subcode: u8,
+    pub subcode_name: String,
+    pub shutdown: Option<DecodeResponse>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct BmpResponse {
+    pub messages: Vec<BmpMessageResponse>,
+    pub warnings: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct BmpMessageResponse {
+    pub offset: usize,
+    pub length: usize,
+    pub message_type: u8,
+    pub type_name: String,
+    pub peer: Option<BmpPeerResponse>,
+    /// Statistics, TLVs and Peer Up addresses, one line each.
+    pub summary: Vec<String>,
+    /// The UPDATE of a Route Monitoring message, the OPENs of a Peer Up.
+    pub messages: Vec<StreamMessageResponse>,
+    pub peer_down: Option<PeerDownResponse>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct BmpPeerResponse {
+    pub peer_type: u8,
+    pub peer_type_name: String,
+    pub flags: u8,
+    pub post_policy: bool,
+    /// Route distinguisher notation, when there is one.
+    pub distinguisher: Option<String>,
+    pub address: String,
+    pub peer_as: u32,
+    pub bgp_id: String,
+    pub timestamp: CaptureTime,
+    /// `timestamp` as RFC 3339.
+    pub time: String,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct PeerDownResponse {
+    pub reason: u8,
+    pub reason_name: String,
+    /// The NOTIFICATION of reasons 1 and 3, as `decode_universal_notification` describes it.
+    pub notification: Option<UniversalDecodeResponse>,
+    /// Why that NOTIFICATION could not be decoded.
+    pub error: Option<String>,
+    pub fsm_event: Option<u16>,
+}
+
 #[derive(Serialize, Deserialize)]
 pub struct UniversalDecodeResponse {
     pub error_code: u8,
@@ -76,489 +373,255 @@ pub struct UniversalDecodeResponse {
     pub data_length: usize,
     pub data_hex: String,
     pub interpretation: String,
+    /// Caveats about the message as a whole, e.g. its size.
+    pub notes: Vec<String>,
+    /// The notification a Hard Reset (RFC 8538) carries in its data.
+    pub encapsulated: Option<Box<UniversalDecodeResponse>>,
 }
 
 // Secure integer parsing with explicit bounds checking
-fn parse_u8_bounded(s: &str, min: u8, max: u8, context: &str) -> Result<u8, String> {
-    let val: u8 = s.parse()
-        .map_err(|_| format!("Invalid {} value: must be a number", context))?;
-    if val < min || val > max {
-        return Err(format!("{} must be between {} and {}", context, min, max));
-    }
-    Ok(val)
+fn parse_u8_bounded(s: &str, min: u8, max: u8, context: &'static str) -> Result<u8, EncodeError> {
+    parse_bounded(s, min as u64, max as u64, context).map(|val| val as u8)
+}
+
+fn parse_u16_bounded(s: &str, min: u16, max: u16, context: &'static str) -> Result<u16, EncodeError> {
+    parse_bounded(s, min as u64, max as u64, context).map(|val| val as u16)
+}
+
+fn parse_u32_bounded(s: &str, min: u32, max: u32, context: &'static str) -> Result<u32, EncodeError> {
+    parse_bounded(s, min as u64, max as u64, context).map(|val| val as u32)
 }
 
-fn parse_u16_bounded(s: &str, min: u16, max: u16, context: &str) -> Result<u16, String> {
-    let val: u16 = s.parse()
-        .map_err(|_| format!("Invalid {} value: must be a number", context))?;
+fn parse_bounded(s: &str, min: u64, max: u64, context: &'static str) -> Result<u64, EncodeError> {
+    let val: u64 = s.parse()
+        .map_err(|_| EncodeError::InvalidNumber { field: context, value: s.to_string() })?;
     if val < min || val > max {
-        return Err(format!("{} must be between {} and {}", context, min, max));
+        return Err(EncodeError::OutOfRange { field: context, actual: val, min, max });
     }
     Ok(val)
 }
 
 // Secure hex parsing with bounds checking
-fn parse_hex_bounded(hex_str: &str, max_bytes: usize) -> Result<Vec<u8>, String> {
-    let clean: String = hex_str.chars()
-        .filter(|c| !c.is_whitespace() && *c != ':' && *c != '-')
+fn parse_hex_bounded(hex_str: &str, max_bytes: usize) -> Result<Vec<u8>, DecodeError> {
+    // Keep each digit's position in the original input for error reporting
+    let digits: Vec<(usize, char)> = hex_str.char_indices()
+        .filter(|(_, c)| !c.is_whitespace() && *c != ':' && *c != '-')
         .collect();
     
-    if clean.is_empty() {
+    if digits.is_empty() {
         return Ok(Vec::new());
     }
     
-    if clean.len() % 2 != 0 {
-        return Err("Hex string must have even number of characters".to_string());
+    if !digits.len().is_multiple_of(2) {
+        return Err(DecodeError::OddHexLength { digits: digits.len() });
     }
     
-    let byte_count = clean.len() / 2;
+    let byte_count = digits.len() / 2;
     if byte_count > max_bytes {
-        return Err(format!("Hex data too long: {} bytes (max {})", byte_count, max_bytes));
+        return Err(DecodeError::HexTooLong { actual: byte_count, max: max_bytes });
     }
     
     let mut bytes = Vec::with_capacity(byte_count);
-    for i in (0..clean.len()).step_by(2) {
-        let byte_str = &clean[i..i+2];
-        let byte = u8::from_str_radix(byte_str, 16)
-            .map_err(|_| format!("Invalid hex character in: {}", byte_str))?;
+    for pair in digits.chunks_exact(2) {
+        let mut byte = 0u8;
+        for &(offset, c) in pair {
+            let nibble = c.to_digit(16)
+                .ok_or(DecodeError::InvalidHexDigit { offset, found: c })?;
+            byte = (byte << 4) | nibble as u8;
+        }
         bytes.push(byte);
     }
     
     Ok(bytes)
 }
 
+/// Parse a hex dump of at most one maximum-size BGP message.
+/// Whitespace, `:` and `-` separators are ignored.
+pub fn parse_hex(hex_input: &str) -> Result<Vec<u8>, DecodeError> {
+    parse_hex_bounded(hex_input, MAX_BGP_MESSAGE_LEN)
+}
+
 // Secure BGP message validation
-fn validate_bgp_message(bytes: &[u8]) -> Result<(), String> {
-    if bytes.len() < MIN_NOTIFICATION_LEN {
-        return Err(format!("Message too short: {} bytes (minimum {})", 
-                          bytes.len(), MIN_NOTIFICATION_LEN));
+fn validate_bgp_message(bytes: &[u8], context: &SessionContext) -> Result<(), DecodeError> {
+    validate_message(bytes, BGP_NOTIFICATION, MIN_NOTIFICATION_LEN, context.max_message_len())
+}
+
+// Marker, length and type checks shared by every message type
+fn validate_message(bytes: &[u8], msg_type: u8, min_len: usize, max_len: usize) -> Result<(), DecodeError> {
+    if bytes.len() < min_len {
+        return Err(DecodeError::MessageTooShort { actual: bytes.len(), min: min_len });
     }
     
-    if bytes.len() > MAX_BGP_MESSAGE_LEN {
-        return Err(format!("Message too long: {} bytes (maximum {})", 
-                          bytes.len(), MAX_BGP_MESSAGE_LEN));
+    if bytes.len() > max_len {
+        return Err(DecodeError::MessageTooLong { actual: bytes.len(), max: max_len });
     }
     
     // Validate BGP marker
-    if !bytes[..16].iter().all(|&b| b == 0xff) {
-        return Err("Invalid BGP marker: must be 16 bytes of 0xFF".to_string());
+    if let Some(offset) = bytes[..16].iter().position(|&b| b != 0xff) {
+        return Err(DecodeError::BadMarker { offset });
     }
     
     // Validate length field
     let declared_length = ((bytes[16] as usize) << 8) | (bytes[17] as usize);
     if declared_length != bytes.len() {
-        return Err(format!("Length mismatch: header declares {} bytes, got {}", 
-                          declared_length, bytes.len()));
+        return Err(DecodeError::LengthMismatch { declared: declared_length, received: bytes.len() });
     }
     
-    if declared_length < MIN_NOTIFICATION_LEN || declared_length > MAX_BGP_MESSAGE_LEN {
-        return Err(format!("Invalid declared length: {} (must be {}-{})", 
-                          declared_length, MIN_NOTIFICATION_LEN, MAX_BGP_MESSAGE_LEN));
+    if !(min_len..=max_len).contains(&declared_length) {
+        return Err(DecodeError::BadDeclaredLength {
+            declared: declared_length,
+            min: min_len,
+            max: max_len,
+        });
     }
     
     // Validate message type
-    if bytes[18] != BGP_NOTIFICATION {
-        return Err(format!("Not a notification message: type {} (expected {})", 
-                          bytes[18], BGP_NOTIFICATION));
+    if bytes[18] != msg_type {
+        return Err(DecodeError::UnexpectedMessageType { expected: msg_type, actual: bytes[18] });
     }
     
     Ok(())
 }
 
-// Main encode function (backward compatible)
-#[wasm_bindgen]
-pub fn encode_shutdown_message(request: JsValue) -> Result<JsValue, JsValue> {
-    let req: EncodeRequest = serde_wasm_bindgen::from_value(request)
-        .map_err(|e| JsValue::from_str(&format!("Invalid request: {}", e)))?;
-
-    let _subcode = BgpCeaseSubcode::from_u8(req.subcode)
-        .ok_or_else(|| JsValue::from_str("Invalid subcode: must be 2 or 4"))?;
-
-    let utf8_bytes = req.message.as_bytes();
-    if utf8_bytes.len() > MAX_SHUTDOWN_MSG_LEN {
-        return Err(JsValue::from_str(&format!(
-            "Message exceeds {} bytes (got {})", MAX_SHUTDOWN_MSG_LEN, utf8_bytes.len()
-        )));
-    }
-
-    // Calculate total length with overflow check
-    let data_len = utf8_bytes.len() + 1; // +1 for length byte
-    let total_len = BGP_HEADER_LEN.checked_add(2) // error + subcode
-        .and_then(|n| n.checked_add(data_len))
-        .ok_or_else(|| JsValue::from_str("Message too large"))?;
-    
-    if total_len > MAX_BGP_MESSAGE_LEN {
-        return Err(JsValue::from_str("Message would exceed BGP maximum length"));
+// Prepend the BGP header to a message body
+fn frame_message(msg_type: u8, body: &[u8], max_len: usize) -> Result<Vec<u8>, EncodeError> {
+    let max_body = max_len - BGP_HEADER_LEN;
+    if body.len() > max_body {
+        return Err(EncodeError::DataTooLong { actual: body.len(), max: max_body });
     }
 
+    let total_len = BGP_HEADER_LEN + body.len();
     let mut message = Vec::with_capacity(total_len);
     message.extend_from_slice(&BGP_MARKER);
-    
-    let total_length = total_len as u16;
-    message.push((total_length >> 8) as u8);
-    message.push((total_length & 0xff) as u8);
-    message.push(BGP_NOTIFICATION);
-    message.push(BGP_ERROR_CEASE);
-    message.push(req.subcode);
-    message.push(utf8_bytes.len() as u8);
-    message.extend_from_slice(utf8_bytes);
-
-    let response = EncodeResponse {
-        hex: to_hex(&message),
-        total_bytes: message.len(),
-        message_bytes: utf8_bytes.len(),
-    };
-
-    serde_wasm_bindgen::to_value(&response)
-        .map_err(|e| JsValue::from_str(&format!("Serialization error: {}", e)))
-}
-
-// Main decode function (backward compatible)
-#[wasm_bindgen]
-pub fn decode_shutdown_message(hex_input: &str) -> Result<JsValue, JsValue> {
-    let clean: String = hex_input.chars()
-        .filter(|c| !c.is_whitespace() && *c != ':' && *c != '-')
-        .collect();
-
-    let bytes = parse_hex_bounded(&clean, MAX_BGP_MESSAGE_LEN)
-        .map_err(|e| JsValue::from_str(&e))?;
-
-    validate_bgp_message(&bytes)
-        .map_err(|e| JsValue::from_str(&e))?;
-
-    let error_code = bytes[19];
-    if error_code != BGP_ERROR_CEASE {
-        return Err(JsValue::from_str(&format!(
-            "Not a Cease error (code={})", error_code
-        )));
-    }
-
-    let subcode = bytes[20];
-    let subcode_enum = BgpCeaseSubcode::from_u8(subcode)
-        .ok_or_else(|| JsValue::from_str(&format!("Unknown subcode: {}", subcode)))?;
-
-    if bytes.len() < 22 {
-        return Err(JsValue::from_str("Missing shutdown message length byte"));
-    }
-
-    let text_length = bytes[21] as usize;
-    let expected_total = MIN_NOTIFICATION_LEN + text_length;
-    
-    if bytes.len() != expected_total {
-        return Err(JsValue::from_str(&format!(
-            "Length mismatch: expected {} bytes, got {}", expected_total, bytes.len()
-        )));
-    }
-
-    let message = if text_length == 0 {
-        String::new()
-    } else {
-        let msg_bytes = &bytes[22..22 + text_length];
-        std::str::from_utf8(msg_bytes)
-            .map_err(|_| JsValue::from_str("Invalid UTF-8 in message"))?
-            .to_string()
-    };
-
-    let response = DecodeResponse {
-        subcode: subcode_enum.as_str().to_string(),
-        subcode_value: subcode,
-        message,
-    };
-
-    serde_wasm_bindgen::to_value(&response)
-        .map_err(|e| JsValue::from_str(&format!("Serialization error: {}", e)))
-}
-
-// Universal encoder with proper validation
-#[wasm_bindgen]
-pub fn encode_universal_notification(request: JsValue) -> Result<JsValue, JsValue> {
-    let req: UniversalEncodeRequest = serde_wasm_bindgen::from_value(request)
-        .map_err(|e| JsValue::from_str(&format!("Invalid request: {}", e)))?;
-
-    if req.error_code == 0 || req.error_code > 6 {
-        return Err(JsValue::from_str("Invalid error code: must be 1-6"));
-    }
-
-    // Bounds checking for data
-    if req.data.len() > MAX_BGP_MESSAGE_LEN - MIN_NOTIFICATION_LEN {
-        return Err(JsValue::from_str("Data too large for BGP message"));
-    }
-
-    let total_len = BGP_HEADER_LEN.checked_add(2)
-        .and_then(|n| n.checked_add(req.data.len()))
-        .ok_or_else(|| JsValue::from_str("Message too large"))?;
-
-    if total_len > MAX_BGP_MESSAGE_LEN {
-        return Err(JsValue::from_str("Message would exceed BGP maximum length"));
-    }
+    message.extend_from_slice(&(total_len as u16).to_be_bytes());
+    message.push(msg_type);
+    message.extend_from_slice(body);
 
-    let mut notification = Vec::with_capacity(total_len);
-    notification.extend_from_slice(&BGP_MARKER);
-
-    let total_length = total_len as u16;
-    notification.push((total_length >> 8) as u8);
-    notification.push((total_length & 0xff) as u8);
-    notification.push(BGP_NOTIFICATION);
-    notification.push(req.error_code);
-    notification.push(req.subcode);
-    notification.extend_from_slice(&req.data);
-
-    let response = EncodeResponse {
-        hex: to_hex(&notification),
-        total_bytes: notification.len(),
-        message_bytes: req.data.len(),
-    };
-
-    serde_wasm_bindgen::to_value(&response)
-        .map_err(|e| JsValue::from_str(&format!("Serialization error: {}", e)))
+    Ok(message)
 }
 
-#[wasm_bindgen]
-pub fn decode_universal_notification(hex_input: &str) -> Result<JsValue, JsValue> {
-    let clean: String = hex_input.chars()
-        .filter(|c| !c.is_whitespace() && *c != ':' && *c != '-')
-        .collect();
-
-    let bytes = parse_hex_bounded(&clean, MAX_BGP_MESSAGE_LEN)
-        .map_err(|e| JsValue::from_str(&e))?;
-
-    validate_bgp_message(&bytes)
-        .map_err(|e| JsValue::from_str(&e))?;
-
-    let error_code = bytes[19];
-    let subcode = bytes[20];
-    let data_bytes = if bytes.len() > MIN_NOTIFICATION_LEN {
-        bytes[21..].to_vec()
-    } else {
-        vec![]
-    };
-
-    let (error_name, subcode_name) = get_error_names(error_code, subcode);
-    let interpretation = interpret_data(error_code, subcode, &data_bytes);
-
-    let response = UniversalDecodeResponse {
-        error_code,
-        error_name,
-        subcode,
-        subcode_name,
-        data_length: data_bytes.len(),
-        data_hex: to_hex(&data_bytes),
-        interpretation,
-    };
-
-    serde_wasm_bindgen::to_value(&response)
-        .map_err(|e| JsValue::from_str(&format!("Serialization error: {}", e)))
-}
-
-// RFC-compliant data field creator with security hardening
-#[wasm_bindgen]
-pub fn create_notification_with_data(error_code: u8, subcode: u8, data_type: &str, data_value: &str) -> Result<JsValue, JsValue> {
-    if error_code == 0 || error_code > 6 {
-        return Err(JsValue::from_str("Invalid error code: must be 1-6"));
+// Messages above 4096 bytes only exist on sessions with Extended Message
+fn extended_length_note(length: usize) -> Option<String> {
+    if length <= MAX_BGP_MESSAGE_LEN {
+        return None;
     }
+    Some(format!(
+        "Message is {} bytes, over the {} byte limit: only valid with the Extended Message capability (RFC 8654)",
+        length, MAX_BGP_MESSAGE_LEN
+    ))
+}
 
-    let mut data = Vec::new();
-
-    match (error_code, subcode, data_type) {
-        // Message Header Errors
-        (1, 2, "length") => {
-            let length = parse_u16_bounded(data_value, 0, 65535, "length")
-                .map_err(|e| JsValue::from_str(&e))?;
-            data.push((length >> 8) as u8);
-            data.push((length & 0xff) as u8);
-        },
-        (1, 3, "type") => {
-            let msg_type = parse_u8_bounded(data_value, 0, 255, "message type")
-                .map_err(|e| JsValue::from_str(&e))?;
-            data.push(msg_type);
-        },
-
-        // OPEN Message Errors
-        (2, 1, "version") => {
-            let version = parse_u16_bounded(data_value, 1, 255, "BGP version")
-                .map_err(|e| JsValue::from_str(&e))?;
-            data.push((version >> 8) as u8);
-            data.push((version & 0xff) as u8);
-        },
-
-        // UPDATE Message Errors
-        (3, 3, "attribute") => {
-            let attr_type = parse_u8_bounded(data_value, 1, 255, "attribute type")
-                .map_err(|e| JsValue::from_str(&e))?;
-            data.push(attr_type);
-        },
-
-        // FSM Errors
-        (5, 1, "message_type") | (5, 2, "message_type") | (5, 3, "message_type") => {
-            let msg_type = parse_u8_bounded(data_value, 1, 5, "message type")
-                .map_err(|e| JsValue::from_str(&e))?;
-            data.push(msg_type);
-        },
-
-        // Cease with shutdown message
-        (6, 2, "message") | (6, 4, "message") => {
-            let utf8_bytes = data_value.as_bytes();
-            if utf8_bytes.len() > MAX_SHUTDOWN_MSG_LEN {
-                return Err(JsValue::from_str(&format!(
-                    "Shutdown message too long: {} bytes (max {})", 
-                    utf8_bytes.len(), MAX_SHUTDOWN_MSG_LEN
-                )));
-            }
-            data.push(utf8_bytes.len() as u8);
-            data.extend_from_slice(utf8_bytes);
-        },
-
-        // Raw hex data
-        (_, _, "hex") => {
-            data = parse_hex_bounded(data_value, MAX_BGP_MESSAGE_LEN - MIN_NOTIFICATION_LEN)
-                .map_err(|e| JsValue::from_str(&e))?;
-        },
-
-        _ => {
-            if !data_value.is_empty() {
-                return Err(JsValue::from_str(
-                    "This error/subcode combination doesn't support additional data"
-                ));
-            }
-        }
+/// Name of a BGP message type as used in RFC 4271 and its extensions.
+pub fn message_type_name(msg_type: u8) -> &'static str {
+    match msg_type {
+        BGP_OPEN => "OPEN",
+        BGP_UPDATE => "UPDATE",
+        BGP_NOTIFICATION => "NOTIFICATION",
+        BGP_KEEPALIVE => "KEEPALIVE",
+        BGP_ROUTE_REFRESH => "ROUTE-REFRESH",
+        _ => "Unknown",
     }
-
-    let request = UniversalEncodeRequest {
-        error_code,
-        subcode,
-        data,
-    };
-
-    encode_universal_notification(serde_wasm_bindgen::to_value(&request)?)
 }
 
 // Utility functions
-#[wasm_bindgen]
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
 pub fn is_hex(input: &str) -> bool {
     let clean: String = input.chars()
         .filter(|c| !c.is_whitespace() && *c != ':' && *c != '-')
         .collect();
 
     !clean.is_empty() 
-        && clean.len() % 2 == 0 
-        && clean.len() <= MAX_BGP_MESSAGE_LEN * 2 // Prevent DoS
+        && clean.len().is_multiple_of(2)
+        && clean.len() <= MAX_EXTENDED_MESSAGE_LEN * 2 // Prevent DoS
         && clean.chars().all(|c| c.is_ascii_hexdigit())
 }
 
-#[wasm_bindgen]
-pub fn get_subcodes() -> JsValue {
-    let subcodes = vec![
-        (BGP_CEASE_ADMIN_SHUTDOWN, "Administrative Shutdown"),
-        (BGP_CEASE_ADMIN_RESET, "Administrative Reset"),
-    ];
-    serde_wasm_bindgen::to_value(&subcodes).unwrap()
-}
-
 // Helper functions (implementation details in next part due to length...)
 fn get_error_names(error_code: u8, subcode: u8) -> (String, String) {
-    let error_name = match error_code {
-        1 => "Message Header Error",
-        2 => "OPEN Message Error",
-        3 => "UPDATE Message Error", 
-        4 => "Hold Timer Expired",
-        5 => "Finite State Machine Error",
-        6 => "Cease",
-        _ => "Unknown Error",
-    }.to_string();
-
-    let subcode_name = match (error_code, subcode) {
-        (1, 1) => "Connection Not Synchronized",
-        (1, 2) => "Bad Message Length",
-        (1, 3) => "Bad Message Type",
-        (2, 0) => "Unspecific",
-        (2, 1) => "Unsupported Version Number",
-        (2, 2) => "Bad Peer AS",
-        (2, 3) => "Bad BGP Identifier",
-        (2, 4) => "Unsupported Optional Parameter",
-        (2, 6) => "Unacceptable Hold Time",
-        (2, 7) => "Unsupported Capability",
-        (3, 0) => "Unspecific",
-        (3, 1) => "Malformed Attribute List",
-        (3, 2) => "Unrecognized Well-known Attribute",
-        (3, 3) => "Missing Well-known Attribute",
-        (3, 4) => "Attribute Flags Error",
-        (3, 5) => "Attribute Length Error",
-        (3, 6) => "Invalid ORIGIN Attribute",
-        (3, 8) => "Invalid NEXT_HOP Attribute",
-        (3, 9) => "Optional Attribute Error",
-        (3, 10) => "Invalid Network Field",
-        (3, 11) => "Malformed AS_PATH",
-        (4, 0) => "Unspecific",
-        (5, 0) => "Unspecified Error",
-        (5, 1) => "Receive Unexpected Message in OpenSent State",
-        (5, 2) => "Receive Unexpected Message in OpenConfirm State", 
-        (5, 3) => "Receive Unexpected Message in Established State",
-        (6, 0) => "Unspecific",
-        (6, 1) => "Maximum Number of Prefixes Reached",
-        (6, 2) => "Administrative Shutdown",
-        (6, 3) => "Peer De-configured",
-        (6, 4) => "Administrative Reset",
-        (6, 5) => "Connection Rejected",
-        (6, 6) => "Other Configuration Change",
-        (6, 7) => "Connection Collision Resolution",
-        (6, 8) => "Out of Resources",
-        (6, 9) => "Hard Reset",
-        _ => "Unknown Subcode",
-    }.to_string();
-
-    (error_name, subcode_name)
-}
-
-fn interpret_data(error_code: u8, subcode: u8, data: &[u8]) -> String {
+    let error_name = error_code_entry(error_code).map_or("Unknown Error", |entry| entry.name);
+    let subcode_name = subcode_entry(error_code, subcode).map_or("Unknown Subcode", |entry| entry.name);
+    (error_name.to_string(), subcode_name.to_string())
+}
+
+fn interpret_data(error_code: u8, subcode: u8, data: &[u8], context: &SessionContext) -> String {
     match (error_code, subcode) {
         (1, 2) if data.len() >= 2 => {
-            let bad_length = ((data[0] as u16) << 8) | (data[1] as u16);
-            format!("Bad message length: {} (valid range: 19-4096)", bad_length)
+            let bad_length = ((data[0] as usize) << 8) | (data[1] as usize);
+            if context.extended_message {
+                format!("Bad message length: {} (valid range: 19-{}, Extended Message)", bad_length, MAX_EXTENDED_MESSAGE_LEN)
+            } else if bad_length > MAX_BGP_MESSAGE_LEN {
+                format!(
+                    "Bad message length: {} (valid range: 19-{}; up to {} with Extended Message)",
+                    bad_length, MAX_BGP_MESSAGE_LEN, MAX_EXTENDED_MESSAGE_LEN
+                )
+            } else {
+                format!("Bad message length: {} (valid range: 19-{})", bad_length, MAX_BGP_MESSAGE_LEN)
+            }
         },
-        (1, 3) if data.len() >= 1 => {
-            let msg_type = match data[0] {
-                1 => "OPEN", 2 => "UPDATE", 3 => "NOTIFICATION", 4 => "KEEPALIVE",
-                5 => "ROUTE-REFRESH", _ => "Unknown"
-            };
-            format!("Bad message type: {} ({})", data[0], msg_type)
+        (1, 3) if !data.is_empty() => {
+            format!("Bad message type: {} ({})", data[0], message_type_name(data[0]))
         },
         (2, 1) if data.len() >= 2 => {
             let version = ((data[0] as u16) << 8) | (data[1] as u16);
             format!("Unsupported BGP version, local supports: {}", version)
         },
-        (3, 3) if data.len() >= 1 => {
-            let attr_type = data[0];
-            let attr_name = match attr_type {
-                1 => "ORIGIN", 2 => "AS_PATH", 3 => "NEXT_HOP", _ => "Unknown"
-            };
-            format!("Missing well-known attribute: {} ({})", attr_type, attr_name)
+        (2, 2) | (2, 4) | (2, 6) | (2, 7) if !data.is_empty() => open::describe_notification_data(subcode, data),
+        (3, 2) | (3, 4) | (3, 5) | (3, 6) | (3, 8) | (3, 9) | (3, 10) | (3, 11) if !data.is_empty() => {
+            update::describe_notification_data(subcode, data)
         },
-        (5, 1) | (5, 2) | (5, 3) if data.len() >= 1 => {
-            let msg_type = match data[0] {
-                1 => "OPEN", 2 => "UPDATE", 3 => "NOTIFICATION", 4 => "KEEPALIVE",
-                5 => "ROUTE-REFRESH", _ => "Unknown"
-            };
+        (3, 3) if !data.is_empty() => {
+            format!("Missing well-known attribute: {} ({})", data[0], attribute_name(data[0]))
+        },
+        (5, 1) | (5, 2) | (5, 3) if !data.is_empty() => {
+            let msg_type = message_type_name(data[0]);
             let state = match subcode {
                 1 => "OpenSent", 2 => "OpenConfirm", 3 => "Established", _ => "Unknown"
             };
             format!("Unexpected {} message in {} state", msg_type, state)
         },
+        // RFC 4486 section 4: AFI, SAFI and the configured upper bound
+        (6, 1) if data.len() == 7 => {
+            let afi = ((data[0] as u16) << 8) | (data[1] as u16);
+            let limit = u32::from_be_bytes([data[3], data[4], data[5], data[6]]);
+            format!("{}, limit {}", family_name(afi, data[2]), limit)
+        },
+        // RFC 8538 section 3: the data is the NOTIFICATION being replaced
+        (6, 9) if data.len() >= 2 => {
+            let (error_name, subcode_name) = get_error_names(data[0], data[1]);
+            let inner = if (data[0], data[1]) == (BGP_ERROR_CEASE, BGP_CEASE_HARD_RESET) {
+                "nested Hard Reset".to_string()
+            } else {
+                interpret_data(data[0], data[1], &data[2..], context)
+            };
+            format!("Hard Reset of {} / {} ({}/{}): {}", error_name, subcode_name, data[0], data[1], inner)
+        },
         (6, 2) | (6, 4) if !data.is_empty() => {
-            if data.len() >= 1 {
-                let msg_len = data[0] as usize;
-                if data.len() >= 1 + msg_len && msg_len > 0 {
-                    match std::str::from_utf8(&data[1..1 + msg_len]) {
-                        Ok(msg) => format!("Shutdown message: \"{}\"", msg),
-                        Err(_) => "Invalid UTF-8 in shutdown message".to_string(),
-                    }
-                } else if msg_len == 0 {
-                    "Empty shutdown message".to_string()
-                } else {
-                    "Truncated shutdown message".to_string()
-                }
+            let shutdown = LenientShutdown::from_data(subcode, data);
+            let text = analyze_text(&shutdown.text).sanitized;
+            if !shutdown.is_clean() {
+                format!("Malformed shutdown message, best effort: \"{}\"", text)
+            } else if text.is_empty() {
+                "Empty shutdown message".to_string()
             } else {
-                "No shutdown message data".to_string()
+                format!("Shutdown message: \"{}\"", text)
+            }
+        },
+        // RFC 9687: the sender could not write to the session for the send hold time
+        (8, _) if data.is_empty() => "Peer stopped reading: nothing could be sent for the send hold time".to_string(),
+        // RFC 7313 section 5: the data is the complete offending ROUTE-REFRESH
+        (7, 1) if data.len() >= BGP_HEADER_LEN => {
+            let declared = ((data[16] as usize) << 8) | (data[17] as usize);
+            let mut text = format!("Rejected ROUTE-REFRESH: declared length {}, {} bytes received", declared, data.len());
+            if data.len() >= ROUTE_REFRESH_LEN {
+                let afi = ((data[19] as u16) << 8) | (data[20] as u16);
+                text.push_str(&format!(
+                    ", {}, subtype {} ({})",
+                    family_name(afi, data[22]),
+                    data[21],
+                    route_refresh_subtype_name(data[21])
+                ));
             }
+            text
         },
         _ => {
             if data.is_empty() {
@@ -571,7 +634,7 @@ fn interpret_data(error_code: u8, subcode: u8, data: &[u8]) -> String {
 }
 
 #[inline]
-fn to_hex(bytes: &[u8]) -> String {
+pub fn to_hex(bytes: &[u8]) -> String {
     bytes.iter()
         .map(|b| format!("{:02x}", b))
         .collect::<Vec<_>>()
@@ -612,16 +675,16 @@ mod tests {
     fn test_bgp_validation_functions() {
         // Test valid BGP notification
         let valid_message = create_test_notification_bytes(4, 0, &[]);
-        assert!(validate_bgp_message(&valid_message).is_ok());
+        assert!(validate_bgp_message(&valid_message, &SessionContext::default()).is_ok());
 
         // Test message too short
         let short_message = vec![0xff; 10];
-        assert!(validate_bgp_message(&short_message).is_err());
+        assert!(validate_bgp_message(&short_message, &SessionContext::default()).is_err());
 
         // Test invalid marker
         let mut invalid_marker = create_test_notification_bytes(4, 0, &[]);
         invalid_marker[0] = 0x00;
-        assert!(validate_bgp_message(&invalid_marker).is_err());
+        assert!(validate_bgp_message(&invalid_marker, &SessionContext::default()).is_err());
     }
 
     #[test]
@@ -674,7 +737,7 @@ mod tests {
         assert_eq!(subcode_name, "Unspecific");
 
         // Test interpret_data
-        let interpretation = interpret_data(1, 2, &[0x00, 0x10]); // Bad length
+        let interpretation = interpret_data(1, 2, &[0x00, 0x10], &SessionContext::default()); // Bad length
         assert!(interpretation.contains("Bad message length: 16"));
     }
 
@@ -688,59 +751,43 @@ mod tests {
         assert!(!is_hex("")); // Empty
     }
 
-    // WASM-specific tests - only run when targeting WASM
-    #[cfg(target_arch = "wasm32")]
-    mod wasm_tests {
-        use super::*;
-        use wasm_bindgen_test::*;
+    #[test]
+    fn test_mikrotik_format_corrected() {
+        let decoded = Notification::from_hex("ffffffffffffffffffffffffffffffff0015030400").unwrap();
+        assert_eq!(decoded.error_code, 4); // Hold Timer Expired
+        assert_eq!(decoded.subcode, 0);    // Unspecific
 
-        #[wasm_bindgen_test]
-        fn test_mikrotik_format_corrected() {
-            let result = decode_universal_notification("ffffffffffffffffffffffffffffffff0015030400");
-            assert!(result.is_ok());
+        let response = UniversalDecodeResponse::from(&decoded);
+        assert_eq!(response.error_name, "Hold Timer Expired");
+        assert_eq!(response.data_length, 0);
+    }
 
-            let decoded: UniversalDecodeResponse = serde_wasm_bindgen::from_value(result.unwrap()).unwrap();
-            assert_eq!(decoded.error_code, 4); // Hold Timer Expired
-            assert_eq!(decoded.subcode, 0);    // Unspecific
-        }
+    #[test]
+    fn test_encode_decode_round_trip() {
+        let comm = ShutdownCommunication::new(BgpCeaseSubcode::AdminShutdown, "Test shutdown");
+        let hex = to_hex(&comm.to_bytes().unwrap());
 
-        #[wasm_bindgen_test]
-        fn test_encode_decode_round_trip() {
-            let req = EncodeRequest {
-                message: "Test shutdown".to_string(),
-                subcode: 2,
-            };
-            
-            let encoded = encode_shutdown_message(serde_wasm_bindgen::to_value(&req).unwrap()).unwrap();
-            let response: EncodeResponse = serde_wasm_bindgen::from_value(encoded).unwrap();
-            
-            let decoded = decode_shutdown_message(&response.hex).unwrap();
-            let decoded_response: DecodeResponse = serde_wasm_bindgen::from_value(decoded).unwrap();
-            
-            assert_eq!(decoded_response.message, "Test shutdown");
-            assert_eq!(decoded_response.subcode_value, 2);
-        }
+        let notification = Notification::from_hex(&hex).unwrap();
+        let decoded = ShutdownCommunication::from_notification(&notification).unwrap();
 
-        #[wasm_bindgen_test]
-        fn test_bounds_checking_wasm() {
-            // Test message too long
-            let long_data = vec![0u8; 5000];
-            let req = UniversalEncodeRequest {
-                error_code: 1,
-                subcode: 1,
-                data: long_data,
-            };
-            let result = encode_universal_notification(serde_wasm_bindgen::to_value(&req).unwrap());
-            assert!(result.is_err());
-        }
+        assert_eq!(decoded.message, "Test shutdown");
+        assert_eq!(decoded.subcode as u8, 2);
+    }
 
-        #[wasm_bindgen_test]
-        fn test_create_notification_with_data_wasm() {
-            let result = create_notification_with_data(1, 2, "length", "999999");
-            assert!(result.is_ok());
-            
-            let result = create_notification_with_data(1, 2, "length", "99999");
-            assert!(result.is_ok());
-        }
+    #[test]
+    fn test_bounds_checking_encode() {
+        // Test message too long
+        let notification = Notification::new(1, 1, vec![0u8; 5000]);
+        assert!(notification.to_bytes().is_err());
+    }
+
+    #[test]
+    fn test_create_notification_with_data() {
+        // Bad Message Length data is a 2-octet length
+        assert!(Notification::with_data(1, 2, "length", "4097").is_ok());
+        assert!(Notification::with_data(1, 2, "length", "65535").is_ok());
+
+        assert!(Notification::with_data(1, 2, "length", "999999").is_err());
+        assert!(Notification::with_data(1, 2, "length", "99999").is_err());
     }
 }
@@ -0,0 +1,814 @@
+//! `chatbgp` command-line tool: encode, decode and explain BGP NOTIFICATION
+//! messages with the same codec the web frontend uses.
+
+use std::io::Read;
+use std::net::{IpAddr, SocketAddr};
+use std::process::ExitCode;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::Serialize;
+
+use chatbgp::{
+    expand_template, find_template, to_hex, BgpCeaseSubcode, BmpResponse, BmpStream, Capture,
+    CaptureResponse, CaptureTime, DecodeError, EncodeError, EncodeResponse, Keepalive,
+    LenientShutdown, MessageStream, MrtCeases, MrtResponse, MrtSession, Notification, Open,
+    OpenDecodeResponse, RouteRefresh, RouteRefreshDecodeResponse, SessionContext,
+    ShutdownCommunication, ShutdownCompatibility, StreamDecodeResponse, SyntheticSession,
+    TemplateValues, UniversalDecodeResponse, Update, UpdateDecodeResponse, BGP_KEEPALIVE, BGP_OPEN,
+    BGP_ROUTE_REFRESH, BGP_UPDATE, DEFAULT_ELLIPSIS, MIN_NOTIFICATION_LEN,
+};
+
+const USAGE: &str = "\
+Usage:
+  chatbgp encode [--subcode <2|4>] [--compat <rfc9003|rfc8203-warn|rfc8203>]
+                 [--truncate [--ellipsis <TEXT>]] [--json] [MESSAGE]
+  chatbgp encode --template <NAME|TEXT> [--asn <N>] [--nick <NICK>] [--ticket <ID>]
+                 [--until <TIME|DURATION>] [--contact <CONTACT>] [--json]
+  chatbgp encode --error-code <N> --subcode <N> [--data-type <TYPE>] [--extended] [--json] [VALUE]
+  chatbgp decode [--extended] [--json] [HEX]   (any message type, or several back to back)
+  chatbgp explain [--extended] [--json] [HEX]
+  chatbgp capture [--extended] [--json] [FILE]   (pcap or pcapng, TCP port 179)
+  chatbgp mrt [--extended] [--json] [FILE]       (Cease notifications in an MRT dump)
+  chatbgp bmp [--extended] [--json] [FILE]       (a recorded BMP feed, RFC 7854)
+
+encode and decode also take --pcap <FILE> [--client <ADDR:PORT>] [--server <ADDR:PORT>]
+to write the message(s) into a pcap of a made-up TCP session, and
+--mrt <FILE> [--peer-as <N>] [--peer-ip <ADDR>] [--local-ip <ADDR>] [--ifindex <N>]
+to write them as BGP4MP_MESSAGE_AS4 records of an MRT dump; --asn sets the local AS.
+
+MESSAGE, VALUE and HEX are read from stdin when not given as arguments, and
+so is the capture, MRT or BMP file when FILE is not given. MRT dumps may be
+gzip or bzip2 compressed.
+--extended allows messages up to 65535 bytes (RFC 8654 Extended Message).
+--compat rfc8203 limits shutdown messages to 128 bytes; rfc8203-warn only warns.
+--truncate cuts a message over the limit at a character boundary and appends
+the ellipsis (default \"\u{2026}\") instead of failing.
+--template takes a built-in template (maintenance, reboot, decommission) or
+template text with {asn}, {nick}, {ticket}, {until} and {contact}; --until is
+an RFC 3339 time or a duration such as 2h30m.
+Data types: length, type, version, attribute, message_type, message, hex,
+prefix_limit (6/1, VALUE is \"<afi> <safi> <limit>\", e.g. \"ipv4 unicast 1000\"),
+shutdown and reset (6/9 Hard Reset around an Administrative Shutdown or Reset,
+VALUE is its shutdown communication)";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Command {
+    Encode,
+    Decode,
+    Explain,
+    Capture,
+    Mrt,
+    Bmp,
+}
+
+#[derive(Debug, Default, PartialEq, Eq)]
+struct Options {
+    json: bool,
+    extended: bool,
+    error_code: Option<u8>,
+    subcode: Option<u8>,
+    data_type: Option<String>,
+    compatibility: ShutdownCompatibility,
+    truncate: bool,
+    ellipsis: Option<String>,
+    template: Option<String>,
+    values: TemplateValues,
+    pcap: Option<String>,
+    session: SyntheticSession,
+    mrt: Option<String>,
+    mrt_session: MrtSession,
+    input: Option<String>,
+}
+
+impl Options {
+    fn context(&self) -> SessionContext {
+        SessionContext::new(self.extended)
+    }
+}
+
+#[derive(Serialize)]
+struct ErrorOutput {
+    code: &'static str,
+    message: String,
+    offset: Option<usize>,
+    expected: Option<usize>,
+    actual: Option<usize>,
+    rfc: Option<&'static str>,
+}
+
+// One annotated span of an explained message
+#[derive(Serialize)]
+struct Field {
+    offset: usize,
+    length: usize,
+    hex: String,
+    description: String,
+}
+
+#[derive(Serialize)]
+struct Explanation {
+    notification: UniversalDecodeResponse,
+    fields: Vec<Field>,
+}
+
+#[derive(Debug)]
+enum CliError {
+    Usage(String),
+    Io(std::io::Error),
+    Decode(DecodeError),
+    Encode(EncodeError),
+}
+
+impl From<&DecodeError> for ErrorOutput {
+    fn from(e: &DecodeError) -> Self {
+        ErrorOutput {
+            code: e.code(),
+            message: e.to_string(),
+            offset: e.offset(),
+            expected: e.expected(),
+            actual: e.actual(),
+            rfc: e.rfc(),
+        }
+    }
+}
+
+impl From<&EncodeError> for ErrorOutput {
+    fn from(e: &EncodeError) -> Self {
+        ErrorOutput {
+            code: e.code(),
+            message: e.to_string(),
+            offset: e.offset(),
+            expected: e.expected(),
+            actual: e.actual(),
+            rfc: e.rfc(),
+        }
+    }
+}
+
+impl From<DecodeError> for CliError {
+    fn from(error: DecodeError) -> Self {
+        CliError::Decode(error)
+    }
+}
+
+impl From<EncodeError> for CliError {
+    fn from(error: EncodeError) -> Self {
+        CliError::Encode(error)
+    }
+}
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if matches!(args.first().map(String::as_str), Some("-h") | Some("--help")) {
+        println!("{}", USAGE);
+        return ExitCode::SUCCESS;
+    }
+
+    let (command, options) = match parse_args(&args) {
+        Ok(parsed) => parsed,
+        Err(message) => {
+            eprintln!("error: {}\n\n{}", message, USAGE);
+            return ExitCode::from(2);
+        }
+    };
+
+    match run(command, &options) {
+        Ok(output) => {
+            println!("{}", output);
+            ExitCode::SUCCESS
+        }
+        Err(CliError::Usage(message)) => {
+            eprintln!("error: {}\n\n{}", message, USAGE);
+            ExitCode::from(2)
+        }
+        Err(CliError::Io(e)) => {
+            eprintln!("error: failed to read input: {}", e);
+            ExitCode::FAILURE
+        }
+        Err(CliError::Decode(e)) => report(options.json, ErrorOutput::from(&e)),
+        Err(CliError::Encode(e)) => report(options.json, ErrorOutput::from(&e)),
+    }
+}
+
+fn report(json: bool, error: ErrorOutput) -> ExitCode {
+    if json {
+        println!("{}", to_json(&error));
+    } else {
+        match error.rfc {
+            Some(rfc) => eprintln!("error: {} ({})", error.message, rfc),
+            None => eprintln!("error: {}", error.message),
+        }
+    }
+    ExitCode::FAILURE
+}
+
+fn parse_args(args: &[String]) -> Result<(Command, Options), String> {
+    let mut iter = args.iter();
+    let command = match iter.next().map(String::as_str) {
+        Some("encode") => Command::Encode,
+        Some("decode") => Command::Decode,
+        Some("explain") => Command::Explain,
+        Some("capture") => Command::Capture,
+        Some("mrt") => Command::Mrt,
+        Some("bmp") => Command::Bmp,
+        None => return Err("missing command".to_string()),
+        Some(other) => return Err(format!("unknown command '{}'", other)),
+    };
+
+    let mut options = Options::default();
+    let mut positional = Vec::new();
+
+    while let Some(arg) = iter.next() {
+        let mut value = |name: &str| {
+            iter.next().cloned().ok_or_else(|| format!("{} requires a value", name))
+        };
+        match arg.as_str() {
+            "--json" => options.json = true,
+            "--extended" => options.extended = true,
+            "-e" | "--error-code" => options.error_code = Some(parse_number(&value(arg)?, arg)?),
+            "-s" | "--subcode" => options.subcode = Some(parse_number(&value(arg)?, arg)?),
+            "-t" | "--data-type" => options.data_type = Some(value(arg)?),
+            "--compat" => options.compatibility = parse_compatibility(&value(arg)?)?,
+            "--truncate" => options.truncate = true,
+            "--ellipsis" => options.ellipsis = Some(value(arg)?),
+            "--template" => options.template = Some(value(arg)?),
+            "--asn" => options.values.asn = Some(parse_asn(&value(arg)?, arg)?),
+            "--nick" => options.values.nick = Some(value(arg)?),
+            "--ticket" => options.values.ticket = Some(value(arg)?),
+            "--until" => options.values.until = Some(value(arg)?),
+            "--contact" => options.values.contact = Some(value(arg)?),
+            "--pcap" => options.pcap = Some(value(arg)?),
+            "--client" => options.session.client = parse_endpoint(&value(arg)?, arg)?,
+            "--server" => options.session.server = parse_endpoint(&value(arg)?, arg)?,
+            "--mrt" => options.mrt = Some(value(arg)?),
+            "--peer-as" => options.mrt_session.peer_as = parse_asn(&value(arg)?, arg)?,
+            "--peer-ip" => options.mrt_session.peer_ip = parse_ip(&value(arg)?, arg)?,
+            "--local-ip" => options.mrt_session.local_ip = parse_ip(&value(arg)?, arg)?,
+            "--ifindex" => {
+                let index = value(arg)?;
+                options.mrt_session.interface_index =
+                    index.parse().map_err(|_| format!("{} must be a number between 0 and 65535", arg))?;
+            },
+            "--" => positional.extend(iter.by_ref().cloned()),
+            flag if flag.starts_with("--") => return Err(format!("unknown option '{}'", flag)),
+            _ => positional.push(arg.clone()),
+        }
+    }
+
+    if !positional.is_empty() {
+        options.input = Some(positional.join(" "));
+    }
+
+    Ok((command, options))
+}
+
+fn parse_number(value: &str, name: &str) -> Result<u8, String> {
+    value.parse().map_err(|_| format!("{} must be a number between 0 and 255", name))
+}
+
+fn parse_asn(value: &str, name: &str) -> Result<u32, String> {
+    let digits = value.strip_prefix("AS").or_else(|| value.strip_prefix("as")).unwrap_or(value);
+    digits.parse().map_err(|_| format!("{} must be an AS number, not '{}'", name, value))
+}
+
+fn parse_ip(value: &str, name: &str) -> Result<IpAddr, String> {
+    value.parse().map_err(|_| format!("{} must be an IP address, not '{}'", name, value))
+}
+
+fn parse_endpoint(value: &str, name: &str) -> Result<SocketAddr, String> {
+    value.parse().map_err(|_| format!("{} must be an address and port such as 192.0.2.1:179", name))
+}
+
+fn parse_compatibility(value: &str) -> Result<ShutdownCompatibility, String> {
+    match value {
+        "rfc9003" => Ok(ShutdownCompatibility::Rfc9003),
+        "rfc8203-warn" => Ok(ShutdownCompatibility::Rfc8203Warn),
+        "rfc8203" => Ok(ShutdownCompatibility::Rfc8203),
+        _ => Err(format!("--compat must be rfc9003, rfc8203-warn or rfc8203, not '{}'", value)),
+    }
+}
+
+// Positional input, or stdin with the trailing newline removed
+fn read_input(options: &Options) -> Result<String, CliError> {
+    if let Some(input) = &options.input {
+        return Ok(input.clone());
+    }
+
+    let mut buffer = String::new();
+    std::io::stdin().read_to_string(&mut buffer).map_err(CliError::Io)?;
+    Ok(buffer.trim_end_matches(['\r', '\n']).to_string())
+}
+
+// The capture or dump named on the command line, or stdin
+fn read_file(options: &Options) -> Result<Vec<u8>, CliError> {
+    match &options.input {
+        Some(path) => std::fs::read(path).map_err(CliError::Io),
+        None => {
+            let mut buffer = Vec::new();
+            std::io::stdin().read_to_end(&mut buffer).map_err(CliError::Io)?;
+            Ok(buffer)
+        }
+    }
+}
+
+fn run(command: Command, options: &Options) -> Result<String, CliError> {
+    // A notification without a data type carries no data, and a template is
+    // the message, so don't wait on stdin. Files are read as bytes below
+    let no_input = options.error_code.is_some() && options.data_type.is_none() || options.template.is_some();
+    let binary = matches!(command, Command::Capture | Command::Mrt | Command::Bmp);
+    let input = if command == Command::Encode && no_input || binary {
+        options.input.clone().unwrap_or_default()
+    } else {
+        read_input(options)?
+    };
+
+    match command {
+        Command::Encode => encode(options, &input),
+        Command::Decode => decode(options, &input),
+        Command::Explain => explain(options, &input),
+        Command::Capture => capture(options, &read_file(options)?),
+        Command::Mrt => mrt(options, &read_file(options)?),
+        Command::Bmp => bmp(options, &read_file(options)?),
+    }
+}
+
+fn encode(options: &Options, input: &str) -> Result<String, CliError> {
+    let mut warnings = Vec::new();
+    let (bytes, message_bytes) = match options.error_code {
+        Some(error_code) => {
+            let subcode = options.subcode
+                .ok_or_else(|| CliError::Usage("--error-code requires --subcode".to_string()))?;
+            let data_type = options.data_type.as_deref().unwrap_or("");
+            let notification = Notification::with_data(error_code, subcode, data_type, input)?;
+            (notification.to_bytes_with(&options.context())?, notification.data.len())
+        }
+        None => {
+            let builtin = options.template.as_deref().and_then(find_template);
+            let value = options.subcode
+                .or(builtin.map(|builtin| builtin.subcode))
+                .unwrap_or(BgpCeaseSubcode::AdminShutdown as u8);
+            let subcode = BgpCeaseSubcode::from_u8(value)
+                .ok_or(EncodeError::InvalidShutdownSubcode { actual: value })?;
+            let message = match &options.template {
+                Some(template) => expand_template(builtin.map_or(template, |builtin| builtin.template), &options.values)?,
+                None => input.to_string(),
+            };
+            let mut communication = ShutdownCommunication::new(subcode, message);
+            if options.truncate {
+                let ellipsis = options.ellipsis.as_deref().unwrap_or(DEFAULT_ELLIPSIS);
+                warnings.extend(communication.truncate(options.compatibility.max_len(), ellipsis)?);
+            }
+            warnings.extend(communication.check_compatibility(options.compatibility)?);
+            (communication.to_bytes()?, communication.message.len())
+        }
+    };
+
+    write_pcap(options, &bytes)?;
+    write_mrt(options, &bytes)?;
+    let response = EncodeResponse {
+        hex: to_hex(&bytes),
+        total_bytes: bytes.len(),
+        message_bytes,
+        warnings,
+    };
+
+    if options.json {
+        return Ok(to_json(&response));
+    }
+    for warning in &response.warnings {
+        eprintln!("warning: {}", warning);
+    }
+    Ok(response.hex)
+}
+
+// --pcap: `bytes` as sent by the client of a made-up session, stamped now
+fn write_pcap(options: &Options, bytes: &[u8]) -> Result<(), CliError> {
+    let Some(path) = &options.pcap else { return Ok(()) };
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default();
+    let session = SyntheticSession {
+        start: CaptureTime::new(now.as_secs(), now.subsec_micros() * 1_000),
+        ..options.session.clone()
+    };
+    std::fs::write(path, session.to_pcap(bytes)?).map_err(CliError::Io)
+}
+
+// --mrt: `bytes` as received from the peer, one record per second from now.
+// The local AS is the one given with --asn
+fn write_mrt(options: &Options, bytes: &[u8]) -> Result<(), CliError> {
+    let Some(path) = &options.mrt else { return Ok(()) };
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default();
+    let mut session = MrtSession { start: CaptureTime::new(now.as_secs(), 0), ..options.mrt_session.clone() };
+    if let Some(asn) = options.values.asn {
+        session.local_as = asn;
+    }
+    std::fs::write(path, session.to_mrt(bytes)?).map_err(CliError::Io)
+}
+
+fn decode(options: &Options, input: &str) -> Result<String, CliError> {
+    // More than one message (or one plus leftovers) is decoded as a stream;
+    // a lone message keeps the detailed per-type errors below
+    let context = options.context();
+    let stream = MessageStream::from_hex_with(input, &context)?;
+    if options.pcap.is_some() || options.mrt.is_some() {
+        let bytes = SessionContext::new(true).parse_hex(input)?;
+        write_pcap(options, &bytes)?;
+        write_mrt(options, &bytes)?;
+    }
+    let framed = stream.messages.len();
+    if framed > 1 || (framed == 1 && (stream.partial.is_some() || stream.framing_error.is_some())) {
+        return decode_stream(options, &stream);
+    }
+
+    let bytes = context.parse_hex(input)?;
+    match bytes.get(18) {
+        Some(&BGP_OPEN) => return decode_open(options, &bytes),
+        Some(&BGP_UPDATE) => return decode_update(options, &bytes),
+        Some(&BGP_KEEPALIVE) => return decode_keepalive(options, &bytes),
+        Some(&BGP_ROUTE_REFRESH) => return decode_route_refresh(options, &bytes),
+        _ => {},
+    }
+
+    let notification = Notification::parse_with(&bytes, &context)?;
+    let response = UniversalDecodeResponse::with_context(&notification, &context);
+
+    if options.json {
+        return Ok(to_json(&response));
+    }
+
+    let mut lines = vec![
+        format!("Error: {} ({})", response.error_code, response.error_name),
+        format!("Subcode: {} ({})", response.subcode, response.subcode_name),
+        format!("Data: {} bytes", response.data_length),
+    ];
+    if !response.interpretation.is_empty() {
+        lines.push(format!("Info: {}", response.interpretation));
+    }
+    lines.extend(response.notes.iter().map(|note| format!("Note: {}", note)));
+    Ok(lines.join("\n"))
+}
+
+fn decode_stream(options: &Options, stream: &MessageStream) -> Result<String, CliError> {
+    let response = StreamDecodeResponse::with_context(stream, &options.context());
+
+    if options.json {
+        return Ok(to_json(&response));
+    }
+
+    let mut lines = Vec::new();
+    for (i, message) in response.messages.iter().enumerate() {
+        lines.push(format!("[{}] offset {}, {} bytes: {}", i, message.offset, message.length, message.type_name));
+        lines.extend(message.summary.iter().map(|line| format!("    {}", line)));
+        if let Some(error) = &message.error {
+            lines.push(format!("    Error: {}", error));
+        }
+    }
+    if let Some(partial) = &response.partial {
+        lines.push(match partial.expected {
+            Some(expected) => format!(
+                "Partial message at offset {}: {} of {} bytes",
+                partial.offset, partial.available, expected
+            ),
+            None => format!("Partial header at offset {}: {} bytes", partial.offset, partial.available),
+        });
+    }
+    if let Some(error) = &response.framing_error {
+        lines.push(format!("Framing stopped: {}", error));
+    }
+    Ok(lines.join("\n"))
+}
+
+// One line per message in capture order, with the flows listed first
+fn capture(options: &Options, bytes: &[u8]) -> Result<String, CliError> {
+    let context = options.context();
+    let capture = Capture::parse_with(bytes, &context)?;
+    let response = CaptureResponse::with_context(&capture, &context);
+
+    if options.json {
+        return Ok(to_json(&response));
+    }
+    for warning in &response.warnings {
+        eprintln!("warning: {}", warning);
+    }
+
+    let mut lines = Vec::new();
+    for flow in &response.flows {
+        lines.push(format!(
+            "Flow {}: {} -> {}, {} packets, {} messages, from frame {}{}",
+            flow.id, flow.client, flow.server, flow.packets, flow.messages, flow.first_frame,
+            if flow.handshake { "" } else { " (joined mid-session)" }
+        ));
+    }
+    for message in &response.messages {
+        let summary = message.message.error.as_ref()
+            .map(|error| format!("Error: {}", error))
+            .or_else(|| message.message.summary.first().cloned())
+            .unwrap_or_default();
+        lines.push(format!(
+            "{}  #{} frame {}  {} -> {}  {}  {}",
+            message.time, message.flow, message.frame, message.source, message.destination,
+            message.message.type_name, summary
+        ).trim_end().to_string());
+    }
+    if lines.is_empty() {
+        lines.push(format!("No BGP sessions in {} packets", response.packets));
+    }
+    Ok(lines.join("\n"))
+}
+
+// Who sent which Cease when, one line each, shutdown communications made safe to print
+fn mrt(options: &Options, bytes: &[u8]) -> Result<String, CliError> {
+    let scan = MrtCeases::parse_with(bytes, &options.context())?;
+    let response = MrtResponse::from(&scan);
+
+    if options.json {
+        return Ok(to_json(&response));
+    }
+    for warning in &response.warnings {
+        eprintln!("warning: {}", warning);
+    }
+
+    let mut lines: Vec<String> = response.ceases.iter().map(|cease| {
+        let text = cease.shutdown.as_ref().map_or(String::new(), |shutdown| format!("  \"{}\"", shutdown.sanitized));
+        format!(
+            "{}  AS{} {} -> AS{} {}  {}{}",
+            cease.time, cease.sender_as, cease.sender_ip, cease.receiver_as, cease.receiver_ip,
+            cease.subcode_name, text
+        )
+    }).collect();
+    lines.push(format!(
+        "{} Cease notifications in {} BGP messages ({} records)",
+        response.ceases.len(), response.messages, response.records
+    ));
+    Ok(lines.join("\n"))
+}
+
+// One line per BMP message, then its details indented; the NOTIFICATION of
+// a Peer Down is described as in universal mode
+fn bmp(options: &Options, bytes: &[u8]) -> Result<String, CliError> {
+    let context = options.context();
+    let stream = BmpStream::parse_with(bytes, &context)?;
+    let response = BmpResponse::with_context(&stream, &context);
+
+    if options.json {
+        return Ok(to_json(&response));
+    }
+    for warning in &response.warnings {
+        eprintln!("warning: {}", warning);
+    }
+
+    let mut lines = Vec::new();
+    for message in &response.messages {
+        lines.push(match &message.peer {
+            Some(peer) => format!("{}  {}  AS{} {}", peer.time, message.type_name, peer.peer_as, peer.address),
+            None => message.type_name.clone(),
+        });
+        lines.extend(message.summary.iter().map(|line| format!("    {}", line)));
+        for bgp in &message.messages {
+            let detail = bgp.error.as_ref()
+                .map(|error| format!("Error: {}", error))
+                .or_else(|| bgp.summary.first().cloned())
+                .unwrap_or_default();
+            lines.push(format!("    {} {}", bgp.type_name, detail).trim_end().to_string());
+        }
+        if let Some(down) = &message.peer_down {
+            lines.push(format!("    {}", down.reason_name));
+            if let Some(notification) = &down.notification {
+                lines.push(format!(
+                    "    {} / {}: {}",
+                    notification.error_name, notification.subcode_name, notification.interpretation
+                ));
+            }
+            if let Some(error) = &down.error {
+                lines.push(format!("    Error: {}", error));
+            }
+        }
+    }
+    Ok(lines.join("\n"))
+}
+
+fn decode_open(options: &Options, bytes: &[u8]) -> Result<String, CliError> {
+    let response = OpenDecodeResponse::from(&Open::parse(bytes)?);
+
+    if options.json {
+        return Ok(to_json(&response));
+    }
+    Ok(response.summary.join("\n"))
+}
+
+fn decode_update(options: &Options, bytes: &[u8]) -> Result<String, CliError> {
+    let update = Update::parse_with(bytes, &options.context())?;
+    let response = UpdateDecodeResponse::from(&update);
+
+    if options.json {
+        return Ok(to_json(&response));
+    }
+
+    let mut lines = vec![
+        format!("Withdrawn routes length: {}", response.withdrawn_routes_length),
+        format!("Total path attribute length: {}", response.total_path_attribute_length),
+    ];
+    lines.extend(update.summary());
+    Ok(lines.join("\n"))
+}
+
+fn decode_keepalive(options: &Options, bytes: &[u8]) -> Result<String, CliError> {
+    Keepalive::parse(bytes)?;
+
+    let summary = "KEEPALIVE: header only, 19 bytes";
+    if options.json {
+        return Ok(to_json(&[summary]));
+    }
+    Ok(summary.to_string())
+}
+
+fn decode_route_refresh(options: &Options, bytes: &[u8]) -> Result<String, CliError> {
+    let route_refresh = RouteRefresh::parse_with(bytes, &options.context())?;
+    let response = RouteRefreshDecodeResponse::from(&route_refresh);
+
+    if options.json {
+        return Ok(to_json(&response));
+    }
+    Ok(response.summary.join("\n"))
+}
+
+fn explain(options: &Options, input: &str) -> Result<String, CliError> {
+    let context = options.context();
+    // Fields are cut from the bytes as given; re-encoding would refuse
+    // unassigned codes that decode with only a note
+    let bytes = context.parse_hex(input)?;
+    let notification = Notification::parse_with(&bytes, &context)?;
+    let fields = explain_fields(&notification, &bytes, &context);
+
+    if options.json {
+        return Ok(to_json(&Explanation {
+            notification: UniversalDecodeResponse::with_context(&notification, &context),
+            fields,
+        }));
+    }
+
+    Ok(fields.iter()
+        .map(|f| format!("{:>4}  {:<3}  {}\n      {}", f.offset, f.length, f.description, f.hex))
+        .collect::<Vec<_>>()
+        .join("\n"))
+}
+
+fn explain_fields(notification: &Notification, bytes: &[u8], context: &SessionContext) -> Vec<Field> {
+    let (error_name, subcode_name) = notification.names();
+    let field = |offset: usize, length: usize, description: String| Field {
+        offset,
+        length,
+        hex: to_hex(&bytes[offset..offset + length]),
+        description,
+    };
+
+    let mut fields = vec![
+        field(0, 16, "Marker (all ones)".to_string()),
+        field(16, 2, format!("Length: {} bytes", bytes.len())),
+        field(18, 1, "Type: 3 (NOTIFICATION)".to_string()),
+        field(19, 1, format!("Error code: {} ({})", notification.error_code, error_name)),
+        field(20, 1, format!("Subcode: {} ({})", notification.subcode, subcode_name)),
+    ];
+
+    let data_len = notification.data.len();
+    if data_len == 0 {
+        return fields;
+    }
+
+    let administrative = BgpCeaseSubcode::from_u8(notification.subcode).is_some();
+    match LenientShutdown::from_notification(notification) {
+        Ok(shutdown) if administrative => {
+            let declared = shutdown.declared_length.unwrap_or_default();
+            let length = if declared == shutdown.actual_length {
+                format!("Shutdown communication length: {}", declared)
+            } else {
+                format!("Shutdown communication length: {} ({} bytes follow)", declared, shutdown.actual_length)
+            };
+            fields.push(field(MIN_NOTIFICATION_LEN, 1, length));
+            if data_len > 1 {
+                let label = if shutdown.is_clean() { "Shutdown communication" } else { "Shutdown communication (malformed)" };
+                fields.push(field(
+                    MIN_NOTIFICATION_LEN + 1,
+                    data_len - 1,
+                    format!("{}: \"{}\"", label, shutdown.safety().sanitized),
+                ));
+            }
+        }
+        _ => match notification.encapsulated() {
+            // RFC 8538 Hard Reset: the replaced notification's code, subcode and data
+            Some(inner) => {
+                let (error_name, subcode_name) = inner.names();
+                fields.push(field(MIN_NOTIFICATION_LEN, 1, format!("Encapsulated error code: {} ({})", inner.error_code, error_name)));
+                fields.push(field(MIN_NOTIFICATION_LEN + 1, 1, format!("Encapsulated subcode: {} ({})", inner.subcode, subcode_name)));
+                if !inner.data.is_empty() {
+                    fields.push(field(
+                        MIN_NOTIFICATION_LEN + 2,
+                        inner.data.len(),
+                        format!("Encapsulated data: {}", inner.interpretation_with(context)),
+                    ));
+                }
+            }
+            None => fields.push(field(
+                MIN_NOTIFICATION_LEN,
+                data_len,
+                format!("Data: {}", notification.interpretation_with(context)),
+            )),
+        },
+    }
+
+    fields
+}
+
+fn to_json<T: Serialize>(value: &T) -> String {
+    serde_json::to_string_pretty(value).expect("response types always serialize")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(list: &[&str]) -> Vec<String> {
+        list.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn test_parse_args() {
+        let (command, options) = parse_args(&args(&["encode", "-s", "4", "back", "soon"])).unwrap();
+        assert_eq!(command, Command::Encode);
+        assert_eq!(options.subcode, Some(4));
+        assert_eq!(options.input.as_deref(), Some("back soon"));
+
+        let (command, options) = parse_args(&args(&["decode", "--json", "--extended"])).unwrap();
+        assert_eq!(command, Command::Decode);
+        assert!(options.json);
+        assert_eq!(options.context(), SessionContext::new(true));
+        assert_eq!(options.input, None);
+
+        let (_, options) = parse_args(&args(&["encode", "--compat", "rfc8203", "hi"])).unwrap();
+        assert_eq!(options.compatibility, ShutdownCompatibility::Rfc8203);
+        assert!(parse_args(&args(&["encode", "--compat", "rfc4271"])).is_err());
+
+        let (_, options) = parse_args(&args(&["encode", "--truncate", "--ellipsis", "...", "hi"])).unwrap();
+        assert!(options.truncate);
+        assert_eq!(options.ellipsis.as_deref(), Some("..."));
+
+        let (_, options) = parse_args(&args(&["encode", "--template", "maintenance", "--asn", "AS64500"])).unwrap();
+        assert_eq!(options.template.as_deref(), Some("maintenance"));
+        assert_eq!(options.values.asn, Some(64500));
+
+        let (command, options) = parse_args(&args(&["capture", "--json", "bgp.pcapng"])).unwrap();
+        assert_eq!(command, Command::Capture);
+        assert_eq!(options.input.as_deref(), Some("bgp.pcapng"));
+        assert_eq!(parse_args(&args(&["mrt", "updates.gz"])).unwrap().0, Command::Mrt);
+        assert_eq!(parse_args(&args(&["bmp", "--json", "feed.bmp"])).unwrap().0, Command::Bmp);
+
+        let (_, options) = parse_args(&args(&["encode", "--pcap", "out.pcap", "--server", "[2001:db8::2]:179", "hi"])).unwrap();
+        assert_eq!(options.pcap.as_deref(), Some("out.pcap"));
+        assert_eq!(options.session.server, "[2001:db8::2]:179".parse().unwrap());
+        assert!(parse_args(&args(&["encode", "--client", "192.0.2.1"])).is_err());
+
+        let (_, options) = parse_args(&args(&["decode", "--mrt", "out.mrt", "--peer-as", "64500", "--ifindex", "3"])).unwrap();
+        assert_eq!(options.mrt.as_deref(), Some("out.mrt"));
+        assert_eq!((options.mrt_session.peer_as, options.mrt_session.interface_index), (64500, 3));
+        assert!(parse_args(&args(&["encode", "--peer-ip", "192.0.2.1:179"])).is_err());
+
+        assert!(parse_args(&args(&["frobnicate"])).is_err());
+        assert!(parse_args(&args(&["encode", "--subcode"])).is_err());
+    }
+
+    #[test]
+    fn test_encode_decode_explain() {
+        let options = Options { subcode: Some(2), ..Options::default() };
+        let hex = encode(&options, "Maintenance in 30min").unwrap();
+        assert!(hex.starts_with("ff ff"));
+
+        let decoded = decode(&Options::default(), &hex).unwrap();
+        assert!(decoded.contains("Administrative Shutdown"));
+        assert!(decoded.contains("\"Maintenance in 30min\""));
+
+        let stream = decode(&Options::default(), &format!("{} ff ff ff", hex)).unwrap();
+        assert!(stream.starts_with("[0] offset 0, 42 bytes: NOTIFICATION"));
+        assert!(stream.ends_with("Partial header at offset 42: 3 bytes"));
+
+        let notification = Notification::from_hex(&hex).unwrap();
+        let fields = explain_fields(&notification, &notification.to_bytes().unwrap(), &SessionContext::default());
+        assert_eq!(fields.len(), 7);
+        assert_eq!(fields[6].offset, 22);
+        assert_eq!(fields[6].length, 20);
+    }
+
+    #[test]
+    fn test_explain_unassigned_codes() {
+        let marker = "ffffffffffffffffffffffffffffffff";
+        let explained = explain(&Options::default(), &format!("{}0015032a00", marker)).unwrap();
+        assert!(explained.contains("Error code: 42"), "{}", explained);
+
+        let explained = explain(&Options::default(), &format!("{}00150306 63", marker)).unwrap();
+        assert!(explained.contains("Subcode: 99"), "{}", explained);
+
+        let extended = Options { extended: true, ..Options::default() };
+        let explained = explain(&extended, &format!("{}00170301021388", marker)).unwrap();
+        assert!(explained.contains("valid range: 19-65535, Extended Message"), "{}", explained);
+        let stream = decode(&extended, &format!("{}00170301021388 {}001304", marker, marker)).unwrap();
+        assert!(stream.contains("valid range: 19-65535, Extended Message"), "{}", stream);
+    }
+}
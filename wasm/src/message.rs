@@ -0,0 +1,106 @@
+use crate::{
+    message_type_name, parse_hex, validate_message, DecodeError, Keepalive, Notification, Open,
+    RouteRefresh, SessionContext, Update, BGP_HEADER_LEN, BGP_KEEPALIVE, BGP_NOTIFICATION,
+    BGP_OPEN, BGP_ROUTE_REFRESH, BGP_UPDATE,
+};
+
+/// Any BGP message, decoded according to the type in its header.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Message {
+    Open(Open),
+    Update(Update),
+    Notification(Notification),
+    Keepalive(Keepalive),
+    RouteRefresh(RouteRefresh),
+    /// A well-framed message of a type this crate does not decode.
+    Unknown { message_type: u8, body: Vec<u8> },
+}
+
+impl Message {
+    /// Parse one complete message, dispatching on the header's type field.
+    pub fn parse(bytes: &[u8]) -> Result<Self, DecodeError> {
+        Self::parse_with(bytes, &SessionContext::default())
+    }
+
+    /// Parse with the size limits of `context`.
+    pub fn parse_with(bytes: &[u8], context: &SessionContext) -> Result<Self, DecodeError> {
+        let message_type = match bytes.get(18) {
+            Some(&message_type) => message_type,
+            None => return Err(DecodeError::MessageTooShort { actual: bytes.len(), min: BGP_HEADER_LEN }),
+        };
+
+        let message = match message_type {
+            BGP_OPEN => Message::Open(Open::parse(bytes)?),
+            BGP_UPDATE => Message::Update(Update::parse_with(bytes, context)?),
+            BGP_NOTIFICATION => Message::Notification(Notification::parse_with(bytes, context)?),
+            BGP_KEEPALIVE => Message::Keepalive(Keepalive::parse(bytes)?),
+            BGP_ROUTE_REFRESH => Message::RouteRefresh(RouteRefresh::parse_with(bytes, context)?),
+            _ => {
+                validate_message(bytes, message_type, BGP_HEADER_LEN, context.max_message_len())?;
+                Message::Unknown { message_type, body: bytes[BGP_HEADER_LEN..].to_vec() }
+            },
+        };
+        Ok(message)
+    }
+
+    /// Parse a hex dump; whitespace, `:` and `-` separators are ignored.
+    pub fn from_hex(hex_input: &str) -> Result<Self, DecodeError> {
+        Self::parse(&parse_hex(hex_input)?)
+    }
+
+    pub fn message_type(&self) -> u8 {
+        match self {
+            Message::Open(_) => BGP_OPEN,
+            Message::Update(_) => BGP_UPDATE,
+            Message::Notification(_) => BGP_NOTIFICATION,
+            Message::Keepalive(_) => BGP_KEEPALIVE,
+            Message::RouteRefresh(_) => BGP_ROUTE_REFRESH,
+            Message::Unknown { message_type, .. } => *message_type,
+        }
+    }
+
+    /// Human-readable description, in the same form as each type's own summary.
+    pub fn summary(&self) -> Vec<String> {
+        self.summary_with(&SessionContext::default())
+    }
+
+    /// Description with length limits following `context`.
+    pub fn summary_with(&self, context: &SessionContext) -> Vec<String> {
+        match self {
+            Message::Open(open) => open.summary(),
+            Message::Update(update) => update.summary(),
+            Message::Notification(notification) => {
+                let (error_name, subcode_name) = notification.names();
+                let mut lines = vec![
+                    format!("Error: {} ({})", notification.error_code, error_name),
+                    format!("Subcode: {} ({})", notification.subcode, subcode_name),
+                    format!("Info: {}", notification.interpretation_with(context)),
+                ];
+                lines.extend(notification.notes());
+                lines
+            },
+            Message::Keepalive(_) => vec!["KEEPALIVE: header only, 19 bytes".to_string()],
+            Message::RouteRefresh(route_refresh) => route_refresh.summary(),
+            Message::Unknown { message_type, body } => {
+                vec![format!("Message type {} ({}): {} bytes", message_type, message_type_name(*message_type), body.len())]
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_message_dispatch() {
+        let keepalive = Message::from_hex("ffffffffffffffffffffffffffffffff 0013 04").unwrap();
+        assert_eq!(keepalive, Message::Keepalive(Keepalive));
+
+        let cease = Message::from_hex("ffffffffffffffffffffffffffffffff 0015 03 06 03").unwrap();
+        assert_eq!(cease.summary()[1], "Subcode: 3 (Peer De-configured)");
+
+        let unknown = Message::from_hex("ffffffffffffffffffffffffffffffff 0014 09 00").unwrap();
+        assert_eq!(unknown.message_type(), 9);
+    }
+}
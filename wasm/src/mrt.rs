@@ -0,0 +1,580 @@
+// MRT routing information export format (RFC 6396), as published by route
+// collectors such as RIPE RIS and RouteViews. BGP4MP message records carry
+// whole BGP messages as the collector saw them; those are decoded with the
+// same codec as everything else, so the Cease notifications in a dump (and
+// the shutdown communications in them) can be listed with who sent them
+// and when. Table dumps and state changes are passed over by type. Crafted
+// messages can be written out the same way, as BGP4MP_MESSAGE_AS4 records
+// of a made-up session, for tools that read dumps rather than captures.
+
+use std::borrow::Cow;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+
+use serde::{Deserialize, Serialize};
+
+use crate::reader::Reader;
+use crate::stream::split_messages;
+use crate::{
+    get_error_names, CaptureTime, DecodeError, DecodeResponse, EncodeError, FramedMessage,
+    LenientShutdown, Message, MrtCeaseResponse, MrtResponse, Notification, SessionContext,
+    BGP_CEASE_ADMIN_RESET, BGP_CEASE_ADMIN_SHUTDOWN, BGP_ERROR_CEASE,
+};
+
+/// MRT record types (RFC 6396 section 4) that carry BGP messages.
+pub const MRT_BGP4MP: u16 = 16;
+/// BGP4MP with a microsecond timestamp field.
+pub const MRT_BGP4MP_ET: u16 = 17;
+
+/// BGP4MP subtypes holding a BGP message (RFC 6396 4.4, RFC 8050 4).
+pub const BGP4MP_MESSAGE: u16 = 1;
+pub const BGP4MP_MESSAGE_AS4: u16 = 4;
+pub const BGP4MP_MESSAGE_LOCAL: u16 = 6;
+pub const BGP4MP_MESSAGE_AS4_LOCAL: u16 = 7;
+pub const BGP4MP_MESSAGE_ADDPATH: u16 = 8;
+pub const BGP4MP_MESSAGE_AS4_ADDPATH: u16 = 9;
+pub const BGP4MP_MESSAGE_LOCAL_ADDPATH: u16 = 10;
+pub const BGP4MP_MESSAGE_AS4_LOCAL_ADDPATH: u16 = 11;
+
+/// Length of the MRT common header.
+pub const MRT_HEADER_LEN: usize = 12;
+
+const RFC_MRT_HEADER: &str = "RFC 6396 2";
+const RFC_BGP4MP: &str = "RFC 6396 4.4.2";
+
+/// One record of an MRT dump, undecoded.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MrtRecord<'a> {
+    /// Offset of the record header in the (decompressed) dump.
+    pub offset: usize,
+    pub timestamp: CaptureTime,
+    pub record_type: u16,
+    pub subtype: u16,
+    /// The message field, after the microseconds of an `_ET` record.
+    pub body: &'a [u8],
+}
+
+impl MrtRecord<'_> {
+    /// A BGP4MP record of a subtype that holds a BGP message.
+    pub fn is_bgp4mp_message(&self) -> bool {
+        matches!(self.record_type, MRT_BGP4MP | MRT_BGP4MP_ET)
+            && matches!(
+                self.subtype,
+                BGP4MP_MESSAGE | BGP4MP_MESSAGE_AS4 | BGP4MP_MESSAGE_LOCAL..=BGP4MP_MESSAGE_AS4_LOCAL_ADDPATH
+            )
+    }
+
+    /// The peers and BGP message of a BGP4MP message record, or `None`
+    /// for any other kind of record.
+    pub fn bgp4mp(&self, context: &SessionContext) -> Option<Result<Bgp4mpMessage, DecodeError>> {
+        self.is_bgp4mp_message().then(|| self.parse_bgp4mp(context))
+    }
+
+    fn parse_bgp4mp(&self, context: &SessionContext) -> Result<Bgp4mpMessage, DecodeError> {
+        let base = self.offset + self.body_offset();
+        let mut r = Reader::new(self.body, base, RFC_BGP4MP);
+        let four_octet = matches!(
+            self.subtype,
+            BGP4MP_MESSAGE_AS4
+                | BGP4MP_MESSAGE_AS4_LOCAL
+                | BGP4MP_MESSAGE_AS4_ADDPATH
+                | BGP4MP_MESSAGE_AS4_LOCAL_ADDPATH
+        );
+        let (peer_as, local_as) = if four_octet {
+            (r.u32("peer AS")?, r.u32("local AS")?)
+        } else {
+            (u32::from(r.u16("peer AS")?), u32::from(r.u16("local AS")?))
+        };
+        let interface_index = r.u16("interface index")?;
+        let afi_offset = r.offset();
+        let (peer_ip, local_ip) = match r.u16("address family")? {
+            1 => (ipv4(r.take(4, "peer IP address")?), ipv4(r.take(4, "local IP address")?)),
+            2 => (ipv6(r.take(16, "peer IP address")?), ipv6(r.take(16, "local IP address")?)),
+            afi => return Err(DecodeError::UnknownAfi { offset: afi_offset, actual: afi }),
+        };
+
+        let offset = r.offset();
+        let bytes = r.rest();
+        Ok(Bgp4mpMessage {
+            timestamp: self.timestamp,
+            subtype: self.subtype,
+            peer_as,
+            local_as,
+            interface_index,
+            peer_ip,
+            local_ip,
+            message: FramedMessage {
+                offset,
+                length: bytes.len(),
+                message_type: bytes.get(18).copied().unwrap_or(0),
+                message: Message::parse_with(bytes, context),
+            },
+        })
+    }
+
+    fn body_offset(&self) -> usize {
+        MRT_HEADER_LEN + if self.record_type == MRT_BGP4MP_ET { 4 } else { 0 }
+    }
+}
+
+fn ipv4(octets: &[u8]) -> IpAddr {
+    IpAddr::V4(Ipv4Addr::new(octets[0], octets[1], octets[2], octets[3]))
+}
+
+fn ipv6(octets: &[u8]) -> IpAddr {
+    let mut address = [0; 16];
+    address.copy_from_slice(octets);
+    IpAddr::V6(Ipv6Addr::from(address))
+}
+
+/// Iterates over the records of an uncompressed MRT dump. A header that
+/// runs past the end of the dump is yielded as an error, and ends it.
+pub struct MrtReader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> MrtReader<'a> {
+    pub fn new(bytes: &'a [u8]) -> Self {
+        MrtReader { bytes, pos: 0 }
+    }
+
+    fn record(&self) -> Result<(MrtRecord<'a>, usize), DecodeError> {
+        let mut r = Reader::new(&self.bytes[self.pos..], self.pos, RFC_MRT_HEADER);
+        let seconds = r.u32("timestamp")?;
+        let record_type = r.u16("type")?;
+        let subtype = r.u16("subtype")?;
+        let length = r.u32("length")? as usize;
+        let mut body = r.sub(length, "message")?;
+        let micros = if record_type == MRT_BGP4MP_ET { body.u32("microsecond timestamp")?.min(999_999) } else { 0 };
+
+        let record = MrtRecord {
+            offset: self.pos,
+            timestamp: CaptureTime::new(u64::from(seconds), micros * 1_000),
+            record_type,
+            subtype,
+            body: body.rest(),
+        };
+        Ok((record, MRT_HEADER_LEN + length))
+    }
+}
+
+impl<'a> Iterator for MrtReader<'a> {
+    type Item = Result<MrtRecord<'a>, DecodeError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.pos >= self.bytes.len() {
+            return None;
+        }
+        match self.record() {
+            Ok((record, length)) => {
+                self.pos += length;
+                Some(Ok(record))
+            },
+            Err(e) => {
+                self.pos = self.bytes.len();
+                Some(Err(e))
+            },
+        }
+    }
+}
+
+/// A BGP message from a BGP4MP record.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Bgp4mpMessage {
+    pub timestamp: CaptureTime,
+    pub subtype: u16,
+    pub peer_as: u32,
+    pub local_as: u32,
+    pub interface_index: u16,
+    pub peer_ip: IpAddr,
+    pub local_ip: IpAddr,
+    /// Offsets count from the start of the dump.
+    pub message: FramedMessage,
+}
+
+impl Bgp4mpMessage {
+    /// The collector itself sent the message (the `_LOCAL` subtypes);
+    /// otherwise it came from the peer.
+    pub fn is_local(&self) -> bool {
+        matches!(
+            self.subtype,
+            BGP4MP_MESSAGE_LOCAL
+                | BGP4MP_MESSAGE_AS4_LOCAL
+                | BGP4MP_MESSAGE_LOCAL_ADDPATH
+                | BGP4MP_MESSAGE_AS4_LOCAL_ADDPATH
+        )
+    }
+}
+
+/// A Cease NOTIFICATION found in an MRT dump.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MrtCease {
+    /// Offset of the record in the (decompressed) dump.
+    pub offset: usize,
+    pub timestamp: CaptureTime,
+    pub sender_as: u32,
+    pub sender_ip: IpAddr,
+    pub receiver_as: u32,
+    pub receiver_ip: IpAddr,
+    pub notification: Notification,
+    /// The shutdown communication of an Administrative Shutdown or Reset.
+    pub shutdown: Option<LenientShutdown>,
+}
+
+/// The Cease notifications in an MRT dump, in dump order.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MrtCeases {
+    pub records: usize,
+    /// BGP4MP records holding a BGP message.
+    pub messages: usize,
+    pub ceases: Vec<MrtCease>,
+    /// Records that could not be read, and where the dump was cut off.
+    pub warnings: Vec<String>,
+}
+
+impl MrtCeases {
+    /// Scan a dump, compressed or not; see [`decompress`].
+    pub fn parse(bytes: &[u8]) -> Result<Self, DecodeError> {
+        Self::parse_with(bytes, &SessionContext::default())
+    }
+
+    /// Scan with the message size limits of `context`.
+    pub fn parse_with(bytes: &[u8], context: &SessionContext) -> Result<Self, DecodeError> {
+        let dump = decompress(bytes)?;
+        let mut scan = MrtCeases { records: 0, messages: 0, ceases: Vec::new(), warnings: Vec::new() };
+
+        for record in MrtReader::new(&dump) {
+            let record = match record {
+                Ok(record) => record,
+                // Not an MRT dump at all, rather than one cut short
+                Err(e) if scan.records == 0 => return Err(e),
+                Err(e) => {
+                    scan.warnings.push(format!("Dump ends mid-record: {}", e));
+                    break;
+                },
+            };
+            scan.records += 1;
+
+            let message = match record.bgp4mp(context) {
+                Some(Ok(message)) => message,
+                Some(Err(e)) => {
+                    scan.warnings.push(format!("Record at byte {}: {}", record.offset, e));
+                    continue;
+                },
+                None => continue,
+            };
+            scan.messages += 1;
+
+            let Ok(Message::Notification(notification)) = &message.message.message else { continue };
+            if notification.error_code != BGP_ERROR_CEASE {
+                continue;
+            }
+            let (sender, receiver) = if message.is_local() {
+                ((message.local_as, message.local_ip), (message.peer_as, message.peer_ip))
+            } else {
+                ((message.peer_as, message.peer_ip), (message.local_as, message.local_ip))
+            };
+            let shutdown = matches!(notification.subcode, BGP_CEASE_ADMIN_SHUTDOWN | BGP_CEASE_ADMIN_RESET)
+                .then(|| LenientShutdown::from_data(notification.subcode, &notification.data));
+            scan.ceases.push(MrtCease {
+                offset: record.offset,
+                timestamp: record.timestamp,
+                sender_as: sender.0,
+                sender_ip: sender.1,
+                receiver_as: receiver.0,
+                receiver_ip: receiver.1,
+                notification: notification.clone(),
+                shutdown,
+            });
+        }
+        Ok(scan)
+    }
+}
+
+impl From<&MrtCeases> for MrtResponse {
+    fn from(scan: &MrtCeases) -> Self {
+        MrtResponse {
+            records: scan.records,
+            messages: scan.messages,
+            ceases: scan.ceases.iter().map(MrtCeaseResponse::from).collect(),
+            warnings: scan.warnings.clone(),
+        }
+    }
+}
+
+impl From<&MrtCease> for MrtCeaseResponse {
+    fn from(cease: &MrtCease) -> Self {
+        MrtCeaseResponse {
+            offset: cease.offset,
+            timestamp: cease.timestamp,
+            time: cease.timestamp.to_string(),
+            sender_as: cease.sender_as,
+            sender_ip: cease.sender_ip.to_string(),
+            receiver_as: cease.receiver_as,
+            receiver_ip: cease.receiver_ip.to_string(),
+            subcode: cease.notification.subcode,
+            subcode_name: get_error_names(BGP_ERROR_CEASE, cease.notification.subcode).1,
+            shutdown: cease.shutdown.as_ref().map(DecodeResponse::from),
+        }
+    }
+}
+
+/// The two ends of a made-up BGP session, for writing crafted messages into
+/// an MRT dump as if a collector had recorded them from its peer.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MrtSession {
+    /// Sends the messages.
+    pub peer_as: u32,
+    pub peer_ip: IpAddr,
+    /// The collector's end.
+    pub local_as: u32,
+    pub local_ip: IpAddr,
+    pub interface_index: u16,
+    /// Time of the first message; each later one follows a second after the last.
+    pub start: CaptureTime,
+}
+
+impl Default for MrtSession {
+    // RFC 5398 and RFC 5737 documentation numbers
+    fn default() -> Self {
+        MrtSession {
+            peer_as: 64496,
+            peer_ip: IpAddr::V4(Ipv4Addr::new(192, 0, 2, 1)),
+            local_as: 64497,
+            local_ip: IpAddr::V4(Ipv4Addr::new(192, 0, 2, 2)),
+            interface_index: 0,
+            start: CaptureTime::default(),
+        }
+    }
+}
+
+impl MrtSession {
+    /// An uncompressed dump holding `stream` one message per record. Bytes
+    /// that do not frame as messages go into a record as they are. Fails if
+    /// the two addresses are not of the same family.
+    pub fn to_mrt(&self, stream: &[u8]) -> Result<Vec<u8>, EncodeError> {
+        let mut dump = Vec::with_capacity(stream.len() + 64);
+        for (i, message) in split_messages(stream).into_iter().enumerate() {
+            let seconds = self.start.seconds.checked_add(i as u64).ok_or(EncodeError::OutOfRange {
+                field: "timestamp",
+                actual: self.start.seconds,
+                min: 0,
+                max: u64::from(u32::MAX),
+            })?;
+            let timestamp = CaptureTime::new(seconds, self.start.nanos);
+            dump.extend_from_slice(&self.record(timestamp, message)?);
+        }
+        Ok(dump)
+    }
+
+    /// One BGP4MP_MESSAGE_AS4 record of `message` sent by the peer at
+    /// `timestamp`. A timestamp with a fraction of a second makes it a
+    /// BGP4MP_ET record with microseconds.
+    pub fn record(&self, timestamp: CaptureTime, message: &[u8]) -> Result<Vec<u8>, EncodeError> {
+        let (peer_ip, local_ip) = match (self.peer_ip, self.local_ip) {
+            (IpAddr::V4(peer), IpAddr::V4(local)) => (peer.octets().to_vec(), local.octets().to_vec()),
+            (IpAddr::V6(peer), IpAddr::V6(local)) => (peer.octets().to_vec(), local.octets().to_vec()),
+            _ => {
+                return Err(EncodeError::InvalidFormat {
+                    field: "local_ip",
+                    expected: "an address of the same family as the peer",
+                })
+            },
+        };
+        let seconds = u32::try_from(timestamp.seconds).map_err(|_| EncodeError::OutOfRange {
+            field: "timestamp",
+            actual: timestamp.seconds,
+            min: 0,
+            max: u64::from(u32::MAX),
+        })?;
+        let extended = timestamp.nanos != 0;
+        let afi: u16 = if self.peer_ip.is_ipv4() { 1 } else { 2 };
+
+        // The length covers the microseconds of an _ET record as well
+        let length = if extended { 4 } else { 0 } + 12 + peer_ip.len() * 2 + message.len();
+        let mut record = Vec::with_capacity(MRT_HEADER_LEN + length);
+        record.extend_from_slice(&seconds.to_be_bytes());
+        record.extend_from_slice(&if extended { MRT_BGP4MP_ET } else { MRT_BGP4MP }.to_be_bytes());
+        record.extend_from_slice(&BGP4MP_MESSAGE_AS4.to_be_bytes());
+        record.extend_from_slice(&(length as u32).to_be_bytes());
+        if extended {
+            record.extend_from_slice(&(timestamp.nanos / 1000).to_be_bytes());
+        }
+        record.extend_from_slice(&self.peer_as.to_be_bytes());
+        record.extend_from_slice(&self.local_as.to_be_bytes());
+        record.extend_from_slice(&self.interface_index.to_be_bytes());
+        record.extend_from_slice(&afi.to_be_bytes());
+        record.extend_from_slice(&peer_ip);
+        record.extend_from_slice(&local_ip);
+        record.extend_from_slice(message);
+        Ok(record)
+    }
+}
+
+/// Undo gzip or bzip2 compression, recognized by its magic number; other
+/// input is returned as it is. Compressed input is only supported in
+/// native builds.
+pub fn decompress(bytes: &[u8]) -> Result<Cow<'_, [u8]>, DecodeError> {
+    let format = if bytes.starts_with(&[0x1f, 0x8b]) {
+        "gzip"
+    } else if bytes.starts_with(b"BZh") {
+        "bzip2"
+    } else {
+        return Ok(Cow::Borrowed(bytes));
+    };
+    inflate(bytes, format).map(Cow::Owned)
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn inflate(bytes: &[u8], format: &'static str) -> Result<Vec<u8>, DecodeError> {
+    use std::io::Read;
+
+    let mut output = Vec::new();
+    // Collectors concatenate gzip members when appending to a file
+    let result = match format {
+        "gzip" => flate2::read::MultiGzDecoder::new(bytes).read_to_end(&mut output),
+        _ => bzip2::read::MultiBzDecoder::new(bytes).read_to_end(&mut output),
+    };
+    result.map(|_| output).map_err(|e| DecodeError::Decompression { format, reason: e.to_string() })
+}
+
+#[cfg(target_arch = "wasm32")]
+fn inflate(_bytes: &[u8], format: &'static str) -> Result<Vec<u8>, DecodeError> {
+    let reason = "not supported in the browser, decompress the file first".to_string();
+    Err(DecodeError::Decompression { format, reason })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{parse_hex, BGP_NOTIFICATION};
+
+    // An AS4 message record around `message`, peer 192.0.2.1 AS65001 and
+    // collector 192.0.2.2 AS65000
+    fn record(seconds: u32, subtype: u16, message: &[u8]) -> Vec<u8> {
+        let mut body = [65001u32, 65000].map(u32::to_be_bytes).concat();
+        body.extend_from_slice(&[0, 0, 0, 1, 192, 0, 2, 1, 192, 0, 2, 2]);
+        body.extend_from_slice(message);
+        let mut record = seconds.to_be_bytes().to_vec();
+        record.extend_from_slice(&MRT_BGP4MP.to_be_bytes());
+        record.extend_from_slice(&subtype.to_be_bytes());
+        record.extend_from_slice(&(body.len() as u32).to_be_bytes());
+        record.extend_from_slice(&body);
+        record
+    }
+
+    fn dump() -> Vec<u8> {
+        let shutdown = parse_hex("ffffffffffffffffffffffffffffffff 0021 03 06 02 0b 4d61696e74656e616e6365").unwrap();
+        let keepalive = parse_hex("ffffffffffffffffffffffffffffffff 0013 04").unwrap();
+        let hold_timer = parse_hex("ffffffffffffffffffffffffffffffff 0015 03 04 00").unwrap();
+        let reset = parse_hex("ffffffffffffffffffffffffffffffff 0016 03 06 04 00").unwrap();
+        [
+            record(1_700_000_000, BGP4MP_MESSAGE_AS4, &keepalive),
+            record(1_700_000_001, BGP4MP_MESSAGE_AS4, &shutdown),
+            record(1_700_000_002, BGP4MP_MESSAGE_AS4, &hold_timer),
+            record(1_700_000_003, BGP4MP_MESSAGE_AS4_LOCAL, &reset),
+        ]
+        .concat()
+    }
+
+    #[test]
+    fn test_mrt_records() {
+        let dump = dump();
+        let records: Vec<_> = MrtReader::new(&dump).map(Result::unwrap).collect();
+        assert_eq!(records.len(), 4);
+        let message = records[1].bgp4mp(&SessionContext::default()).unwrap().unwrap();
+        assert_eq!((message.peer_as, message.local_as, message.interface_index), (65001, 65000, 0));
+        assert_eq!(message.peer_ip, IpAddr::V4(Ipv4Addr::new(192, 0, 2, 1)));
+        assert_eq!((message.message.offset, message.message.message_type), (records[1].offset + 32, BGP_NOTIFICATION));
+
+        // An extended timestamp, and a record that is not a message
+        let mut extended = record(1_700_000_000, BGP4MP_MESSAGE_AS4, &[]);
+        extended[4..6].copy_from_slice(&MRT_BGP4MP_ET.to_be_bytes());
+        extended[11] += 4;
+        extended.splice(12..12, 250_000u32.to_be_bytes());
+        let state_change = [&[0; 4][..], &[0, 16, 0, 5], &[0, 0, 0, 0]].concat();
+        let dump = [extended, state_change].concat();
+        let records: Vec<_> = MrtReader::new(&dump).map(Result::unwrap).collect();
+        assert_eq!(records[0].timestamp.to_string(), "2023-11-14T22:13:20.250000Z");
+        assert!(records[0].bgp4mp(&SessionContext::default()).unwrap().is_ok());
+        assert_eq!(records[1].bgp4mp(&SessionContext::default()), None);
+    }
+
+    #[test]
+    fn test_mrt_ceases() {
+        let scan = MrtCeases::parse(&dump()).unwrap();
+        assert_eq!((scan.records, scan.messages), (4, 4));
+        assert_eq!(scan.ceases.len(), 2);
+        assert_eq!(scan.ceases[0].sender_as, 65001);
+        assert_eq!(scan.ceases[0].shutdown.as_ref().unwrap().text, "Maintenance");
+        // Sent by the collector's side of the session
+        assert_eq!((scan.ceases[1].sender_as, scan.ceases[1].receiver_as), (65000, 65001));
+        assert_eq!(scan.ceases[1].shutdown.as_ref().unwrap().text, "");
+        assert!(scan.warnings.is_empty());
+
+        let mut cut = dump();
+        cut.truncate(cut.len() - 3);
+        let scan = MrtCeases::parse(&cut).unwrap();
+        assert_eq!(scan.ceases.len(), 1);
+        assert!(scan.warnings[0].starts_with("Dump ends mid-record: Truncated message"));
+        assert!(matches!(MrtCeases::parse(b"junk"), Err(DecodeError::Truncated { .. })));
+    }
+
+    #[test]
+    fn test_mrt_session_round_trip() {
+        let shutdown = parse_hex("ffffffffffffffffffffffffffffffff 0021 03 06 02 0b 4d61696e74656e616e6365").unwrap();
+        let keepalive = parse_hex("ffffffffffffffffffffffffffffffff 0013 04").unwrap();
+        let session = MrtSession { start: CaptureTime::new(1_700_000_000, 0), ..MrtSession::default() };
+        let dump = session.to_mrt(&[keepalive.clone(), shutdown].concat()).unwrap();
+        // Laid out as the collectors write them
+        let header = parse_hex("6553f100 0010 0004 00000027 0000fbf0 0000fbf1 0000 0001 c0000201 c0000202").unwrap();
+        assert_eq!(dump[..MRT_HEADER_LEN + 20], header[..]);
+        assert_eq!(dump[MRT_HEADER_LEN + 20..MRT_HEADER_LEN + 39], keepalive[..]);
+
+        let scan = MrtCeases::parse(&dump).unwrap();
+        assert_eq!((scan.records, scan.messages, scan.ceases.len()), (2, 2, 1));
+        let cease = &scan.ceases[0];
+        assert_eq!((cease.sender_as, cease.receiver_as), (64496, 64497));
+        assert_eq!(cease.timestamp, CaptureTime::new(1_700_000_001, 0));
+        assert_eq!(cease.shutdown.as_ref().unwrap().text, "Maintenance");
+
+        // Microseconds, IPv6, and addresses of different families
+        let session = MrtSession {
+            peer_ip: "2001:db8::1".parse().unwrap(),
+            local_ip: "2001:db8::2".parse().unwrap(),
+            interface_index: 7,
+            ..MrtSession::default()
+        };
+        let record = session.record(CaptureTime::new(1_700_000_000, 250_000_000), &keepalive).unwrap();
+        let parsed = MrtReader::new(&record).next().unwrap().unwrap();
+        assert_eq!(parsed.timestamp.to_string(), "2023-11-14T22:13:20.250000Z");
+        let message = parsed.bgp4mp(&SessionContext::default()).unwrap().unwrap();
+        assert_eq!((message.interface_index, message.local_ip), (7, session.local_ip));
+        assert_eq!(message.message.length, keepalive.len());
+        let mixed = MrtSession { local_ip: IpAddr::V4(Ipv4Addr::LOCALHOST), ..session };
+        assert!(matches!(mixed.to_mrt(&keepalive), Err(EncodeError::InvalidFormat { field: "local_ip", .. })));
+        // A start past the u32 seconds field is rejected, not wrapped
+        let late = MrtSession { start: CaptureTime::new(u64::MAX, 0), ..MrtSession::default() };
+        let stream = [keepalive.clone(), keepalive].concat();
+        assert!(matches!(late.to_mrt(&stream), Err(EncodeError::OutOfRange { field: "timestamp", .. })));
+    }
+
+    #[test]
+    #[cfg(not(target_arch = "wasm32"))]
+    fn test_compressed_dumps() {
+        use std::io::Write;
+
+        let dump = dump();
+        let mut gzip = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::fast());
+        gzip.write_all(&dump).unwrap();
+        let mut bzip2 = bzip2::write::BzEncoder::new(Vec::new(), bzip2::Compression::fast());
+        bzip2.write_all(&dump).unwrap();
+
+        for compressed in [gzip.finish().unwrap(), bzip2.finish().unwrap()] {
+            assert_eq!(decompress(&compressed).unwrap(), dump);
+            assert_eq!(MrtCeases::parse(&compressed).unwrap().ceases.len(), 2);
+        }
+        assert!(matches!(decompress(&[0x1f, 0x8b, 8, 0]), Err(DecodeError::Decompression { format: "gzip", .. })));
+    }
+}
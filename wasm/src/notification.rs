@@ -0,0 +1,353 @@
+use crate::{
+    error_code_entry, extended_length_note, frame_message, get_error_names, interpret_data,
+    parse_afi, parse_hex, parse_hex_bounded, parse_safi, parse_u16_bounded, parse_u32_bounded,
+    parse_u8_bounded, subcode_entry, to_hex, validate_bgp_message, BgpCeaseSubcode, DecodeError,
+    EncodeError, LenientShutdown, SessionContext, ShutdownCommunication, UniversalDecodeResponse,
+    BGP_CEASE_ADMIN_RESET, BGP_CEASE_ADMIN_SHUTDOWN, BGP_CEASE_HARD_RESET, BGP_ERROR_CEASE, BGP_NOTIFICATION, MAX_EXTENDED_MESSAGE_LEN,
+    MAX_SHUTDOWN_MSG_LEN, MIN_NOTIFICATION_LEN,
+};
+
+/// A BGP NOTIFICATION message (RFC 4271 section 4.5).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Notification {
+    pub error_code: u8,
+    pub subcode: u8,
+    pub data: Vec<u8>,
+}
+
+impl Notification {
+    pub fn new(error_code: u8, subcode: u8, data: Vec<u8>) -> Self {
+        Notification { error_code, subcode, data }
+    }
+
+    /// A Cease / Hard Reset (RFC 8538) standing in for `inner`.
+    pub fn hard_reset(inner: &Notification) -> Self {
+        let mut data = Vec::with_capacity(2 + inner.data.len());
+        data.push(inner.error_code);
+        data.push(inner.subcode);
+        data.extend_from_slice(&inner.data);
+        Notification::new(BGP_ERROR_CEASE, BGP_CEASE_HARD_RESET, data)
+    }
+
+    /// Parse a complete NOTIFICATION, including the 19-byte BGP header.
+    pub fn parse(bytes: &[u8]) -> Result<Self, DecodeError> {
+        Self::parse_with(bytes, &SessionContext::default())
+    }
+
+    /// Parse with the size limit of `context`, e.g. 65535 bytes once
+    /// Extended Message has been negotiated.
+    pub fn parse_with(bytes: &[u8], context: &SessionContext) -> Result<Self, DecodeError> {
+        validate_bgp_message(bytes, context)?;
+
+        Ok(Notification {
+            error_code: bytes[19],
+            subcode: bytes[20],
+            data: bytes[MIN_NOTIFICATION_LEN..].to_vec(),
+        })
+    }
+
+    /// Parse a hex dump; whitespace, `:` and `-` separators are ignored.
+    pub fn from_hex(hex_input: &str) -> Result<Self, DecodeError> {
+        Self::parse(&parse_hex(hex_input)?)
+    }
+
+    /// Build a notification whose data field is derived from a typed value,
+    /// e.g. `(1, 2, "length", "4097")` or `(6, 2, "message", "maintenance")`.
+    pub fn with_data(
+        error_code: u8,
+        subcode: u8,
+        data_type: &str,
+        data_value: &str,
+    ) -> Result<Self, EncodeError> {
+        if error_code_entry(error_code).is_none() {
+            return Err(EncodeError::InvalidErrorCode { actual: error_code });
+        }
+
+        let mut data = Vec::new();
+
+        match (error_code, subcode, data_type) {
+            // Message Header Errors
+            (1, 2, "length") => {
+                let length = parse_u16_bounded(data_value, 0, 65535, "length")?;
+                data.extend_from_slice(&length.to_be_bytes());
+            },
+            (1, 3, "type") => {
+                let msg_type = parse_u8_bounded(data_value, 0, 255, "message type")?;
+                data.push(msg_type);
+            },
+
+            // OPEN Message Errors
+            (2, 1, "version") => {
+                let version = parse_u16_bounded(data_value, 1, 255, "BGP version")?;
+                data.extend_from_slice(&version.to_be_bytes());
+            },
+
+            // UPDATE Message Errors
+            (3, 3, "attribute") => {
+                let attr_type = parse_u8_bounded(data_value, 1, 255, "attribute type")?;
+                data.push(attr_type);
+            },
+
+            // FSM Errors
+            (5, 1, "message_type") | (5, 2, "message_type") | (5, 3, "message_type") => {
+                let msg_type = parse_u8_bounded(data_value, 1, 5, "message type")?;
+                data.push(msg_type);
+            },
+
+            // Cease: Maximum Number of Prefixes Reached (RFC 4486 section 4)
+            (6, 1, "prefix_limit") => {
+                let [afi, safi, limit] = data_value.split_whitespace().collect::<Vec<_>>()[..] else {
+                    return Err(EncodeError::InvalidFormat { field: "prefix limit", expected: "<afi> <safi> <limit>" });
+                };
+                let afi = parse_afi(afi).ok_or(EncodeError::InvalidNumber { field: "AFI", value: afi.to_string() })?;
+                let safi = parse_safi(safi).ok_or(EncodeError::InvalidNumber { field: "SAFI", value: safi.to_string() })?;
+                let limit = parse_u32_bounded(limit, 0, u32::MAX, "prefix limit")?;
+                data.extend_from_slice(&afi.to_be_bytes());
+                data.push(safi);
+                data.extend_from_slice(&limit.to_be_bytes());
+            },
+
+            // Hard Reset wrapping an RFC 9003 shutdown communication (RFC 8538)
+            (6, 9, "shutdown") | (6, 9, "reset") => {
+                let inner_subcode = if data_type == "shutdown" { BgpCeaseSubcode::AdminShutdown } else { BgpCeaseSubcode::AdminReset };
+                let inner = ShutdownCommunication::new(inner_subcode, data_value).to_notification()?;
+                data = Notification::hard_reset(&inner).data;
+            },
+
+            // Cease with shutdown message
+            (6, 2, "message") | (6, 4, "message") => {
+                let utf8_bytes = data_value.as_bytes();
+                if utf8_bytes.len() > MAX_SHUTDOWN_MSG_LEN {
+                    return Err(EncodeError::ShutdownMessageTooLong {
+                        actual: utf8_bytes.len(),
+                        max: MAX_SHUTDOWN_MSG_LEN,
+                    });
+                }
+                data.push(utf8_bytes.len() as u8);
+                data.extend_from_slice(utf8_bytes);
+            },
+
+            // Raw hex data
+            (_, _, "hex") => {
+                // The session's actual limit is enforced by to_bytes_with
+                data = parse_hex_bounded(data_value, MAX_EXTENDED_MESSAGE_LEN - MIN_NOTIFICATION_LEN)?;
+            },
+
+            _ => {
+                if !data_value.is_empty() {
+                    return Err(EncodeError::UnsupportedData { error_code, subcode });
+                }
+            }
+        }
+
+        Ok(Notification { error_code, subcode, data })
+    }
+
+    /// Serialize to wire format, header included.
+    pub fn to_bytes(&self) -> Result<Vec<u8>, EncodeError> {
+        self.to_bytes_with(&SessionContext::default())
+    }
+
+    /// Serialize within the size limit of `context`.
+    pub fn to_bytes_with(&self, context: &SessionContext) -> Result<Vec<u8>, EncodeError> {
+        if error_code_entry(self.error_code).is_none() {
+            return Err(EncodeError::InvalidErrorCode { actual: self.error_code });
+        }
+
+        // Bounds checking for data
+        let max_data = context.max_message_len() - MIN_NOTIFICATION_LEN;
+        if self.data.len() > max_data {
+            return Err(EncodeError::DataTooLong { actual: self.data.len(), max: max_data });
+        }
+
+        let mut body = Vec::with_capacity(2 + self.data.len());
+        body.push(self.error_code);
+        body.push(self.subcode);
+        body.extend_from_slice(&self.data);
+
+        frame_message(BGP_NOTIFICATION, &body, context.max_message_len())
+    }
+
+    /// Human-readable error code and subcode names.
+    pub fn names(&self) -> (String, String) {
+        get_error_names(self.error_code, self.subcode)
+    }
+
+    /// One-line explanation of the data field.
+    pub fn interpretation(&self) -> String {
+        self.interpretation_with(&SessionContext::default())
+    }
+
+    /// Explanation of the data field; length limits follow `context`.
+    pub fn interpretation_with(&self, context: &SessionContext) -> String {
+        interpret_data(self.error_code, self.subcode, &self.data, context)
+    }
+
+    /// The notification carried by a Hard Reset (RFC 8538 section 3).
+    /// A Hard Reset inside a Hard Reset is not unwrapped.
+    pub fn encapsulated(&self) -> Option<Notification> {
+        if !self.is_hard_reset() || self.data.len() < 2 {
+            return None;
+        }
+        let inner = Notification::new(self.data[0], self.data[1], self.data[2..].to_vec());
+        (!inner.is_hard_reset()).then_some(inner)
+    }
+
+    fn is_hard_reset(&self) -> bool {
+        self.error_code == BGP_ERROR_CEASE && self.subcode == BGP_CEASE_HARD_RESET
+    }
+
+    /// Caveats about the message as a whole, e.g. an extended length or
+    /// a code IANA has not assigned or has since deprecated.
+    pub fn notes(&self) -> Vec<String> {
+        let mut notes: Vec<String> = extended_length_note(MIN_NOTIFICATION_LEN + self.data.len()).into_iter().collect();
+        match (error_code_entry(self.error_code), subcode_entry(self.error_code, self.subcode)) {
+            (None, _) => notes.push(format!("Error code {} is not assigned by IANA", self.error_code)),
+            (Some(entry), None) => notes.push(format!(
+                "Subcode {} is not assigned for error code {} ({})",
+                self.subcode, entry.code, entry.name
+            )),
+            (Some(_), Some(entry)) if entry.deprecated => {
+                notes.push(format!("Subcode {} ({}) is deprecated ({})", entry.subcode, entry.name, entry.rfc));
+            },
+            _ => {},
+        }
+        // RFC 4486 senders leave the data out entirely, which is fine
+        let administrative = matches!(self.subcode, BGP_CEASE_ADMIN_SHUTDOWN | BGP_CEASE_ADMIN_RESET);
+        if self.error_code == BGP_ERROR_CEASE && administrative && !self.data.is_empty() {
+            let shutdown = LenientShutdown::from_data(self.subcode, &self.data);
+            notes.extend(shutdown.diagnostics.iter().map(|e| format!("Malformed shutdown message: {}", e)));
+            notes.extend(shutdown.notes());
+        }
+        notes
+    }
+}
+
+impl UniversalDecodeResponse {
+    /// Describe `notification` as decoded on a session with `context`.
+    pub fn with_context(notification: &Notification, context: &SessionContext) -> Self {
+        let (error_name, subcode_name) = notification.names();
+
+        UniversalDecodeResponse {
+            error_code: notification.error_code,
+            error_name,
+            subcode: notification.subcode,
+            subcode_name,
+            data_length: notification.data.len(),
+            data_hex: to_hex(&notification.data),
+            interpretation: notification.interpretation_with(context),
+            notes: notification.notes(),
+            encapsulated: notification.encapsulated()
+                .map(|inner| Box::new(Self::with_context(&inner, context))),
+        }
+    }
+}
+
+impl From<&Notification> for UniversalDecodeResponse {
+    fn from(notification: &Notification) -> Self {
+        Self::with_context(notification, &SessionContext::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_to_bytes_round_trip() {
+        let notification = Notification::new(6, 2, vec![0x02, b'h', b'i']);
+        let bytes = notification.to_bytes().unwrap();
+        assert_eq!(bytes.len(), 24);
+        assert_eq!(&bytes[16..18], &[0x00, 0x18]);
+        assert_eq!(Notification::parse(&bytes).unwrap(), notification);
+
+        let send_hold_timer = Notification::with_data(8, 0, "", "").unwrap();
+        assert_eq!(send_hold_timer.names().0, "Send Hold Timer Expired");
+        assert!(send_hold_timer.notes().is_empty());
+        assert_eq!(Notification::new(9, 0, vec![]).to_bytes().unwrap_err().to_string(), "Invalid error code: must be 1-8");
+
+        // Unassigned codes still decode, with a note
+        let bytes = Notification::new(8, 0, vec![]).to_bytes().unwrap();
+        let mut unknown = bytes.clone();
+        unknown[19] = 42;
+        let decoded = Notification::parse(&unknown).unwrap();
+        assert_eq!(decoded.names().0, "Unknown Error");
+        assert_eq!(decoded.notes(), vec!["Error code 42 is not assigned by IANA"]);
+
+        let deprecated = Notification::new(3, 7, vec![]);
+        assert_eq!(deprecated.names().1, "AS Routing Loop");
+        assert_eq!(deprecated.notes(), vec!["Subcode 7 (AS Routing Loop) is deprecated (RFC 4271)"]);
+    }
+
+    #[test]
+    fn test_hard_reset() {
+        let n = Notification::with_data(6, 9, "shutdown", "upgrading").unwrap();
+        assert_eq!(&n.data[..3], &[6, 2, 9]);
+        assert_eq!(
+            n.interpretation(),
+            "Hard Reset of Cease / Administrative Shutdown (6/2): Shutdown message: \"upgrading\""
+        );
+
+        let response = UniversalDecodeResponse::from(&n);
+        let inner = response.encapsulated.unwrap();
+        assert_eq!((inner.error_code, inner.subcode), (6, 2));
+        assert!(inner.encapsulated.is_none());
+
+        // Hold Timer Expired replaced by a Hard Reset, and a nested Hard Reset
+        let hold_timer = Notification::hard_reset(&Notification::new(4, 0, vec![]));
+        assert_eq!(hold_timer.encapsulated(), Some(Notification::new(4, 0, vec![])));
+        let nested = Notification::hard_reset(&hold_timer);
+        assert_eq!(nested.encapsulated(), None);
+        assert!(nested.interpretation().ends_with("nested Hard Reset"));
+    }
+
+    #[test]
+    fn test_extended_message() {
+        let notification = Notification::new(3, 1, vec![0; 5000]);
+        assert_eq!(notification.to_bytes().unwrap_err().code(), "data_too_long");
+
+        let extended = SessionContext::new(true);
+        let bytes = notification.to_bytes_with(&extended).unwrap();
+        assert_eq!(Notification::parse(&bytes).unwrap_err().code(), "message_too_long");
+
+        let decoded = Notification::parse_with(&bytes, &extended).unwrap();
+        let response = UniversalDecodeResponse::with_context(&decoded, &extended);
+        assert!(response.notes[0].contains("Extended Message"));
+
+        let bad_length = Notification::new(1, 2, vec![0x13, 0x88]);
+        assert!(bad_length.interpretation().contains("up to 65535 with Extended Message"));
+        assert!(bad_length.interpretation_with(&extended).contains("valid range: 19-65535"));
+    }
+
+    #[test]
+    fn test_with_data() {
+        let n = Notification::with_data(1, 2, "length", "4097").unwrap();
+        assert_eq!(n.data, vec![0x10, 0x01]);
+
+        let n = Notification::with_data(6, 1, "prefix_limit", "ipv6 unicast 200000").unwrap();
+        assert_eq!(n.data, vec![0x00, 0x02, 0x01, 0x00, 0x03, 0x0d, 0x40]);
+        assert_eq!(n.interpretation(), "IPv6 unicast, limit 200000");
+        assert_eq!(
+            Notification::with_data(6, 1, "prefix_limit", "ipv6 200000").unwrap_err().code(),
+            "invalid_format"
+        );
+
+        let n = Notification::with_data(6, 4, "message", "reboot").unwrap();
+        assert_eq!(n.data[0], 6);
+        assert_eq!(&n.data[1..], b"reboot");
+
+        assert!(Notification::with_data(4, 0, "", "").unwrap().data.is_empty());
+        assert_eq!(
+            Notification::with_data(4, 0, "", "oops"),
+            Err(EncodeError::UnsupportedData { error_code: 4, subcode: 0 })
+        );
+        assert_eq!(
+            Notification::with_data(1, 2, "length", "70000").unwrap_err().code(),
+            "out_of_range"
+        );
+        assert_eq!(
+            Notification::with_data(3, 1, "hex", "00 1g").unwrap_err().offset(),
+            Some(4)
+        );
+    }
+}
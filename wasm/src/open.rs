@@ -0,0 +1,395 @@
+use std::net::Ipv4Addr;
+
+use serde::{Deserialize, Serialize};
+
+use crate::reader::Reader;
+use crate::{
+    frame_message, parse_hex, validate_message, Capability, DecodeError, EncodeError,
+    OpenDecodeResponse, BGP_HEADER_LEN, BGP_OPEN, MAX_BGP_MESSAGE_LEN, MIN_NOTIFICATION_LEN,
+};
+
+const RFC_OPEN: &str = "RFC 4271 4.2";
+const RFC_OPEN_ERROR: &str = "RFC 4271 6.2";
+const PARAM_CAPABILITIES: u8 = 2;
+// RFC 9072: a non-extended length and type of 255 announce extended parameters
+const PARAM_EXTENDED: u8 = 255;
+
+/// Smallest OPEN message: header plus the 10 fixed octets.
+pub const MIN_OPEN_LEN: usize = 29;
+/// Placeholder 2-octet AS for speakers with a 4-octet AS number (RFC 6793).
+pub const AS_TRANS: u16 = 23456;
+
+/// A BGP OPEN message (RFC 4271 section 4.2).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Open {
+    #[serde(default = "default_version")]
+    pub version: u8,
+    pub my_as: u16,
+    pub hold_time: u16,
+    pub bgp_identifier: Ipv4Addr,
+    #[serde(default)]
+    pub optional_parameters: Vec<OptionalParameter>,
+}
+
+/// An OPEN optional parameter (RFC 4271 section 4.2, RFC 5492).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum OptionalParameter {
+    Capabilities { capabilities: Vec<Capability> },
+    Unknown { param_type: u8, value: Vec<u8> },
+}
+
+fn default_version() -> u8 {
+    4
+}
+
+impl Open {
+    /// Build a version 4 OPEN carrying `capabilities` in one parameter.
+    ///
+    /// AS numbers above 65535 are sent as AS_TRANS in My AS, and the 4-octet
+    /// AS capability is added if `capabilities` does not already include it.
+    pub fn new(asn: u32, hold_time: u16, bgp_identifier: Ipv4Addr, mut capabilities: Vec<Capability>) -> Self {
+        let my_as = u16::try_from(asn).unwrap_or(AS_TRANS);
+        let has_four_octet = capabilities.iter().any(|c| matches!(c, Capability::FourOctetAs { .. }));
+        if my_as == AS_TRANS && !has_four_octet {
+            capabilities.push(Capability::FourOctetAs { asn });
+        }
+
+        let optional_parameters = if capabilities.is_empty() {
+            Vec::new()
+        } else {
+            vec![OptionalParameter::Capabilities { capabilities }]
+        };
+
+        Open { version: 4, my_as, hold_time, bgp_identifier, optional_parameters }
+    }
+
+    /// Parse a complete OPEN message, header included. OPEN is limited to
+    /// 4096 bytes even with Extended Message (RFC 8654 section 3).
+    pub fn parse(bytes: &[u8]) -> Result<Self, DecodeError> {
+        validate_message(bytes, BGP_OPEN, MIN_OPEN_LEN, MAX_BGP_MESSAGE_LEN)?;
+
+        let mut r = Reader::new(&bytes[BGP_HEADER_LEN..], BGP_HEADER_LEN, RFC_OPEN);
+        let version = r.u8("version")?;
+        let my_as = r.u16("My Autonomous System")?;
+        let hold_time = r.u16("Hold Time")?;
+        let bgp_identifier = Ipv4Addr::from(r.u32("BGP Identifier")?);
+
+        let mut params_len = r.u8("optional parameters length")? as usize;
+        let extended = params_len == 255 && r.peek() == Some(PARAM_EXTENDED);
+        if extended {
+            r.u8("extended parameters marker")?;
+            params_len = r.u16("extended optional parameters length")? as usize;
+        }
+
+        let mut params = r.sub(params_len, "optional parameters")?;
+        r.finish("optional parameters")?;
+
+        let mut optional_parameters = Vec::new();
+        while !params.is_empty() {
+            let param_type = params.u8("parameter type")?;
+            let len = if extended {
+                params.u16("parameter length")? as usize
+            } else {
+                params.u8("parameter length")? as usize
+            };
+            let mut value = params.sub(len, "parameter value")?;
+
+            if param_type != PARAM_CAPABILITIES {
+                optional_parameters.push(OptionalParameter::Unknown {
+                    param_type,
+                    value: value.rest().to_vec(),
+                });
+                continue;
+            }
+
+            let capabilities = read_capabilities(&mut value)?;
+            optional_parameters.push(OptionalParameter::Capabilities { capabilities });
+        }
+
+        Ok(Open { version, my_as, hold_time, bgp_identifier, optional_parameters })
+    }
+
+    /// Parse a hex dump; whitespace, `:` and `-` separators are ignored.
+    pub fn from_hex(hex_input: &str) -> Result<Self, DecodeError> {
+        Self::parse(&parse_hex(hex_input)?)
+    }
+
+    /// Serialize to wire format, header included. Parameters longer than
+    /// 255 octets switch to the RFC 9072 extended encoding.
+    pub fn to_bytes(&self) -> Result<Vec<u8>, EncodeError> {
+        let mut params = Vec::with_capacity(self.optional_parameters.len());
+        for param in &self.optional_parameters {
+            let (param_type, value) = match param {
+                OptionalParameter::Capabilities { capabilities } => {
+                    let mut value = Vec::new();
+                    for capability in capabilities {
+                        value.extend_from_slice(&capability.to_bytes()?);
+                    }
+                    (PARAM_CAPABILITIES, value)
+                },
+                OptionalParameter::Unknown { param_type, value } => (*param_type, value.clone()),
+            };
+            params.push((param_type, value));
+        }
+
+        let short_len: usize = params.iter().map(|(_, value)| 2 + value.len()).sum();
+        let extended = short_len > 255 || params.iter().any(|(_, value)| value.len() > 255);
+
+        let mut encoded = Vec::new();
+        for (param_type, value) in &params {
+            encoded.push(*param_type);
+            if extended {
+                if value.len() > u16::MAX as usize {
+                    return Err(EncodeError::FieldTooLong {
+                        field: "optional parameter",
+                        actual: value.len(),
+                        max: u16::MAX as usize,
+                    });
+                }
+                encoded.extend_from_slice(&(value.len() as u16).to_be_bytes());
+            } else {
+                encoded.push(value.len() as u8);
+            }
+            encoded.extend_from_slice(value);
+        }
+
+        let mut body = Vec::with_capacity(13 + encoded.len());
+        body.push(self.version);
+        body.extend_from_slice(&self.my_as.to_be_bytes());
+        body.extend_from_slice(&self.hold_time.to_be_bytes());
+        body.extend_from_slice(&self.bgp_identifier.octets());
+        if extended {
+            body.push(255);
+            body.push(PARAM_EXTENDED);
+            // Oversized parameters are rejected by frame_message below
+            body.extend_from_slice(&(encoded.len() as u16).to_be_bytes());
+        } else {
+            body.push(encoded.len() as u8);
+        }
+        body.extend_from_slice(&encoded);
+
+        frame_message(BGP_OPEN, &body, MAX_BGP_MESSAGE_LEN)
+    }
+
+    /// All capabilities, across every Capabilities parameter.
+    pub fn capabilities(&self) -> impl Iterator<Item = &Capability> {
+        self.optional_parameters.iter().flat_map(|param| match param {
+            OptionalParameter::Capabilities { capabilities } => capabilities.as_slice(),
+            OptionalParameter::Unknown { .. } => &[],
+        })
+    }
+
+    /// The speaker's AS number: the 4-octet AS capability if present, My AS otherwise.
+    pub fn asn(&self) -> u32 {
+        self.capabilities()
+            .find_map(|c| match c {
+                Capability::FourOctetAs { asn } => Some(*asn),
+                _ => None,
+            })
+            .unwrap_or(self.my_as as u32)
+    }
+
+    /// Human-readable description, one line per field or capability.
+    pub fn summary(&self) -> Vec<String> {
+        let mut lines = Vec::new();
+
+        if self.version == 4 {
+            lines.push("Version: 4".to_string());
+        } else {
+            lines.push(format!("Version: {} (only version 4 is defined)", self.version));
+        }
+
+        if self.my_as == AS_TRANS {
+            lines.push(format!("My AS: {} (AS_TRANS, real AS{})", AS_TRANS, self.asn()));
+        } else {
+            lines.push(format!("My AS: {}", self.my_as));
+        }
+
+        lines.push(match self.hold_time {
+            0 => "Hold Time: 0 (keepalives disabled)".to_string(),
+            1 | 2 => format!("Hold Time: {} seconds (invalid, must be 0 or at least 3)", self.hold_time),
+            t => format!("Hold Time: {} seconds", t),
+        });
+
+        if self.bgp_identifier.is_unspecified() {
+            lines.push("BGP Identifier: 0.0.0.0 (invalid, must be non-zero)".to_string());
+        } else {
+            lines.push(format!("BGP Identifier: {}", self.bgp_identifier));
+        }
+
+        for param in &self.optional_parameters {
+            match param {
+                OptionalParameter::Capabilities { capabilities } => {
+                    lines.extend(capabilities.iter().map(|c| format!("Capability: {}", c)));
+                },
+                OptionalParameter::Unknown { param_type, value } => {
+                    lines.push(format!("Optional parameter {}: {} bytes", param_type, value.len()));
+                },
+            }
+        }
+
+        lines
+    }
+}
+
+// Capability TLVs (code, length, value) up to the end of `r`, RFC 5492 section 4
+fn read_capabilities(r: &mut Reader) -> Result<Vec<Capability>, DecodeError> {
+    let mut capabilities = Vec::new();
+    while !r.is_empty() {
+        let code = r.u8("capability code")?;
+        let len = r.u8("capability length")? as usize;
+        let offset = r.offset();
+        capabilities.push(Capability::parse(code, r.take(len, "capability value")?, offset)?);
+    }
+    Ok(capabilities)
+}
+
+/// Describe the Data field of an OPEN Message Error NOTIFICATION
+/// (RFC 4271 section 6.2, RFC 5492 section 3).
+pub(crate) fn describe_notification_data(subcode: u8, data: &[u8]) -> String {
+    let mut r = Reader::new(data, MIN_NOTIFICATION_LEN, RFC_OPEN_ERROR);
+    match (subcode, data.len()) {
+        // 2-octet or 4-octet AS, depending on what the speaker checked
+        (2, 2) | (2, 4) => {
+            let asn = data.iter().fold(0u32, |asn, &b| (asn << 8) | b as u32);
+            if data.len() == 2 && asn == AS_TRANS as u32 {
+                format!("Bad peer AS: AS{} (AS_TRANS, the real AS is in the 4-octet AS capability)", asn)
+            } else {
+                format!("Bad peer AS: AS{}", asn)
+            }
+        },
+        (6, 2) => {
+            let hold_time = ((data[0] as u16) << 8) | (data[1] as u16);
+            if hold_time == 1 || hold_time == 2 {
+                format!("Unacceptable hold time: {}s (must be 0 or at least 3)", hold_time)
+            } else {
+                format!("Unacceptable hold time: {}s", hold_time)
+            }
+        },
+        // Unsupported Capability: the capability TLVs the peer refused
+        (7, _) => match read_capabilities(&mut r) {
+            Ok(capabilities) => {
+                let list: Vec<String> = capabilities.iter().map(Capability::to_string).collect();
+                format!("Unsupported capability: {}", list.join("; "))
+            },
+            Err(e) => format!("Unsupported capability, undecodable: {}", e),
+        },
+        // Unsupported Optional Parameter: the parameter TLVs
+        (4, _) => {
+            let mut params = Vec::new();
+            while !r.is_empty() {
+                let param = r.u8("parameter type").and_then(|param_type| {
+                    let len = r.u8("parameter length")? as usize;
+                    let mut value = r.sub(len, "parameter value")?;
+                    Ok(if param_type == PARAM_CAPABILITIES {
+                        let capabilities: Vec<String> =
+                            read_capabilities(&mut value)?.iter().map(Capability::to_string).collect();
+                        format!("Capabilities ({}): {}", param_type, capabilities.join("; "))
+                    } else {
+                        format!("parameter {}, {} bytes", param_type, len)
+                    })
+                });
+                match param {
+                    Ok(param) => params.push(param),
+                    Err(e) => return format!("Unsupported optional parameter, undecodable: {}", e),
+                }
+            }
+            format!("Unsupported optional parameter: {}", params.join("; "))
+        },
+        _ => format!("{} bytes of data", data.len()),
+    }
+}
+
+impl From<&Open> for OpenDecodeResponse {
+    fn from(open: &Open) -> Self {
+        OpenDecodeResponse {
+            open: open.clone(),
+            asn: open.asn(),
+            summary: open.summary(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::AddPathFamily;
+
+    // OPEN from a BIRD 2 speaker: AS 65001, hold 240, id 10.0.0.1,
+    // MP IPv4 unicast, route refresh, 4-octet AS 65001
+    const BIRD_OPEN: &str = "ffffffffffffffffffffffffffffffff 002f 01 04 fde9 00f0 0a000001 12 \
+                             02 10 0104 00010001 0200 4104 0000fde9 0600";
+
+    #[test]
+    fn test_parse_open() {
+        let open = Open::from_hex(BIRD_OPEN).unwrap();
+        assert_eq!(open.version, 4);
+        assert_eq!(open.my_as, 65001);
+        assert_eq!(open.hold_time, 240);
+        assert_eq!(open.bgp_identifier, Ipv4Addr::new(10, 0, 0, 1));
+
+        let capabilities: Vec<_> = open.capabilities().cloned().collect();
+        assert_eq!(capabilities, vec![
+            Capability::Multiprotocol { afi: 1, safi: 1 },
+            Capability::RouteRefresh,
+            Capability::FourOctetAs { asn: 65001 },
+            Capability::ExtendedMessage,
+        ]);
+        assert_eq!(open.to_bytes().unwrap(), parse_hex(BIRD_OPEN).unwrap());
+    }
+
+    #[test]
+    fn test_open_four_octet_asn() {
+        let open = Open::new(4_200_000_000, 90, Ipv4Addr::new(192, 0, 2, 1), vec![
+            Capability::AddPath { families: vec![AddPathFamily { afi: 2, safi: 1, send_receive: 1 }] },
+        ]);
+        assert_eq!(open.my_as, AS_TRANS);
+
+        let parsed = Open::parse(&open.to_bytes().unwrap()).unwrap();
+        assert_eq!(parsed.asn(), 4_200_000_000);
+        assert!(parsed.summary()[1].contains("AS_TRANS"));
+    }
+
+    #[test]
+    fn test_open_extended_parameters() {
+        let capabilities = (0..100).map(|afi| Capability::Multiprotocol { afi, safi: 1 }).collect();
+        let open = Open::new(65000, 180, Ipv4Addr::new(192, 0, 2, 1), capabilities);
+        let bytes = open.to_bytes().unwrap();
+        assert_eq!(&bytes[28..30], &[255, 255]);
+        assert_eq!(Open::parse(&bytes).unwrap(), open);
+    }
+
+    #[test]
+    fn test_open_malformed() {
+        // Optional parameters length claims more than the message holds
+        let mut bytes = parse_hex(BIRD_OPEN).unwrap();
+        bytes[28] = 0x15;
+        let err = Open::parse(&bytes).unwrap_err();
+        assert_eq!(err.code(), "truncated");
+        assert_eq!(err.offset(), Some(29));
+
+        let notification = "ffffffffffffffffffffffffffffffff0015030400";
+        assert_eq!(Open::from_hex(notification).unwrap_err().code(), "message_too_short");
+    }
+
+    #[test]
+    fn test_open_error_data() {
+        assert_eq!(describe_notification_data(2, &[0x5b, 0xa0]), "Bad peer AS: AS23456 (AS_TRANS, the real AS is in the 4-octet AS capability)");
+        assert_eq!(describe_notification_data(2, &[0xfa, 0x56, 0xea, 0x00]), "Bad peer AS: AS4200000000");
+        assert_eq!(describe_notification_data(6, &[0x00, 0x02]), "Unacceptable hold time: 2s (must be 0 or at least 3)");
+
+        // Unsupported Capability: Multiprotocol IPv6 unicast, BGP Role
+        let data = [0x01, 0x04, 0x00, 0x02, 0x00, 0x01, 0x09, 0x01, 0x03];
+        assert_eq!(
+            describe_notification_data(7, &data),
+            "Unsupported capability: Multiprotocol Extensions: IPv6 unicast; BGP Role: Customer (3)"
+        );
+
+        // The same capabilities wrapped in an optional parameter, then a truncated one
+        let mut data = vec![0x02, 0x09];
+        data.extend_from_slice(&[0x01, 0x04, 0x00, 0x02, 0x00, 0x01, 0x09, 0x01, 0x03]);
+        assert!(describe_notification_data(4, &data).starts_with("Unsupported optional parameter: Capabilities (2): "));
+        assert!(describe_notification_data(7, &[0x41, 0x04, 0x00]).contains("at byte 23"));
+    }
+}
@@ -0,0 +1,1191 @@
+// libpcap and pcapng capture files. TCP port 179 segments are pulled out
+// of each frame, reassembled per direction and framed with the same
+// MessageDecoder the hex input uses, giving a timeline of every message
+// on every BGP session in the capture. Crafted messages can go the other
+// way, into a pcap of a made-up session.
+
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+
+use serde::{Deserialize, Serialize};
+
+use crate::stream::split_messages;
+use crate::{
+    Capability, CaptureResponse, CapturedMessageResponse, DecodeError, EncodeError, FramedMessage,
+    Message, MessageDecoder, SessionContext, StreamItem, StreamMessageResponse,
+};
+
+/// TCP port BGP speakers listen on (RFC 4271 section 8.2.1).
+pub const BGP_PORT: u16 = 179;
+
+const RFC_PCAP: &str = "draft-ietf-opsawg-pcap";
+const RFC_PCAPNG: &str = "draft-ietf-opsawg-pcapng";
+
+const PCAPNG_SECTION_HEADER: u32 = 0x0a0d_0d0a;
+const PCAPNG_BYTE_ORDER: u32 = 0x1a2b_3c4d;
+
+// Fixed initial sequence numbers keep written captures reproducible
+const SYNTHETIC_CLIENT_ISN: u32 = 0x1000_0000;
+const SYNTHETIC_SERVER_ISN: u32 = 0x2000_0000;
+
+const TCP_FIN: u8 = 0x01;
+const TCP_SYN: u8 = 0x02;
+const TCP_RST: u8 = 0x04;
+const TCP_PSH: u8 = 0x08;
+const TCP_ACK: u8 = 0x10;
+
+/// Which of the two capture file formats was read.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CaptureFormat {
+    Pcap,
+    Pcapng,
+}
+
+/// When a packet was captured, since the Unix epoch.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub struct CaptureTime {
+    pub seconds: u64,
+    pub nanos: u32,
+}
+
+impl CaptureTime {
+    pub fn new(seconds: u64, nanos: u32) -> Self {
+        CaptureTime { seconds, nanos }
+    }
+
+    // `ticks` counted at `per_second` since the epoch
+    fn from_ticks(ticks: u64, per_second: u64) -> Self {
+        let nanos = (ticks % per_second) as u128 * 1_000_000_000 / per_second as u128;
+        CaptureTime { seconds: ticks / per_second, nanos: nanos as u32 }
+    }
+}
+
+/// RFC 3339 in UTC, with micro- or nanoseconds as the capture recorded them.
+impl fmt::Display for CaptureTime {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (year, month, day) = civil_date((self.seconds / 86_400) as i64);
+        let time = self.seconds % 86_400;
+        write!(f, "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}", year, month, day, time / 3_600, time % 3_600 / 60, time % 60)?;
+        if self.nanos.is_multiple_of(1_000) {
+            write!(f, ".{:06}Z", self.nanos / 1_000)
+        } else {
+            write!(f, ".{:09}Z", self.nanos)
+        }
+    }
+}
+
+// Days since 1970-01-01 to a proleptic Gregorian date (Howard Hinnant's
+// civil_from_days)
+fn civil_date(days: i64) -> (i64, u32, u32) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+/// One TCP connection carrying BGP.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TcpFlow {
+    pub id: usize,
+    /// The side that sent the SYN, or the first sender if the handshake
+    /// is not in the capture.
+    pub client: SocketAddr,
+    pub server: SocketAddr,
+    pub handshake: bool,
+    pub first_frame: usize,
+    pub packets: usize,
+    pub messages: usize,
+}
+
+/// A BGP message and the packet that completed it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CapturedMessage {
+    pub flow: usize,
+    /// 1-based packet number, as Wireshark counts them.
+    pub frame: usize,
+    pub timestamp: CaptureTime,
+    pub source: SocketAddr,
+    pub destination: SocketAddr,
+    /// Offsets count from the start of this direction's byte stream.
+    pub message: FramedMessage,
+}
+
+/// Every BGP message in a capture file, in capture order.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Capture {
+    pub format: CaptureFormat,
+    pub packets: usize,
+    pub flows: Vec<TcpFlow>,
+    pub messages: Vec<CapturedMessage>,
+    /// Lost segments, skipped bytes, unsupported link types and the like.
+    pub warnings: Vec<String>,
+}
+
+impl Capture {
+    /// Read a pcap or pcapng file.
+    pub fn parse(bytes: &[u8]) -> Result<Self, DecodeError> {
+        Self::parse_with(bytes, &SessionContext::default())
+    }
+
+    /// Read with the limits of `context`, which each flow keeps until both
+    /// of its OPENs advertise Extended Message.
+    pub fn parse_with(bytes: &[u8], context: &SessionContext) -> Result<Self, DecodeError> {
+        let magic = match bytes.get(..4) {
+            Some(magic) => u32::from_be_bytes([magic[0], magic[1], magic[2], magic[3]]),
+            None => {
+                return Err(DecodeError::Truncated {
+                    field: "capture file header",
+                    offset: 0,
+                    expected: 4,
+                    actual: bytes.len(),
+                    rfc: RFC_PCAP,
+                })
+            },
+        };
+
+        let mut warnings = Vec::new();
+        let (format, packets) = if magic == PCAPNG_SECTION_HEADER {
+            (CaptureFormat::Pcapng, read_pcapng(bytes, &mut warnings)?)
+        } else {
+            (CaptureFormat::Pcap, read_pcap(bytes, magic, &mut warnings)?)
+        };
+
+        let mut sessions = Sessions::new(*context);
+        for packet in &packets {
+            sessions.packet(packet);
+        }
+        let (flows, mut messages, session_warnings) = sessions.finish();
+        warnings.extend(session_warnings);
+        messages.sort_by_key(|message| (message.timestamp, message.frame));
+
+        Ok(Capture { format, packets: packets.len(), flows, messages, warnings })
+    }
+}
+
+impl CaptureResponse {
+    /// Describe `capture` as decoded with `context`.
+    pub fn with_context(capture: &Capture, context: &SessionContext) -> Self {
+        CaptureResponse {
+            format: capture.format,
+            packets: capture.packets,
+            flows: capture.flows.clone(),
+            messages: capture.messages.iter()
+                .map(|captured| CapturedMessageResponse::with_context(captured, context))
+                .collect(),
+            warnings: capture.warnings.clone(),
+        }
+    }
+}
+
+impl From<&Capture> for CaptureResponse {
+    fn from(capture: &Capture) -> Self {
+        Self::with_context(capture, &SessionContext::default())
+    }
+}
+
+impl CapturedMessageResponse {
+    /// Describe `captured` as decoded with `context`.
+    pub fn with_context(captured: &CapturedMessage, context: &SessionContext) -> Self {
+        CapturedMessageResponse {
+            flow: captured.flow,
+            frame: captured.frame,
+            timestamp: captured.timestamp,
+            time: captured.timestamp.to_string(),
+            source: captured.source.to_string(),
+            destination: captured.destination.to_string(),
+            message: StreamMessageResponse::with_context(&captured.message, context),
+        }
+    }
+}
+
+impl From<&CapturedMessage> for CapturedMessageResponse {
+    fn from(captured: &CapturedMessage) -> Self {
+        Self::with_context(captured, &SessionContext::default())
+    }
+}
+
+// A captured frame, before any protocol is looked at
+struct Packet<'a> {
+    frame: usize,
+    time: CaptureTime,
+    link_type: u16,
+    data: &'a [u8],
+}
+
+// Cursor for capture files, which come in either byte order
+struct FileReader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+    base: usize,
+    little_endian: bool,
+    rfc: &'static str,
+}
+
+impl<'a> FileReader<'a> {
+    fn new(bytes: &'a [u8], base: usize, little_endian: bool, rfc: &'static str) -> Self {
+        FileReader { bytes, pos: 0, base, little_endian, rfc }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.pos == self.bytes.len()
+    }
+
+    fn take(&mut self, len: usize, field: &'static str) -> Result<&'a [u8], DecodeError> {
+        let remaining = self.bytes.len() - self.pos;
+        if len > remaining {
+            return Err(DecodeError::Truncated {
+                field,
+                offset: self.base + self.pos,
+                expected: len,
+                actual: remaining,
+                rfc: self.rfc,
+            });
+        }
+        let slice = &self.bytes[self.pos..self.pos + len];
+        self.pos += len;
+        Ok(slice)
+    }
+
+    fn u16(&mut self, field: &'static str) -> Result<u16, DecodeError> {
+        let b = self.take(2, field)?;
+        let b = [b[0], b[1]];
+        Ok(if self.little_endian { u16::from_le_bytes(b) } else { u16::from_be_bytes(b) })
+    }
+
+    fn u32(&mut self, field: &'static str) -> Result<u32, DecodeError> {
+        let b = self.take(4, field)?;
+        let b = [b[0], b[1], b[2], b[3]];
+        Ok(if self.little_endian { u32::from_le_bytes(b) } else { u32::from_be_bytes(b) })
+    }
+}
+
+// Classic libpcap: a 24-byte file header, then a 16-byte header per record
+fn read_pcap<'a>(bytes: &'a [u8], magic: u32, warnings: &mut Vec<String>) -> Result<Vec<Packet<'a>>, DecodeError> {
+    let (little_endian, nanosecond) = match magic {
+        0xa1b2_c3d4 => (false, false),
+        0xd4c3_b2a1 => (true, false),
+        0xa1b2_3c4d => (false, true),
+        0x4d3c_b2a1 => (true, true),
+        _ => return Err(DecodeError::UnknownCaptureFormat { magic }),
+    };
+
+    let mut r = FileReader::new(bytes, 0, little_endian, RFC_PCAP);
+    // Magic, version, time zone, accuracy and snapshot length
+    r.take(20, "file header")?;
+    // The upper bits carry FCS length flags
+    let link_type = r.u32("link type")? as u16;
+
+    let mut packets = Vec::new();
+    while !r.is_empty() {
+        let mut record = || -> Result<Packet<'a>, DecodeError> {
+            let seconds = r.u32("timestamp")?;
+            let fraction = r.u32("timestamp")?;
+            let captured = r.u32("captured length")? as usize;
+            r.u32("original length")?;
+            let data = r.take(captured, "packet data")?;
+            let nanos = if nanosecond { fraction } else { fraction.saturating_mul(1_000) };
+            Ok(Packet {
+                frame: packets.len() + 1,
+                time: CaptureTime::new(seconds as u64, nanos.min(999_999_999)),
+                link_type,
+                data,
+            })
+        };
+        match record() {
+            Ok(packet) => packets.push(packet),
+            Err(e) => {
+                warnings.push(format!("Capture ends mid-record: {}", e));
+                break;
+            },
+        }
+    }
+    Ok(packets)
+}
+
+struct Interface {
+    link_type: u16,
+    ticks_per_second: u64,
+    offset_seconds: i64,
+}
+
+// pcapng: a sequence of blocks, each section declaring its byte order and
+// interfaces. Only the blocks that carry packets matter here
+fn read_pcapng<'a>(bytes: &'a [u8], warnings: &mut Vec<String>) -> Result<Vec<Packet<'a>>, DecodeError> {
+    let mut packets = Vec::new();
+    let mut interfaces: Vec<Interface> = Vec::new();
+    let mut little_endian = false;
+    let mut last_time = CaptureTime::default();
+    let mut pos = 0;
+
+    while pos < bytes.len() {
+        let block = &bytes[pos..];
+        let header = block_header(block, pos, &mut little_endian);
+        let (block_type, length) = match header {
+            Ok(header) => header,
+            // Without its first block this is not a pcapng file at all
+            Err(e) if pos == 0 => return Err(e),
+            Err(e) => {
+                warnings.push(format!("Capture ends mid-block: {}", e));
+                break;
+            },
+        };
+        if block_type == PCAPNG_SECTION_HEADER {
+            interfaces.clear();
+        }
+
+        let mut body = FileReader::new(&block[8..length - 4], pos + 8, little_endian, RFC_PCAPNG);
+        let frame = packets.len() + 1;
+        let packet = match block_type {
+            1 => interface_block(&mut body).map(|interface| {
+                interfaces.push(interface);
+                None
+            }),
+            2 | 3 | 6 => packet_block(block_type, &mut body, &interfaces, last_time).map(|(interface, time, data)| {
+                match interfaces.get(interface) {
+                    Some(interface) => Some(Packet { frame, time, link_type: interface.link_type, data }),
+                    None => {
+                        warnings.push(format!("Frame {}: no interface {} in this section", frame, interface));
+                        None
+                    },
+                }
+            }),
+            _ => Ok(None),
+        };
+
+        match packet {
+            Ok(Some(packet)) => {
+                last_time = packet.time;
+                packets.push(packet);
+            },
+            Ok(None) => {},
+            Err(e) => warnings.push(format!("Skipped malformed block: {}", e)),
+        }
+        pos += length;
+    }
+    Ok(packets)
+}
+
+// Type and total length of the block at `pos`, checked against what is
+// left of the file. A Section Header Block sets the byte order first
+fn block_header(block: &[u8], pos: usize, little_endian: &mut bool) -> Result<(u32, usize), DecodeError> {
+    if block.starts_with(&PCAPNG_SECTION_HEADER.to_be_bytes()) {
+        let mut r = FileReader::new(block, pos, false, RFC_PCAPNG);
+        r.take(8, "section header")?;
+        let order = r.u32("byte-order magic")?;
+        *little_endian = match order {
+            PCAPNG_BYTE_ORDER => false,
+            _ if order.swap_bytes() == PCAPNG_BYTE_ORDER => true,
+            _ => return Err(DecodeError::UnknownCaptureFormat { magic: order }),
+        };
+    }
+
+    let mut r = FileReader::new(block, pos, *little_endian, RFC_PCAPNG);
+    let block_type = r.u32("block type")?;
+    let length = r.u32("block length")? as usize;
+    if length < 12 || !length.is_multiple_of(4) || length > block.len() {
+        return Err(DecodeError::Truncated {
+            field: "block",
+            offset: pos,
+            expected: length.max(12),
+            actual: block.len(),
+            rfc: RFC_PCAPNG,
+        });
+    }
+    Ok((block_type, length))
+}
+
+// Interface, timestamp and data of an Enhanced (6), Simple (3) or
+// obsolete Packet Block (2). A Simple Packet Block has no timestamp and
+// is taken to follow the previous packet
+fn packet_block<'a>(
+    block_type: u32,
+    body: &mut FileReader<'a>,
+    interfaces: &[Interface],
+    last_time: CaptureTime,
+) -> Result<(usize, CaptureTime, &'a [u8]), DecodeError> {
+    if block_type == 3 {
+        let original = body.u32("original length")? as usize;
+        let data = &body.bytes[body.pos..];
+        return Ok((0, last_time, &data[..original.min(data.len())]));
+    }
+
+    let interface = if block_type == 6 {
+        body.u32("interface ID")? as usize
+    } else {
+        let interface = body.u16("interface ID")?;
+        body.u16("drops count")?;
+        usize::from(interface)
+    };
+    let high = u64::from(body.u32("timestamp")?);
+    let low = u64::from(body.u32("timestamp")?);
+    let captured = body.u32("captured length")? as usize;
+    body.u32("original length")?;
+    let data = body.take(captured, "packet data")?;
+
+    let time = match interfaces.get(interface) {
+        Some(settings) => {
+            let mut time = CaptureTime::from_ticks((high << 32) | low, settings.ticks_per_second);
+            time.seconds = time.seconds.saturating_add_signed(settings.offset_seconds);
+            time
+        },
+        None => last_time,
+    };
+    Ok((interface, time, data))
+}
+
+fn interface_block(body: &mut FileReader) -> Result<Interface, DecodeError> {
+    let link_type = body.u16("link type")?;
+    body.take(6, "interface description")?;
+    let mut interface = Interface { link_type, ticks_per_second: 1_000_000, offset_seconds: 0 };
+
+    // Options end at code 0 or with the block; a broken one only loses the rest
+    while !body.is_empty() {
+        let Ok(code) = body.u16("option code") else { break };
+        let Ok(len) = body.u16("option length").map(usize::from) else { break };
+        let Ok(value) = body.take(len, "option value") else { break };
+        let _ = body.take((4 - len % 4) % 4, "option padding");
+        match (code, value) {
+            (0, _) => break,
+            // if_tsresol: a power of ten, or of two with the top bit set
+            (9, &[resolution]) => {
+                let exponent = u32::from(resolution & 0x7f);
+                let ticks =
+                    if resolution & 0x80 == 0 { 10u64.checked_pow(exponent) } else { 1u64.checked_shl(exponent) };
+                interface.ticks_per_second = ticks.filter(|&ticks| ticks > 0).unwrap_or(interface.ticks_per_second);
+            },
+            (14, &[a, b, c, d, e, f, g, h]) => {
+                let bytes = [a, b, c, d, e, f, g, h];
+                interface.offset_seconds =
+                    if body.little_endian { i64::from_le_bytes(bytes) } else { i64::from_be_bytes(bytes) };
+            },
+            _ => {},
+        }
+    }
+    Ok(interface)
+}
+
+// The IP packet inside a frame of `link_type`, if it is IPv4 or IPv6
+fn ip_packet(link_type: u16, data: &[u8]) -> Option<&[u8]> {
+    let (ethertype, payload) = match link_type {
+        // BSD loopback: address family in the capturing host's byte order
+        0 | 108 => {
+            let family = data.get(..4)?;
+            let family = if link_type == 108 || family[0] == 0 { family[3] } else { family[0] };
+            return matches!(family, 2 | 10 | 24 | 28 | 30).then(|| &data[4..]);
+        },
+        1 => {
+            let mut offset = 12;
+            let mut ethertype = u16::from_be_bytes([*data.get(offset)?, *data.get(offset + 1)?]);
+            // 802.1Q and 802.1ad tags, possibly stacked
+            while matches!(ethertype, 0x8100 | 0x88a8 | 0x9100) {
+                offset += 4;
+                ethertype = u16::from_be_bytes([*data.get(offset)?, *data.get(offset + 1)?]);
+            }
+            (ethertype, data.get(offset + 2..)?)
+        },
+        // Raw IP
+        12 | 14 | 101 | 228 | 229 => return Some(data),
+        // Linux cooked capture v1 and v2
+        113 => (u16::from_be_bytes([*data.get(14)?, *data.get(15)?]), data.get(16..)?),
+        276 => (u16::from_be_bytes([*data.first()?, *data.get(1)?]), data.get(20..)?),
+        _ => return None,
+    };
+    matches!(ethertype, 0x0800 | 0x86dd).then_some(payload)
+}
+
+struct Segment<'a> {
+    source: SocketAddr,
+    destination: SocketAddr,
+    seq: u32,
+    flags: u8,
+    payload: &'a [u8],
+    // The capture's snapshot length cut off part of the payload
+    truncated: bool,
+}
+
+fn tcp_segment(ip: &[u8]) -> Option<Segment<'_>> {
+    let (source, destination, tcp, truncated) = match ip.first()? >> 4 {
+        4 => {
+            let header_len = usize::from(ip[0] & 0x0f) * 4;
+            let total_len = usize::from(u16::from_be_bytes([*ip.get(2)?, *ip.get(3)?]));
+            let fragment = u16::from_be_bytes([*ip.get(6)?, *ip.get(7)?]);
+            // More fragments, or not the first: BGP speakers set DF, so skip
+            if *ip.get(9)? != 6 || fragment & 0x3fff != 0 || header_len < 20 || header_len > ip.len() {
+                return None;
+            }
+            let addresses = ip.get(12..20)?;
+            let source = Ipv4Addr::new(addresses[0], addresses[1], addresses[2], addresses[3]);
+            let destination = Ipv4Addr::new(addresses[4], addresses[5], addresses[6], addresses[7]);
+            // Ethernet pads short frames; the total length says where IP ends
+            let end = total_len.min(ip.len());
+            (IpAddr::V4(source), IpAddr::V4(destination), ip.get(header_len..end)?, total_len > ip.len())
+        },
+        6 => {
+            let payload_len = usize::from(u16::from_be_bytes([*ip.get(4)?, *ip.get(5)?]));
+            let address = |offset: usize| -> Option<IpAddr> {
+                let octets: [u8; 16] = ip.get(offset..offset + 16)?.try_into().ok()?;
+                Some(IpAddr::V6(Ipv6Addr::from(octets)))
+            };
+            let (source, destination) = (address(8)?, address(24)?);
+            let end = (40 + payload_len).min(ip.len());
+            let mut next_header = ip[6];
+            let mut offset = 40;
+            // Hop-by-hop, routing and destination options may come first
+            while matches!(next_header, 0 | 43 | 60) {
+                next_header = *ip.get(offset)?;
+                offset += (usize::from(*ip.get(offset + 1)?) + 1) * 8;
+            }
+            if next_header != 6 {
+                return None;
+            }
+            (source, destination, ip.get(offset..end)?, 40 + payload_len > ip.len())
+        },
+        _ => return None,
+    };
+
+    let data_offset = usize::from(*tcp.get(12)? >> 4) * 4;
+    Some(Segment {
+        source: SocketAddr::new(source, u16::from_be_bytes([tcp[0], tcp[1]])),
+        destination: SocketAddr::new(destination, u16::from_be_bytes([tcp[2], tcp[3]])),
+        seq: u32::from_be_bytes([tcp[4], tcp[5], tcp[6], tcp[7]]),
+        flags: *tcp.get(13)?,
+        payload: tcp.get(data_offset..)?,
+        truncated,
+    })
+}
+
+// One direction of a flow: the next sequence number expected, segments
+// that arrived ahead of it, and the framer
+struct Direction {
+    base: u32,
+    next: Option<u32>,
+    pending: BTreeMap<u32, (usize, CaptureTime, Vec<u8>)>,
+    decoder: MessageDecoder,
+    extended_message: bool,
+}
+
+impl Direction {
+    fn new(context: SessionContext) -> Self {
+        Direction {
+            base: 0,
+            next: None,
+            pending: BTreeMap::new(),
+            decoder: MessageDecoder::new(context),
+            extended_message: false,
+        }
+    }
+
+    fn start(&mut self, seq: u32) {
+        self.base = seq;
+        self.next = Some(seq);
+    }
+}
+
+struct FlowState {
+    directions: [Direction; 2],
+    closed: bool,
+    truncation_reported: bool,
+}
+
+struct Sessions {
+    context: SessionContext,
+    flows: Vec<TcpFlow>,
+    states: Vec<FlowState>,
+    // Both orientations of each address pair, to the current flow
+    lookup: HashMap<(SocketAddr, SocketAddr), usize>,
+    messages: Vec<CapturedMessage>,
+    warnings: Vec<String>,
+}
+
+impl Sessions {
+    fn new(context: SessionContext) -> Self {
+        Sessions {
+            context,
+            flows: Vec::new(),
+            states: Vec::new(),
+            lookup: HashMap::new(),
+            messages: Vec::new(),
+            warnings: Vec::new(),
+        }
+    }
+
+    fn packet(&mut self, packet: &Packet) {
+        let Some(segment) = ip_packet(packet.link_type, packet.data).and_then(tcp_segment) else { return };
+        if segment.source.port() != BGP_PORT && segment.destination.port() != BGP_PORT {
+            return;
+        }
+
+        let syn = segment.flags & TCP_SYN != 0;
+        let id = match self.lookup.get(&(segment.source, segment.destination)) {
+            // A new SYN on a finished connection's addresses is a new connection
+            Some(&id) if !(syn && self.is_restart(id, &segment)) => id,
+            _ => self.new_flow(packet, &segment),
+        };
+        let flow = &mut self.flows[id];
+        flow.packets += 1;
+        let direction = usize::from(segment.source != flow.client);
+
+        let state = &mut self.states[id];
+        if segment.flags & (TCP_RST | TCP_FIN) != 0 {
+            state.closed = true;
+        }
+        if syn {
+            state.directions[direction].start(segment.seq.wrapping_add(1));
+            return;
+        }
+        if segment.payload.is_empty() {
+            return;
+        }
+        if segment.truncated {
+            if !state.truncation_reported {
+                state.truncation_reported = true;
+                self.warnings.push(format!(
+                    "Flow {}: frame {} was cut short by the capture's snapshot length; its payload is lost",
+                    id, packet.frame
+                ));
+            }
+            return;
+        }
+
+        let dir = &mut state.directions[direction];
+        let next = *dir.next.get_or_insert_with(|| {
+            dir.base = segment.seq;
+            segment.seq
+        });
+        let ahead = segment.seq.wrapping_sub(next) as i32;
+        if ahead > 0 {
+            let offset = segment.seq.wrapping_sub(dir.base);
+            dir.pending.insert(offset, (packet.frame, packet.time, segment.payload.to_vec()));
+            return;
+        }
+        // Retransmitted bytes were delivered already
+        let overlap = ahead.unsigned_abs() as usize;
+        if overlap >= segment.payload.len() {
+            return;
+        }
+        self.deliver(id, direction, packet.frame, packet.time, &segment.payload[overlap..]);
+        self.drain_pending(id, direction, Some((packet.frame, packet.time)));
+    }
+
+    // Sender and receiver of `direction`: 0 is client to server
+    fn endpoints(&self, id: usize, direction: usize) -> (SocketAddr, SocketAddr) {
+        let flow = &self.flows[id];
+        if direction == 0 { (flow.client, flow.server) } else { (flow.server, flow.client) }
+    }
+
+    fn is_restart(&self, id: usize, segment: &Segment) -> bool {
+        let state = &self.states[id];
+        let direction = usize::from(segment.source != self.flows[id].client);
+        let retransmitted = state.directions[direction].next == Some(segment.seq.wrapping_add(1));
+        state.closed || !retransmitted && state.directions[direction].next.is_some()
+    }
+
+    fn new_flow(&mut self, packet: &Packet, segment: &Segment) -> usize {
+        let handshake = segment.flags & TCP_SYN != 0;
+        // A SYN-ACK comes from the server
+        let (client, server) = if handshake && segment.flags & TCP_ACK != 0 {
+            (segment.destination, segment.source)
+        } else {
+            (segment.source, segment.destination)
+        };
+
+        let id = self.flows.len();
+        self.flows.push(TcpFlow { id, client, server, handshake, first_frame: packet.frame, packets: 0, messages: 0 });
+        self.states.push(FlowState {
+            directions: [Direction::new(self.context), Direction::new(self.context)],
+            closed: false,
+            truncation_reported: false,
+        });
+        self.lookup.insert((client, server), id);
+        self.lookup.insert((server, client), id);
+        id
+    }
+
+    // Segments that were waiting for the bytes `arrival` just delivered;
+    // their messages are only complete as of that packet. At the end of
+    // the capture (no arrival) nothing else will come, so jump over
+    // whatever is missing
+    fn drain_pending(&mut self, id: usize, direction: usize, arrival: Option<(usize, CaptureTime)>) {
+        loop {
+            let dir = &mut self.states[id].directions[direction];
+            let Some(next) = dir.next else { return };
+            let next_offset = next.wrapping_sub(dir.base);
+            let Some((&offset, _)) = dir.pending.first_key_value() else { return };
+            if offset > next_offset {
+                if arrival.is_some() {
+                    return;
+                }
+                let (source, destination) = self.endpoints(id, direction);
+                self.warnings.push(format!(
+                    "Flow {} {} -> {}: {} bytes missing at stream offset {}",
+                    id, source, destination, offset - next_offset, next_offset
+                ));
+                let dir = &mut self.states[id].directions[direction];
+                dir.decoder.reset_at(offset as usize);
+                dir.next = Some(dir.base.wrapping_add(offset));
+                continue;
+            }
+
+            let (frame, time, payload) = dir.pending.remove(&offset).unwrap_or_default();
+            let (frame, time) = arrival.unwrap_or((frame, time));
+            let overlap = (next_offset - offset) as usize;
+            if overlap < payload.len() {
+                self.deliver(id, direction, frame, time, &payload[overlap..]);
+            }
+        }
+    }
+
+    fn deliver(&mut self, id: usize, direction: usize, frame: usize, timestamp: CaptureTime, payload: &[u8]) {
+        let (source, destination) = self.endpoints(id, direction);
+        let state = &mut self.states[id];
+        let dir = &mut state.directions[direction];
+        dir.next = dir.next.map(|next| next.wrapping_add(payload.len() as u32));
+        dir.decoder.push(payload);
+
+        while let Some(item) = state.directions[direction].decoder.decode() {
+            match item {
+                StreamItem::Message(message) => {
+                    // RFC 8654: larger messages only once both sides have offered it
+                    if let Ok(Message::Open(open)) = &message.message {
+                        if open.capabilities().any(|capability| *capability == Capability::ExtendedMessage) {
+                            state.directions[direction].extended_message = true;
+                        }
+                        if state.directions.iter().all(|dir| dir.extended_message) {
+                            for dir in &mut state.directions {
+                                dir.decoder.set_context(SessionContext::new(true));
+                            }
+                        }
+                    }
+                    self.flows[id].messages += 1;
+                    self.messages.push(CapturedMessage { flow: id, frame, timestamp, source, destination, message });
+                },
+                StreamItem::Resync { offset, skipped, error } => self.warnings.push(format!(
+                    "Flow {} {} -> {}: skipped {} bytes at stream offset {}: {}",
+                    id, source, destination, skipped, offset, error
+                )),
+            }
+        }
+    }
+
+    fn finish(mut self) -> (Vec<TcpFlow>, Vec<CapturedMessage>, Vec<String>) {
+        for id in 0..self.flows.len() {
+            for direction in 0..2 {
+                self.drain_pending(id, direction, None);
+                if let Some(partial) = self.states[id].directions[direction].decoder.pending() {
+                    let (source, destination) = self.endpoints(id, direction);
+                    self.warnings.push(format!(
+                        "Flow {} {} -> {}: capture ends inside a message ({} bytes at stream offset {})",
+                        id, source, destination, partial.available, partial.offset
+                    ));
+                }
+            }
+        }
+        (self.flows, self.messages, self.warnings)
+    }
+}
+
+/// A made-up TCP connection for writing crafted messages into a capture,
+/// e.g. to look at them in Wireshark.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SyntheticSession {
+    /// Opens the connection, sends the messages and closes it.
+    pub client: SocketAddr,
+    pub server: SocketAddr,
+    /// Time of the SYN; each later packet follows a millisecond after the last.
+    pub start: CaptureTime,
+}
+
+impl Default for SyntheticSession {
+    // RFC 5737 documentation addresses
+    fn default() -> Self {
+        SyntheticSession {
+            client: SocketAddr::new(IpAddr::V4(Ipv4Addr::new(192, 0, 2, 1)), 49152),
+            server: SocketAddr::new(IpAddr::V4(Ipv4Addr::new(192, 0, 2, 2)), BGP_PORT),
+            start: CaptureTime::default(),
+        }
+    }
+}
+
+impl SyntheticSession {
+    pub fn new(client: SocketAddr, server: SocketAddr, start: CaptureTime) -> Self {
+        SyntheticSession { client, server, start }
+    }
+
+    /// A pcap file (Ethernet, microsecond timestamps) holding the three-way
+    /// handshake, `stream` sent by the client one message per segment, the
+    /// server's ACKs and the close. Bytes that do not frame as messages go
+    /// out as they are. Checksums are filled in, so Wireshark shows none as
+    /// bad. Fails if the two addresses are not of the same family, or if
+    /// a packet's time does not fit the 32-bit seconds field.
+    pub fn to_pcap(&self, stream: &[u8]) -> Result<Vec<u8>, EncodeError> {
+        if self.client.is_ipv4() != self.server.is_ipv4() {
+            return Err(EncodeError::InvalidFormat {
+                field: "server",
+                expected: "an address of the same family as the client",
+            });
+        }
+        // Largest segment that fits a 1500-byte Ethernet MTU
+        let mss = if self.client.is_ipv4() { 1460 } else { 1440 };
+
+        let mut writer = PcapWriter::new(self);
+        let (mut client_seq, mut server_seq) = (SYNTHETIC_CLIENT_ISN, SYNTHETIC_SERVER_ISN);
+        writer.packet(true, client_seq, 0, TCP_SYN, &[])?;
+        writer.packet(false, server_seq, client_seq + 1, TCP_SYN | TCP_ACK, &[])?;
+        client_seq += 1;
+        server_seq += 1;
+        writer.packet(true, client_seq, server_seq, TCP_ACK, &[])?;
+
+        for chunk in split_messages(stream) {
+            for segment in chunk.chunks(mss) {
+                writer.packet(true, client_seq, server_seq, TCP_PSH | TCP_ACK, segment)?;
+                client_seq = client_seq.wrapping_add(segment.len() as u32);
+            }
+            writer.packet(false, server_seq, client_seq, TCP_ACK, &[])?;
+        }
+
+        writer.packet(true, client_seq, server_seq, TCP_FIN | TCP_ACK, &[])?;
+        writer.packet(false, server_seq, client_seq + 1, TCP_FIN | TCP_ACK, &[])?;
+        writer.packet(true, client_seq + 1, server_seq + 1, TCP_ACK, &[])?;
+        Ok(writer.file)
+    }
+}
+
+struct PcapWriter<'a> {
+    session: &'a SyntheticSession,
+    file: Vec<u8>,
+    time: CaptureTime,
+    ip_id: u16,
+}
+
+impl<'a> PcapWriter<'a> {
+    fn new(session: &'a SyntheticSession) -> Self {
+        // Little-endian microsecond pcap, version 2.4, Ethernet
+        let mut file = Vec::with_capacity(4096);
+        file.extend_from_slice(&0xa1b2_c3d4u32.to_le_bytes());
+        file.extend_from_slice(&2u16.to_le_bytes());
+        file.extend_from_slice(&4u16.to_le_bytes());
+        file.extend_from_slice(&[0; 8]);
+        file.extend_from_slice(&262_144u32.to_le_bytes());
+        file.extend_from_slice(&1u32.to_le_bytes());
+        PcapWriter { session, file, time: session.start, ip_id: 1 }
+    }
+
+    fn packet(&mut self, from_client: bool, seq: u32, ack: u32, flags: u8, payload: &[u8]) -> Result<(), EncodeError> {
+        let (source, destination) = if from_client {
+            (self.session.client, self.session.server)
+        } else {
+            (self.session.server, self.session.client)
+        };
+
+        // Locally administered MACs, 02:00:00:00:00:01 for the client
+        let mut frame = Vec::with_capacity(94 + payload.len());
+        frame.extend_from_slice(&[2, 0, 0, 0, 0, if from_client { 2 } else { 1 }]);
+        frame.extend_from_slice(&[2, 0, 0, 0, 0, if from_client { 1 } else { 2 }]);
+        frame.extend_from_slice(if source.is_ipv4() { &[0x08, 0x00] } else { &[0x86, 0xdd] });
+
+        let tcp = tcp_header(source, destination, seq, ack, flags, payload);
+        match (source.ip(), destination.ip()) {
+            (IpAddr::V4(source), IpAddr::V4(destination)) => {
+                let mut ip = vec![0x45, 0];
+                ip.extend_from_slice(&((20 + tcp.len()) as u16).to_be_bytes());
+                ip.extend_from_slice(&self.ip_id.to_be_bytes());
+                // Don't Fragment, TTL 64, TCP
+                ip.extend_from_slice(&[0x40, 0, 64, 6, 0, 0]);
+                ip.extend_from_slice(&source.octets());
+                ip.extend_from_slice(&destination.octets());
+                let checksum = internet_checksum(&ip);
+                ip[10..12].copy_from_slice(&checksum.to_be_bytes());
+                frame.extend_from_slice(&ip);
+                self.ip_id = self.ip_id.wrapping_add(1);
+            },
+            (source, destination) => {
+                frame.extend_from_slice(&[0x60, 0, 0, 0]);
+                frame.extend_from_slice(&(tcp.len() as u16).to_be_bytes());
+                frame.extend_from_slice(&[6, 64]);
+                frame.extend_from_slice(&ipv6_octets(source));
+                frame.extend_from_slice(&ipv6_octets(destination));
+            },
+        }
+        frame.extend_from_slice(&tcp);
+
+        // The record header has 32 bits of seconds
+        let seconds = u32::try_from(self.time.seconds).map_err(|_| EncodeError::OutOfRange {
+            field: "start",
+            actual: self.time.seconds,
+            min: 0,
+            max: u64::from(u32::MAX),
+        })?;
+        for field in [seconds, self.time.nanos / 1_000, frame.len() as u32, frame.len() as u32] {
+            self.file.extend_from_slice(&field.to_le_bytes());
+        }
+        self.file.extend_from_slice(&frame);
+
+        self.time.nanos += 1_000_000;
+        if self.time.nanos >= 1_000_000_000 {
+            self.time.seconds += 1;
+            self.time.nanos -= 1_000_000_000;
+        }
+        Ok(())
+    }
+}
+
+fn ipv6_octets(address: IpAddr) -> [u8; 16] {
+    match address {
+        IpAddr::V4(address) => address.to_ipv6_mapped().octets(),
+        IpAddr::V6(address) => address.octets(),
+    }
+}
+
+// TCP header and payload, checksummed over the RFC 793 (IPv4) or
+// RFC 8200 8.1 (IPv6) pseudo-header. SYNs carry an MSS option
+fn tcp_header(source: SocketAddr, destination: SocketAddr, seq: u32, ack: u32, flags: u8, payload: &[u8]) -> Vec<u8> {
+    let syn = flags & TCP_SYN != 0;
+    let mut tcp = Vec::with_capacity(24 + payload.len());
+    tcp.extend_from_slice(&source.port().to_be_bytes());
+    tcp.extend_from_slice(&destination.port().to_be_bytes());
+    tcp.extend_from_slice(&seq.to_be_bytes());
+    tcp.extend_from_slice(&ack.to_be_bytes());
+    tcp.extend_from_slice(&[if syn { 0x60 } else { 0x50 }, flags, 0xff, 0xff, 0, 0, 0, 0]);
+    if syn {
+        let mss: u16 = if source.is_ipv4() { 1460 } else { 1440 };
+        tcp.extend_from_slice(&[2, 4]);
+        tcp.extend_from_slice(&mss.to_be_bytes());
+    }
+    tcp.extend_from_slice(payload);
+
+    let mut pseudo = Vec::with_capacity(40 + tcp.len());
+    match (source.ip(), destination.ip()) {
+        (IpAddr::V4(source), IpAddr::V4(destination)) => {
+            pseudo.extend_from_slice(&source.octets());
+            pseudo.extend_from_slice(&destination.octets());
+            pseudo.extend_from_slice(&[0, 6]);
+            pseudo.extend_from_slice(&(tcp.len() as u16).to_be_bytes());
+        },
+        (source, destination) => {
+            pseudo.extend_from_slice(&ipv6_octets(source));
+            pseudo.extend_from_slice(&ipv6_octets(destination));
+            pseudo.extend_from_slice(&(tcp.len() as u32).to_be_bytes());
+            pseudo.extend_from_slice(&[0, 0, 0, 6]);
+        },
+    }
+    pseudo.extend_from_slice(&tcp);
+    let checksum = internet_checksum(&pseudo);
+    tcp[16..18].copy_from_slice(&checksum.to_be_bytes());
+    tcp
+}
+
+// RFC 1071 one's complement sum; zero over data that includes its checksum
+fn internet_checksum(data: &[u8]) -> u16 {
+    let mut sum: u32 = data.chunks(2)
+        .map(|pair| u32::from(u16::from_be_bytes([pair[0], pair.get(1).copied().unwrap_or(0)])))
+        .sum();
+    while sum > 0xffff {
+        sum = (sum & 0xffff) + (sum >> 16);
+    }
+    !(sum as u16)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{parse_hex, BGP_KEEPALIVE, BGP_NOTIFICATION, BGP_OPEN};
+
+    const OPEN: &str = "ffffffffffffffffffffffffffffffff 001d 01 04 fde8 00b4 c0000201 00";
+    const KEEPALIVE: &str = "ffffffffffffffffffffffffffffffff 0013 04";
+    const CEASE: &str = "ffffffffffffffffffffffffffffffff 0015 03 06 02";
+
+    // Ethernet, IPv4 and TCP headers around `payload`; checksums are not read
+    fn frame(from_client: bool, seq: u32, flags: u8, payload: &[u8]) -> Vec<u8> {
+        let (client, server) = ([192, 0, 2, 1], [192, 0, 2, 2]);
+        let (source, destination) = if from_client { (client, server) } else { (server, client) };
+        let (sport, dport) = if from_client { (50123u16, BGP_PORT) } else { (BGP_PORT, 50123) };
+
+        let mut frame = vec![0; 12];
+        frame.extend_from_slice(&[0x08, 0x00]);
+        frame.extend_from_slice(&[0x45, 0, 0, 0, 0, 0, 0x40, 0, 64, 6, 0, 0]);
+        let total = (40 + payload.len()) as u16;
+        frame[16..18].copy_from_slice(&total.to_be_bytes());
+        frame.extend_from_slice(&source);
+        frame.extend_from_slice(&destination);
+        frame.extend_from_slice(&sport.to_be_bytes());
+        frame.extend_from_slice(&dport.to_be_bytes());
+        frame.extend_from_slice(&seq.to_be_bytes());
+        frame.extend_from_slice(&[0, 0, 0, 0, 0x50, flags, 0xff, 0xff, 0, 0, 0, 0]);
+        frame.extend_from_slice(payload);
+        frame
+    }
+
+    fn pcap(frames: &[(u32, Vec<u8>)]) -> Vec<u8> {
+        let mut file = vec![0xd4, 0xc3, 0xb2, 0xa1, 2, 0, 4, 0];
+        file.extend_from_slice(&[0; 8]);
+        file.extend_from_slice(&65535u32.to_le_bytes());
+        file.extend_from_slice(&1u32.to_le_bytes());
+        for (i, (seconds, frame)) in frames.iter().enumerate() {
+            file.extend_from_slice(&seconds.to_le_bytes());
+            file.extend_from_slice(&(i as u32 * 1000).to_le_bytes());
+            file.extend_from_slice(&(frame.len() as u32).to_le_bytes());
+            file.extend_from_slice(&(frame.len() as u32).to_le_bytes());
+            file.extend_from_slice(frame);
+        }
+        file
+    }
+
+    #[test]
+    fn test_pcap_session_timeline() {
+        let (open, keepalive) = (parse_hex(OPEN).unwrap(), parse_hex(KEEPALIVE).unwrap());
+        let cease = parse_hex(CEASE).unwrap();
+        let both = [keepalive.clone(), cease.clone()].concat();
+        let file = pcap(&[
+            (1_700_000_000, frame(true, 99, TCP_SYN, &[])),
+            (1_700_000_000, frame(false, 499, TCP_SYN | TCP_ACK, &[])),
+            (1_700_000_001, frame(true, 100, TCP_ACK, &open[..10])),
+            (1_700_000_001, frame(true, 110, TCP_ACK, &open[10..])),
+            // Out of order, then the segment it was waiting for, then a retransmission
+            (1_700_000_002, frame(false, 500 + 29, TCP_ACK, &keepalive)),
+            (1_700_000_002, frame(false, 500, TCP_ACK, &open)),
+            (1_700_000_002, frame(false, 500, TCP_ACK, &open)),
+            (1_700_000_090, frame(true, 129, TCP_ACK | TCP_FIN, &both)),
+        ]);
+
+        let capture = Capture::parse(&file).unwrap();
+        assert_eq!((capture.format, capture.packets), (CaptureFormat::Pcap, 8));
+        assert_eq!(capture.flows.len(), 1);
+        assert_eq!(capture.flows[0].client, "192.0.2.1:50123".parse().unwrap());
+        assert!(capture.flows[0].handshake);
+        assert!(capture.warnings.is_empty(), "{:?}", capture.warnings);
+
+        let timeline: Vec<_> = capture.messages.iter()
+            .map(|m| (m.frame, m.source.port(), m.message.message_type))
+            .collect();
+        assert_eq!(timeline, vec![
+            (4, 50123, BGP_OPEN),
+            (6, BGP_PORT, BGP_OPEN),
+            (6, BGP_PORT, BGP_KEEPALIVE),
+            (8, 50123, BGP_KEEPALIVE),
+            (8, 50123, BGP_NOTIFICATION),
+        ]);
+        assert_eq!(capture.messages[4].timestamp.to_string(), "2023-11-14T22:14:50.007000Z");
+        assert_eq!(capture.messages[4].message.offset, 48);
+    }
+
+    #[test]
+    fn test_pcapng_and_link_types() {
+        let cease = parse_hex(CEASE).unwrap();
+        // Linux cooked capture v2 around the IPv4 packet
+        let mut cooked = vec![0x08, 0x00, 0, 0, 0, 0, 0, 2, 0, 1, 0, 6, 0, 0, 0, 0, 0, 0, 0, 0];
+        cooked.extend_from_slice(&frame(false, 1000, TCP_ACK, &cease)[14..]);
+
+        let block = |block_type: u32, body: &[u8]| {
+            let length = (12 + body.len()) as u32;
+            [&block_type.to_le_bytes()[..], &length.to_le_bytes(), body, &length.to_le_bytes()].concat()
+        };
+        let section = [&0x1a2b_3c4du32.to_le_bytes()[..], &[1, 0, 0, 0], &[0xff; 8]].concat();
+        // SLL2, nanosecond resolution
+        let interface = [&276u16.to_le_bytes()[..], &[0, 0], &[0; 4], &[9, 0, 1, 0, 9, 0, 0, 0], &[0; 4]].concat();
+        let ticks: u64 = 1_700_000_000_123_456_789;
+        let mut packet = [0u32, (ticks >> 32) as u32, ticks as u32].map(u32::to_le_bytes).concat();
+        packet.extend_from_slice(&(cooked.len() as u32).to_le_bytes());
+        packet.extend_from_slice(&(cooked.len() as u32).to_le_bytes());
+        packet.extend_from_slice(&cooked);
+        packet.resize(packet.len().next_multiple_of(4), 0);
+
+        let file = [block(PCAPNG_SECTION_HEADER, &section), block(1, &interface), block(6, &packet)].concat();
+        let capture = Capture::parse(&file).unwrap();
+        assert_eq!(capture.format, CaptureFormat::Pcapng);
+        assert_eq!(capture.messages.len(), 1);
+        assert_eq!(capture.messages[0].timestamp, CaptureTime::new(1_700_000_000, 123_456_789));
+        assert_eq!(capture.messages[0].timestamp.to_string(), "2023-11-14T22:13:20.123456789Z");
+        // Mid-session capture: the server is whoever spoke first
+        assert!(!capture.flows[0].handshake);
+        assert_eq!(capture.flows[0].client.port(), BGP_PORT);
+    }
+
+    #[test]
+    fn test_synthetic_session_round_trip() {
+        let stream = [OPEN, KEEPALIVE, CEASE].map(|hex| parse_hex(hex).unwrap()).concat();
+        let start = CaptureTime::new(1_700_000_000, 999_500_000);
+        let session = SyntheticSession { start, ..SyntheticSession::default() };
+        let file = session.to_pcap(&stream).unwrap();
+
+        let capture = Capture::parse(&file).unwrap();
+        assert_eq!(capture.packets, 12);
+        assert!(capture.warnings.is_empty(), "{:?}", capture.warnings);
+        assert_eq!(capture.flows[0].client, session.client);
+        let types: Vec<_> = capture.messages.iter().map(|m| m.message.message_type).collect();
+        assert_eq!(types, vec![BGP_OPEN, BGP_KEEPALIVE, BGP_NOTIFICATION]);
+        assert_eq!(capture.messages[0].timestamp, CaptureTime::new(1_700_000_001, 2_500_000));
+
+        // Every IPv4 header and TCP segment sums to zero with its checksum in place
+        let mut r = FileReader::new(&file[24..], 24, true, RFC_PCAP);
+        while !r.is_empty() {
+            r.take(8, "timestamp").unwrap();
+            let len = r.u32("captured length").unwrap() as usize;
+            r.u32("original length").unwrap();
+            let ip = &r.take(len, "packet data").unwrap()[14..];
+            assert_eq!(internet_checksum(&ip[..20]), 0);
+            let pseudo = [&ip[12..20], &[0, 6], &(ip.len() as u16 - 20).to_be_bytes(), &ip[20..]].concat();
+            assert_eq!(internet_checksum(&pseudo), 0);
+        }
+
+        // Packets that would pass the u32 seconds field are rejected, not wrapped
+        let late = SyntheticSession { start: CaptureTime::new(u64::from(u32::MAX), 999_500_000), ..session };
+        assert!(matches!(late.to_pcap(&stream), Err(EncodeError::OutOfRange { field: "start", .. })));
+        let late = SyntheticSession { start: CaptureTime::new(u64::MAX, 0), ..session };
+        assert!(matches!(late.to_pcap(&stream), Err(EncodeError::OutOfRange { field: "start", .. })));
+    }
+
+    #[test]
+    fn test_synthetic_session_ipv6() {
+        let cease = parse_hex(CEASE).unwrap();
+        let session = SyntheticSession::new(
+            "[2001:db8::1]:50000".parse().unwrap(),
+            "[2001:db8::2]:179".parse().unwrap(),
+            CaptureTime::default(),
+        );
+        let capture = Capture::parse(&session.to_pcap(&cease).unwrap()).unwrap();
+        assert_eq!(capture.messages.len(), 1);
+        assert_eq!(capture.messages[0].destination, session.server);
+
+        // A wrong 2 KiB blob still goes out, split to fit the MTU
+        let capture = Capture::parse(&session.to_pcap(&[0; 2048]).unwrap()).unwrap();
+        assert_eq!(capture.packets, 9);
+
+        let mixed = SyntheticSession { server: "192.0.2.2:179".parse().unwrap(), ..session };
+        assert!(matches!(mixed.to_pcap(&cease), Err(EncodeError::InvalidFormat { field: "server", .. })));
+    }
+
+    #[test]
+    fn test_capture_damage() {
+        assert_eq!(Capture::parse(b"GIF89a"), Err(DecodeError::UnknownCaptureFormat { magic: 0x4749_4638 }));
+        assert!(matches!(Capture::parse(&[0xd4, 0xc3]), Err(DecodeError::Truncated { .. })));
+
+        // A lost segment, and a file cut off mid-record
+        let keepalive = parse_hex(KEEPALIVE).unwrap();
+        let mut file = pcap(&[
+            (1, frame(true, 100, TCP_ACK, &keepalive)),
+            (2, frame(true, 100 + 19 + 19, TCP_ACK, &keepalive)),
+        ]);
+        file.extend_from_slice(&[1, 0, 0]);
+        let capture = Capture::parse(&file).unwrap();
+        assert_eq!(capture.messages.len(), 2);
+        assert_eq!(capture.messages[1].message.offset, 38);
+        assert!(capture.warnings[0].starts_with("Capture ends mid-record"));
+        assert_eq!(capture.warnings[1], "Flow 0 192.0.2.1:50123 -> 192.0.2.2:179: 19 bytes missing at stream offset 19");
+
+        // Raw IP record cut off before the IPv4 addresses
+        let mut file = pcap(&[(1, vec![0x45, 0, 0, 0x28, 0, 0, 0x40, 0, 0x40, 6, 0, 0])]);
+        file[20..24].copy_from_slice(&101u32.to_le_bytes());
+        let capture = Capture::parse(&file).unwrap();
+        assert!(capture.messages.is_empty());
+        assert!(capture.flows.is_empty());
+    }
+}
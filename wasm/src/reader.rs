@@ -0,0 +1,102 @@
+use crate::DecodeError;
+
+// Bounds-checked big-endian cursor over a message body. Offsets in errors
+// are absolute (relative to the start of the whole message) so they can be
+// highlighted in the original hex dump.
+#[derive(Clone)]
+pub(crate) struct Reader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+    base: usize,
+    rfc: &'static str,
+}
+
+impl<'a> Reader<'a> {
+    pub(crate) fn new(bytes: &'a [u8], base: usize, rfc: &'static str) -> Self {
+        Reader { bytes, pos: 0, base, rfc }
+    }
+
+    pub(crate) fn offset(&self) -> usize {
+        self.base + self.pos
+    }
+
+    pub(crate) fn remaining(&self) -> usize {
+        self.bytes.len() - self.pos
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.remaining() == 0
+    }
+
+    pub(crate) fn peek(&self) -> Option<u8> {
+        self.bytes.get(self.pos).copied()
+    }
+
+    pub(crate) fn take(&mut self, len: usize, field: &'static str) -> Result<&'a [u8], DecodeError> {
+        if len > self.remaining() {
+            return Err(DecodeError::Truncated {
+                field,
+                offset: self.offset(),
+                expected: len,
+                actual: self.remaining(),
+                rfc: self.rfc,
+            });
+        }
+        let slice = &self.bytes[self.pos..self.pos + len];
+        self.pos += len;
+        Ok(slice)
+    }
+
+    pub(crate) fn u8(&mut self, field: &'static str) -> Result<u8, DecodeError> {
+        Ok(self.take(1, field)?[0])
+    }
+
+    pub(crate) fn u16(&mut self, field: &'static str) -> Result<u16, DecodeError> {
+        let b = self.take(2, field)?;
+        Ok(u16::from_be_bytes([b[0], b[1]]))
+    }
+
+    pub(crate) fn u32(&mut self, field: &'static str) -> Result<u32, DecodeError> {
+        let b = self.take(4, field)?;
+        Ok(u32::from_be_bytes([b[0], b[1], b[2], b[3]]))
+    }
+
+    // Split off the next `len` bytes as a reader of their own, for nested TLVs
+    pub(crate) fn sub(&mut self, len: usize, field: &'static str) -> Result<Reader<'a>, DecodeError> {
+        let base = self.offset();
+        let bytes = self.take(len, field)?;
+        Ok(Reader { bytes, pos: 0, base, rfc: self.rfc })
+    }
+
+    // Require that every byte has been consumed
+    pub(crate) fn finish(&self, field: &'static str) -> Result<(), DecodeError> {
+        if self.is_empty() {
+            return Ok(());
+        }
+        Err(DecodeError::TrailingData { field, offset: self.offset(), actual: self.remaining(), rfc: self.rfc })
+    }
+
+    pub(crate) fn rest(&mut self) -> &'a [u8] {
+        let slice = &self.bytes[self.pos..];
+        self.pos = self.bytes.len();
+        slice
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reader_offsets() {
+        let mut r = Reader::new(&[0x00, 0x01, 0x02, 0x03, 0x04], 19, "RFC 4271 4.2");
+        assert_eq!(r.u16("a").unwrap(), 1);
+        let mut nested = r.sub(2, "b").unwrap();
+        assert_eq!(nested.offset(), 21);
+        assert_eq!(nested.u8("c").unwrap(), 2);
+        assert_eq!(
+            r.u16("d"),
+            Err(DecodeError::Truncated { field: "d", offset: 23, expected: 2, actual: 1, rfc: "RFC 4271 4.2" })
+        );
+    }
+}
@@ -0,0 +1,191 @@
+// BGP Error (NOTIFICATION) Codes and their subcode registries, as kept by
+// IANA under "Border Gateway Protocol (BGP) Parameters". This is the one
+// table the decoder, encoder and frontend all read names from.
+
+use serde::Serialize;
+
+/// One NOTIFICATION error code and its subcodes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct ErrorCodeEntry {
+    pub code: u8,
+    pub name: &'static str,
+    pub rfc: &'static str,
+    pub deprecated: bool,
+    pub subcodes: &'static [SubcodeEntry],
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct SubcodeEntry {
+    pub subcode: u8,
+    pub name: &'static str,
+    pub rfc: &'static str,
+    pub deprecated: bool,
+}
+
+const fn sub(subcode: u8, name: &'static str, rfc: &'static str) -> SubcodeEntry {
+    SubcodeEntry { subcode, name, rfc, deprecated: false }
+}
+
+const fn deprecated(subcode: u8, name: &'static str, rfc: &'static str) -> SubcodeEntry {
+    SubcodeEntry { subcode, name, rfc, deprecated: true }
+}
+
+// Codes without a subcode registry only ever carry 0 (RFC 4271 4.5)
+const UNSPECIFIC_ONLY: &[SubcodeEntry] = &[sub(0, "Unspecific", "RFC 4271")];
+
+/// Every assigned error code, in code order.
+pub const ERROR_CODES: &[ErrorCodeEntry] = &[
+    ErrorCodeEntry {
+        code: 1,
+        name: "Message Header Error",
+        rfc: "RFC 4271",
+        deprecated: false,
+        subcodes: &[
+            sub(0, "Unspecific", "RFC 4271 Errata 4493"),
+            sub(1, "Connection Not Synchronized", "RFC 4271"),
+            sub(2, "Bad Message Length", "RFC 4271"),
+            sub(3, "Bad Message Type", "RFC 4271"),
+        ],
+    },
+    ErrorCodeEntry {
+        code: 2,
+        name: "OPEN Message Error",
+        rfc: "RFC 4271",
+        deprecated: false,
+        subcodes: &[
+            sub(0, "Unspecific", "RFC 4271 Errata 4493"),
+            sub(1, "Unsupported Version Number", "RFC 4271"),
+            sub(2, "Bad Peer AS", "RFC 4271"),
+            sub(3, "Bad BGP Identifier", "RFC 4271"),
+            sub(4, "Unsupported Optional Parameter", "RFC 4271"),
+            deprecated(5, "Authentication Failure", "RFC 4271"),
+            sub(6, "Unacceptable Hold Time", "RFC 4271"),
+            sub(7, "Unsupported Capability", "RFC 5492"),
+            deprecated(8, "Grouping Conflict", "RFC 9234"),
+            deprecated(9, "Grouping Required", "RFC 9234"),
+            deprecated(10, "Capability Value Mismatch", "RFC 9234"),
+            sub(11, "Role Mismatch", "RFC 9234"),
+        ],
+    },
+    ErrorCodeEntry {
+        code: 3,
+        name: "UPDATE Message Error",
+        rfc: "RFC 4271",
+        deprecated: false,
+        subcodes: &[
+            sub(0, "Unspecific", "RFC 4271 Errata 4493"),
+            sub(1, "Malformed Attribute List", "RFC 4271"),
+            sub(2, "Unrecognized Well-known Attribute", "RFC 4271"),
+            sub(3, "Missing Well-known Attribute", "RFC 4271"),
+            sub(4, "Attribute Flags Error", "RFC 4271"),
+            sub(5, "Attribute Length Error", "RFC 4271"),
+            sub(6, "Invalid ORIGIN Attribute", "RFC 4271"),
+            deprecated(7, "AS Routing Loop", "RFC 4271"),
+            sub(8, "Invalid NEXT_HOP Attribute", "RFC 4271"),
+            sub(9, "Optional Attribute Error", "RFC 4271"),
+            sub(10, "Invalid Network Field", "RFC 4271"),
+            sub(11, "Malformed AS_PATH", "RFC 4271"),
+        ],
+    },
+    ErrorCodeEntry {
+        code: 4,
+        name: "Hold Timer Expired",
+        rfc: "RFC 4271",
+        deprecated: false,
+        subcodes: UNSPECIFIC_ONLY,
+    },
+    ErrorCodeEntry {
+        code: 5,
+        name: "Finite State Machine Error",
+        rfc: "RFC 4271",
+        deprecated: false,
+        subcodes: &[
+            sub(0, "Unspecified Error", "RFC 6608"),
+            sub(1, "Receive Unexpected Message in OpenSent State", "RFC 6608"),
+            sub(2, "Receive Unexpected Message in OpenConfirm State", "RFC 6608"),
+            sub(3, "Receive Unexpected Message in Established State", "RFC 6608"),
+        ],
+    },
+    ErrorCodeEntry {
+        code: 6,
+        name: "Cease",
+        rfc: "RFC 4271",
+        deprecated: false,
+        subcodes: &[
+            sub(0, "Reserved", "RFC 4486"),
+            sub(1, "Maximum Number of Prefixes Reached", "RFC 4486"),
+            sub(2, "Administrative Shutdown", "RFC 4486, RFC 9003"),
+            sub(3, "Peer De-configured", "RFC 4486"),
+            sub(4, "Administrative Reset", "RFC 4486, RFC 9003"),
+            sub(5, "Connection Rejected", "RFC 4486"),
+            sub(6, "Other Configuration Change", "RFC 4486"),
+            sub(7, "Connection Collision Resolution", "RFC 4486"),
+            sub(8, "Out of Resources", "RFC 4486"),
+            sub(9, "Hard Reset", "RFC 8538"),
+            sub(10, "BFD Down", "RFC 9384"),
+        ],
+    },
+    ErrorCodeEntry {
+        code: 7,
+        name: "ROUTE-REFRESH Message Error",
+        rfc: "RFC 7313",
+        deprecated: false,
+        subcodes: &[
+            sub(0, "Reserved", "RFC 7313"),
+            sub(1, "Invalid Message Length", "RFC 7313"),
+            sub(255, "Reserved", "RFC 7313"),
+        ],
+    },
+    ErrorCodeEntry {
+        code: 8,
+        name: "Send Hold Timer Expired",
+        rfc: "RFC 9687",
+        deprecated: false,
+        subcodes: UNSPECIFIC_ONLY,
+    },
+];
+
+/// Registry entry for `code`, if IANA has assigned it.
+pub fn error_code_entry(code: u8) -> Option<&'static ErrorCodeEntry> {
+    ERROR_CODES.iter().find(|entry| entry.code == code)
+}
+
+/// Highest assigned error code. Codes 1 through this one are all assigned,
+/// so it bounds what the encoder accepts.
+pub fn max_error_code() -> u8 {
+    ERROR_CODES.last().map_or(0, |entry| entry.code)
+}
+
+/// Registry entry for `subcode` under `code`.
+pub fn subcode_entry(code: u8, subcode: u8) -> Option<&'static SubcodeEntry> {
+    error_code_entry(code)?.subcodes.iter().find(|entry| entry.subcode == subcode)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_registry_lookup() {
+        assert_eq!(error_code_entry(8).unwrap().name, "Send Hold Timer Expired");
+        assert_eq!(subcode_entry(2, 11).unwrap().name, "Role Mismatch");
+        assert!(subcode_entry(3, 7).unwrap().deprecated);
+        assert_eq!(subcode_entry(5, 2).unwrap().rfc, "RFC 6608");
+        assert_eq!(subcode_entry(2, 0).unwrap().rfc, "RFC 4271 Errata 4493");
+        assert_eq!(subcode_entry(6, 0).unwrap().name, "Reserved");
+        assert_eq!(subcode_entry(7, 255).unwrap().name, "Reserved");
+        assert_eq!(error_code_entry(0), None);
+        assert_eq!(subcode_entry(1, 0).unwrap().name, "Unspecific");
+        assert_eq!(subcode_entry(1, 4), None);
+    }
+
+    #[test]
+    fn test_registry_sorted() {
+        // Lookups, max_error_code and the frontend's listing assume 1, 2, 3...
+        assert!(ERROR_CODES.iter().enumerate().all(|(i, entry)| entry.code as usize == i + 1));
+        assert_eq!(max_error_code(), 8);
+        for entry in ERROR_CODES {
+            assert!(entry.subcodes.windows(2).all(|w| w[0].subcode < w[1].subcode), "code {}", entry.code);
+        }
+    }
+}
@@ -0,0 +1,164 @@
+use serde::{Deserialize, Serialize};
+
+use crate::reader::Reader;
+use crate::{
+    extended_length_note, family_name, frame_message, parse_hex, validate_message, DecodeError,
+    EncodeError, RouteRefreshDecodeResponse, SessionContext, BGP_HEADER_LEN, BGP_ROUTE_REFRESH,
+};
+
+const RFC_ROUTE_REFRESH: &str = "RFC 2918 3";
+const RFC_ENHANCED_ROUTE_REFRESH: &str = "RFC 7313 5";
+
+/// ROUTE-REFRESH is always the header plus AFI, subtype and SAFI.
+pub const ROUTE_REFRESH_LEN: usize = 23;
+
+pub const ROUTE_REFRESH_NORMAL: u8 = 0;
+/// Beginning of Route Refresh (RFC 7313).
+pub const ROUTE_REFRESH_BORR: u8 = 1;
+/// End of Route Refresh (RFC 7313).
+pub const ROUTE_REFRESH_EORR: u8 = 2;
+
+/// A BGP ROUTE-REFRESH message (RFC 2918, RFC 7313).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RouteRefresh {
+    pub afi: u16,
+    pub safi: u8,
+    /// Message subtype; the "Reserved" octet of RFC 2918.
+    #[serde(default)]
+    pub subtype: u8,
+    /// Outbound Route Filtering entries (RFC 5291), kept raw.
+    #[serde(default)]
+    pub orf: Vec<u8>,
+}
+
+impl RouteRefresh {
+    pub fn new(afi: u16, safi: u8, subtype: u8) -> Self {
+        RouteRefresh { afi, safi, subtype, orf: Vec::new() }
+    }
+
+    /// Parse a complete ROUTE-REFRESH message, header included.
+    pub fn parse(bytes: &[u8]) -> Result<Self, DecodeError> {
+        Self::parse_with(bytes, &SessionContext::default())
+    }
+
+    /// Parse with the size limit of `context`.
+    pub fn parse_with(bytes: &[u8], context: &SessionContext) -> Result<Self, DecodeError> {
+        validate_message(bytes, BGP_ROUTE_REFRESH, ROUTE_REFRESH_LEN, context.max_message_len())?;
+
+        let mut r = Reader::new(&bytes[BGP_HEADER_LEN..], BGP_HEADER_LEN, RFC_ROUTE_REFRESH);
+        let afi = r.u16("AFI")?;
+        let subtype = r.u8("message subtype")?;
+        let safi = r.u8("SAFI")?;
+
+        // Only a normal refresh may carry ORF entries; BoRR and EoRR with
+        // any other length are a ROUTE-REFRESH Message Error (code 7/1)
+        if matches!(subtype, ROUTE_REFRESH_BORR | ROUTE_REFRESH_EORR) && !r.is_empty() {
+            return Err(DecodeError::TrailingData {
+                field: "demarcation ROUTE-REFRESH",
+                offset: r.offset(),
+                actual: r.remaining(),
+                rfc: RFC_ENHANCED_ROUTE_REFRESH,
+            });
+        }
+
+        Ok(RouteRefresh { afi, safi, subtype, orf: r.rest().to_vec() })
+    }
+
+    /// Parse a hex dump; whitespace, `:` and `-` separators are ignored.
+    pub fn from_hex(hex_input: &str) -> Result<Self, DecodeError> {
+        Self::parse(&parse_hex(hex_input)?)
+    }
+
+    pub fn to_bytes(&self) -> Result<Vec<u8>, EncodeError> {
+        self.to_bytes_with(&SessionContext::default())
+    }
+
+    /// Serialize within the size limit of `context`.
+    pub fn to_bytes_with(&self, context: &SessionContext) -> Result<Vec<u8>, EncodeError> {
+        let mut body = Vec::with_capacity(4 + self.orf.len());
+        body.extend_from_slice(&self.afi.to_be_bytes());
+        body.push(self.subtype);
+        body.push(self.safi);
+        body.extend_from_slice(&self.orf);
+        frame_message(BGP_ROUTE_REFRESH, &body, context.max_message_len())
+    }
+
+    /// Human-readable description, one line per field.
+    pub fn summary(&self) -> Vec<String> {
+        let mut lines = vec![
+            format!("Address family: {} (AFI {}, SAFI {})", family_name(self.afi, self.safi), self.afi, self.safi),
+            format!("Subtype: {} ({})", self.subtype, route_refresh_subtype_name(self.subtype)),
+        ];
+        if !self.orf.is_empty() {
+            lines.push(format!("ORF entries: {} bytes", self.orf.len()));
+        }
+        lines.extend(extended_length_note(ROUTE_REFRESH_LEN + self.orf.len()));
+        lines
+    }
+}
+
+/// Name of a ROUTE-REFRESH message subtype (RFC 7313 section 3.2).
+pub fn route_refresh_subtype_name(subtype: u8) -> &'static str {
+    match subtype {
+        ROUTE_REFRESH_NORMAL => "Normal route refresh request",
+        ROUTE_REFRESH_BORR => "BoRR, Beginning of Route Refresh",
+        ROUTE_REFRESH_EORR => "EoRR, End of Route Refresh",
+        255 => "Reserved",
+        _ => "Unknown, ignored by the receiver",
+    }
+}
+
+impl From<&RouteRefresh> for RouteRefreshDecodeResponse {
+    fn from(route_refresh: &RouteRefresh) -> Self {
+        RouteRefreshDecodeResponse {
+            route_refresh: route_refresh.clone(),
+            family: family_name(route_refresh.afi, route_refresh.safi),
+            subtype_name: route_refresh_subtype_name(route_refresh.subtype).to_string(),
+            summary: route_refresh.summary(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_route_refresh_round_trip() {
+        let eorr = RouteRefresh::new(2, 1, ROUTE_REFRESH_EORR);
+        let bytes = eorr.to_bytes().unwrap();
+        assert_eq!(bytes, parse_hex("ffffffffffffffffffffffffffffffff 0017 05 0002 02 01").unwrap());
+        assert_eq!(RouteRefresh::parse(&bytes).unwrap(), eorr);
+        assert_eq!(eorr.summary()[1], "Subtype: 2 (EoRR, End of Route Refresh)");
+    }
+
+    #[test]
+    fn test_route_refresh_length() {
+        // A normal refresh may carry ORF entries
+        let refresh = RouteRefresh::from_hex("ffffffffffffffffffffffffffffffff 0019 05 0001 00 01 0140").unwrap();
+        assert_eq!(refresh.orf, vec![0x01, 0x40]);
+
+        // BoRR must be exactly 23 bytes
+        let err = RouteRefresh::from_hex("ffffffffffffffffffffffffffffffff 0018 05 0001 01 01 00").unwrap_err();
+        assert_eq!(err.code(), "trailing_data");
+        assert_eq!(err.rfc(), Some("RFC 7313 5"));
+
+        let err = RouteRefresh::from_hex("ffffffffffffffffffffffffffffffff 0016 05 0001 00").unwrap_err();
+        assert_eq!(err.code(), "message_too_short");
+    }
+
+    #[test]
+    fn test_route_refresh_message_error() {
+        let rejected = parse_hex("ffffffffffffffffffffffffffffffff 0018 05 0001 01 01 00").unwrap();
+        let notification = crate::Notification::new(7, 1, rejected);
+        let bytes = notification.to_bytes().unwrap();
+
+        let decoded = crate::Notification::parse(&bytes).unwrap();
+        assert_eq!(decoded.names().1, "Invalid Message Length");
+        assert_eq!(
+            decoded.interpretation(),
+            "Rejected ROUTE-REFRESH: declared length 24, 24 bytes received, IPv4 unicast, \
+             subtype 1 (BoRR, Beginning of Route Refresh)"
+        );
+    }
+}
@@ -0,0 +1,273 @@
+// Display safety checks for shutdown communications. RFC 9003 section 5
+// warns that the text is untrusted: it can carry control characters,
+// bidirectional overrides or lookalike letters meant to mislead whoever
+// reads it in a terminal, log or ticket.
+
+use serde::{Deserialize, Serialize};
+
+const ESC: char = '\u{1b}';
+const CSI: char = '\u{9b}';
+const BEL: char = '\u{7}';
+
+/// What makes a character sequence unsafe to display verbatim.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SafetyIssueKind {
+    /// C0 control or DEL.
+    ControlCharacter,
+    /// C1 control (U+0080 to U+009F).
+    C1Control,
+    /// Bidirectional embedding, override, isolate or mark.
+    BidiControl,
+    /// A character that renders as nothing.
+    ZeroWidth,
+    /// A word mixing Latin, Cyrillic or Greek letters.
+    MixedScript,
+    /// An ANSI/VT escape sequence a terminal would act on.
+    TerminalEscape,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SafetyIssue {
+    pub kind: SafetyIssueKind,
+    /// Byte offset and length within the text.
+    pub offset: usize,
+    pub length: usize,
+    pub description: String,
+}
+
+/// Result of [`analyze_text`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TextSafety {
+    pub issues: Vec<SafetyIssue>,
+    /// The text with every invisible or active character made visible:
+    /// C0 controls as control pictures (U+2400 block), the rest as `<U+XXXX>`.
+    pub sanitized: String,
+}
+
+impl TextSafety {
+    pub fn is_safe(&self) -> bool {
+        self.issues.is_empty()
+    }
+}
+
+/// Look for characters that could mislead a reader of `text`.
+pub fn analyze_text(text: &str) -> TextSafety {
+    let chars: Vec<(usize, char)> = text.char_indices().collect();
+    let mut issues = Vec::new();
+    let mut sanitized = String::with_capacity(text.len());
+
+    let mut i = 0;
+    while i < chars.len() {
+        let (offset, c) = chars[i];
+
+        if c == ESC || c == CSI {
+            let end = escape_sequence_end(&chars, i);
+            let end_offset = chars.get(end).map_or(text.len(), |&(offset, _)| offset);
+            issues.push(SafetyIssue {
+                kind: SafetyIssueKind::TerminalEscape,
+                offset,
+                length: end_offset - offset,
+                description: format!("Terminal escape sequence at byte {}", offset),
+            });
+            for &(_, c) in &chars[i..end] {
+                sanitized.push_str(&visible(c));
+            }
+            i = end;
+            continue;
+        }
+
+        let kind = match c {
+            '\u{0}'..='\u{1f}' | '\u{7f}' => Some(SafetyIssueKind::ControlCharacter),
+            '\u{80}'..='\u{9f}' => Some(SafetyIssueKind::C1Control),
+            _ if bidi_name(c).is_some() => Some(SafetyIssueKind::BidiControl),
+            // Joiners shape emoji and Indic/Arabic text; next to ASCII they only hide things
+            '\u{200c}' | '\u{200d}' if !touches_ascii(&chars, i) => None,
+            _ if zero_width_name(c).is_some() => Some(SafetyIssueKind::ZeroWidth),
+            _ => None,
+        };
+
+        if let Some(kind) = kind {
+            let name = bidi_name(c).or_else(|| zero_width_name(c)).unwrap_or(match kind {
+                SafetyIssueKind::C1Control => "C1 control",
+                _ => "control character",
+            });
+            issues.push(SafetyIssue {
+                kind,
+                offset,
+                length: c.len_utf8(),
+                description: format!("U+{:04X} ({}) at byte {}", c as u32, name, offset),
+            });
+            sanitized.push_str(&visible(c));
+        } else {
+            sanitized.push(c);
+        }
+        i += 1;
+    }
+
+    issues.extend(mixed_script_words(text));
+    issues.sort_by_key(|issue| issue.offset);
+
+    TextSafety { issues, sanitized }
+}
+
+// Index one past the escape sequence starting at `start`: CSI runs to its
+// final byte (0x40-0x7e), OSC to BEL or ST, anything else is ESC plus one
+fn escape_sequence_end(chars: &[(usize, char)], start: usize) -> usize {
+    let (body, kind) = match chars[start].1 {
+        CSI => (start + 1, '['),
+        _ => match chars.get(start + 1) {
+            Some(&(_, kind @ ('[' | ']'))) => (start + 2, kind),
+            Some(_) => return start + 2,
+            None => return start + 1,
+        },
+    };
+
+    let mut i = body;
+    while let Some(&(_, c)) = chars.get(i) {
+        match kind {
+            '[' if ('\u{40}'..='\u{7e}').contains(&c) => return i + 1,
+            '[' if !('\u{20}'..='\u{3f}').contains(&c) => return i,
+            ']' if c == BEL => return i + 1,
+            ']' if c == ESC => return if chars.get(i + 1).map(|&(_, c)| c) == Some('\\') { i + 2 } else { i },
+            _ => {},
+        }
+        i += 1;
+    }
+    i
+}
+
+fn touches_ascii(chars: &[(usize, char)], i: usize) -> bool {
+    let before = i.checked_sub(1).and_then(|i| chars.get(i));
+    [before, chars.get(i + 1)].iter().any(|c| c.is_some_and(|&(_, c)| c.is_ascii()))
+}
+
+fn visible(c: char) -> String {
+    match c {
+        '\u{0}'..='\u{1f}' => char::from_u32(0x2400 + c as u32).unwrap().to_string(),
+        '\u{7f}' => '\u{2421}'.to_string(),
+        c if c == CSI || bidi_name(c).is_some() || zero_width_name(c).is_some() || ('\u{80}'..='\u{9f}').contains(&c) => {
+            format!("<U+{:04X}>", c as u32)
+        },
+        c => c.to_string(),
+    }
+}
+
+fn bidi_name(c: char) -> Option<&'static str> {
+    Some(match c {
+        '\u{061c}' => "ARABIC LETTER MARK",
+        '\u{200e}' => "LEFT-TO-RIGHT MARK",
+        '\u{200f}' => "RIGHT-TO-LEFT MARK",
+        '\u{202a}' => "LEFT-TO-RIGHT EMBEDDING",
+        '\u{202b}' => "RIGHT-TO-LEFT EMBEDDING",
+        '\u{202c}' => "POP DIRECTIONAL FORMATTING",
+        '\u{202d}' => "LEFT-TO-RIGHT OVERRIDE",
+        '\u{202e}' => "RIGHT-TO-LEFT OVERRIDE",
+        '\u{2066}' => "LEFT-TO-RIGHT ISOLATE",
+        '\u{2067}' => "RIGHT-TO-LEFT ISOLATE",
+        '\u{2068}' => "FIRST STRONG ISOLATE",
+        '\u{2069}' => "POP DIRECTIONAL ISOLATE",
+        _ => return None,
+    })
+}
+
+fn zero_width_name(c: char) -> Option<&'static str> {
+    Some(match c {
+        '\u{00ad}' => "SOFT HYPHEN",
+        '\u{180e}' => "MONGOLIAN VOWEL SEPARATOR",
+        '\u{200b}' => "ZERO WIDTH SPACE",
+        '\u{200c}' => "ZERO WIDTH NON-JOINER",
+        '\u{200d}' => "ZERO WIDTH JOINER",
+        '\u{2060}' => "WORD JOINER",
+        '\u{2061}'..='\u{2064}' => "INVISIBLE OPERATOR",
+        '\u{feff}' => "ZERO WIDTH NO-BREAK SPACE",
+        _ => return None,
+    })
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Script {
+    Latin,
+    Greek,
+    Cyrillic,
+}
+
+// Only the scripts with letters that pass for one another
+fn script(c: char) -> Option<Script> {
+    match c {
+        'A'..='Z' | 'a'..='z' | '\u{00c0}'..='\u{024f}' | '\u{1e00}'..='\u{1eff}' if c != '×' && c != '÷' => {
+            Some(Script::Latin)
+        },
+        '\u{0370}'..='\u{03ff}' | '\u{1f00}'..='\u{1fff}' => Some(Script::Greek),
+        '\u{0400}'..='\u{052f}' => Some(Script::Cyrillic),
+        _ => None,
+    }
+}
+
+// Words such as "pаypal" with a Cyrillic "а": each script alone is fine
+fn mixed_script_words(text: &str) -> Vec<SafetyIssue> {
+    let mut issues = Vec::new();
+    let mut offset = 0;
+
+    for word in text.split(|c: char| !c.is_alphanumeric()) {
+        let mut scripts: Vec<Script> = Vec::new();
+        for script in word.chars().filter_map(script) {
+            if !scripts.contains(&script) {
+                scripts.push(script);
+            }
+        }
+        if scripts.len() > 1 {
+            let names: Vec<String> = scripts.iter().map(|script| format!("{:?}", script)).collect();
+            issues.push(SafetyIssue {
+                kind: SafetyIssueKind::MixedScript,
+                offset,
+                length: word.len(),
+                description: format!("\"{}\" at byte {} mixes {} letters", word, offset, names.join(" and ")),
+            });
+        }
+        // Every separator is a single character; step over the word and it
+        offset += word.len();
+        offset += text[offset..].chars().next().map_or(0, char::len_utf8);
+    }
+
+    issues
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_plain_text_is_safe() {
+        for text in ["Maintenance in 30min", "Přestávka, zpět v 15:00", "メンテナンス中 🛠", "👨\u{200d}👩\u{200d}👧 family"] {
+            let safety = analyze_text(text);
+            assert!(safety.is_safe(), "{}: {:?}", text, safety.issues);
+            assert_eq!(safety.sanitized, text);
+        }
+    }
+
+    #[test]
+    fn test_invisible_and_control_characters() {
+        let safety = analyze_text("ok\u{202e}txt.exe\u{200b}\u{7}\u{85}");
+        let kinds: Vec<_> = safety.issues.iter().map(|issue| (issue.kind, issue.offset)).collect();
+        assert_eq!(kinds, vec![
+            (SafetyIssueKind::BidiControl, 2),
+            (SafetyIssueKind::ZeroWidth, 12),
+            (SafetyIssueKind::ControlCharacter, 15),
+            (SafetyIssueKind::C1Control, 16),
+        ]);
+        assert_eq!(safety.sanitized, "ok<U+202E>txt.exe<U+200B>\u{2407}<U+0085>");
+        assert_eq!(safety.issues[0].description, "U+202E (RIGHT-TO-LEFT OVERRIDE) at byte 2");
+    }
+
+    #[test]
+    fn test_escapes_and_mixed_script() {
+        let safety = analyze_text("\u{1b}[2J\u{1b}]0;pwned\u{7}Call p\u{430}ypal");
+        assert_eq!(safety.issues[0].kind, SafetyIssueKind::TerminalEscape);
+        assert_eq!((safety.issues[0].offset, safety.issues[0].length), (0, 4));
+        assert_eq!((safety.issues[1].offset, safety.issues[1].length), (4, 10));
+        assert_eq!(safety.issues[2].kind, SafetyIssueKind::MixedScript);
+        assert_eq!(safety.issues[2].description, "\"p\u{430}ypal\" at byte 19 mixes Latin and Cyrillic letters");
+        assert!(safety.sanitized.starts_with("\u{241b}[2J\u{241b}]0;pwned\u{2407}"));
+    }
+}
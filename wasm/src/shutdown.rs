@@ -0,0 +1,454 @@
+use std::borrow::Cow;
+
+use serde::{Deserialize, Serialize};
+use unicode_segmentation::UnicodeSegmentation;
+
+use crate::{
+    analyze_text, extract_entities, get_error_names, parse_hex, BgpCeaseSubcode, DecodeError,
+    DecodeResponse, EncodeError, Entity, Notification, TextSafety, BGP_ERROR_CEASE,
+    BGP_NOTIFICATION, MAX_SHUTDOWN_MSG_LEN, MIN_NOTIFICATION_LEN, RFC8203_MAX_SHUTDOWN_MSG_LEN,
+};
+
+/// An RFC 9003 Shutdown Communication carried in a Cease NOTIFICATION
+/// with subcode Administrative Shutdown (2) or Administrative Reset (4).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ShutdownCommunication {
+    pub subcode: BgpCeaseSubcode,
+    pub message: String,
+}
+
+/// Which length limit the encoder should respect: many deployed speakers
+/// still implement RFC 8203 and truncate or reject anything over 128 octets.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ShutdownCompatibility {
+    /// Up to 255 octets (RFC 9003).
+    #[default]
+    Rfc9003,
+    /// Up to 255 octets, warning above 128.
+    Rfc8203Warn,
+    /// Up to 128 octets (RFC 8203).
+    Rfc8203,
+}
+
+impl ShutdownCompatibility {
+    /// Longest message, in bytes, this mode lets through.
+    pub fn max_len(self) -> usize {
+        match self {
+            ShutdownCompatibility::Rfc8203 => RFC8203_MAX_SHUTDOWN_MSG_LEN,
+            _ => MAX_SHUTDOWN_MSG_LEN,
+        }
+    }
+}
+
+/// Appended by [`truncate_message`] unless the caller picks another.
+pub const DEFAULT_ELLIPSIS: &str = "\u{2026}";
+
+/// How much of a byte limit a message uses.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MessageBudget {
+    /// UTF-8 length of the message.
+    pub bytes: usize,
+    pub max: usize,
+    /// Bytes still free; 0 once over the limit.
+    pub remaining: usize,
+    /// The first character that does not fit, if any.
+    pub overflow: Option<BudgetOverflow>,
+}
+
+/// The grapheme cluster (user-perceived character) that crosses the limit.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BudgetOverflow {
+    pub grapheme: String,
+    /// Byte offset and length within the message.
+    pub offset: usize,
+    pub length: usize,
+    /// Position counted in grapheme clusters, as a user would count.
+    pub index: usize,
+}
+
+/// Whatever could be recovered from a Cease NOTIFICATION that may not be a
+/// well-formed shutdown communication: operators still want to read a
+/// message whose length octet is off by one or that has a stray byte.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LenientShutdown {
+    pub subcode: u8,
+    /// Every byte after the length octet, with invalid UTF-8 replaced by U+FFFD.
+    pub text: String,
+    /// The length octet, if the data field had one.
+    pub declared_length: Option<usize>,
+    /// Bytes actually present after the length octet.
+    pub actual_length: usize,
+    /// What the strict decoder would have rejected, in wire order.
+    pub diagnostics: Vec<DecodeError>,
+}
+
+impl ShutdownCommunication {
+    pub fn new(subcode: BgpCeaseSubcode, message: impl Into<String>) -> Self {
+        ShutdownCommunication { subcode, message: message.into() }
+    }
+
+    /// Parse a complete NOTIFICATION message, header included.
+    pub fn parse(bytes: &[u8]) -> Result<Self, DecodeError> {
+        Self::from_notification(&Notification::parse(bytes)?)
+    }
+
+    /// Extract the shutdown communication from an already parsed notification.
+    pub fn from_notification(notification: &Notification) -> Result<Self, DecodeError> {
+        if notification.error_code != BGP_ERROR_CEASE {
+            return Err(DecodeError::NotCease { actual: notification.error_code });
+        }
+
+        let subcode = BgpCeaseSubcode::from_u8(notification.subcode)
+            .ok_or(DecodeError::UnknownCeaseSubcode { actual: notification.subcode })?;
+
+        let data = &notification.data;
+        if data.is_empty() {
+            return Err(DecodeError::MissingShutdownLength);
+        }
+
+        let text_length = data[0] as usize;
+        if data.len() != 1 + text_length {
+            return Err(DecodeError::ShutdownLengthMismatch {
+                expected: MIN_NOTIFICATION_LEN + 1 + text_length,
+                actual: MIN_NOTIFICATION_LEN + data.len(),
+            });
+        }
+
+        let message = std::str::from_utf8(&data[1..])
+            .map_err(|e| DecodeError::InvalidUtf8 {
+                offset: MIN_NOTIFICATION_LEN + 1 + e.valid_up_to(),
+            })?
+            .to_string();
+
+        Ok(ShutdownCommunication { subcode, message })
+    }
+
+    /// Wrap the communication in a Cease NOTIFICATION.
+    pub fn to_notification(&self) -> Result<Notification, EncodeError> {
+        let utf8_bytes = self.message.as_bytes();
+        if utf8_bytes.len() > MAX_SHUTDOWN_MSG_LEN {
+            return Err(EncodeError::ShutdownMessageTooLong {
+                actual: utf8_bytes.len(),
+                max: MAX_SHUTDOWN_MSG_LEN,
+            });
+        }
+
+        let mut data = Vec::with_capacity(1 + utf8_bytes.len());
+        data.push(utf8_bytes.len() as u8);
+        data.extend_from_slice(utf8_bytes);
+
+        Ok(Notification::new(BGP_ERROR_CEASE, self.subcode as u8, data))
+    }
+
+    /// Check the message length against `compatibility`. Returns the
+    /// warnings to show alongside the encoded message.
+    pub fn check_compatibility(&self, compatibility: ShutdownCompatibility) -> Result<Vec<String>, EncodeError> {
+        let len = self.message.len();
+        match compatibility {
+            ShutdownCompatibility::Rfc8203 if len > RFC8203_MAX_SHUTDOWN_MSG_LEN => {
+                Err(EncodeError::ShutdownMessageTooLong { actual: len, max: RFC8203_MAX_SHUTDOWN_MSG_LEN })
+            },
+            ShutdownCompatibility::Rfc8203Warn => Ok(self.rfc8203_note().into_iter().collect()),
+            _ => Ok(Vec::new()),
+        }
+    }
+
+    /// Cut the message down to `max` bytes at a grapheme cluster boundary,
+    /// ending it with `ellipsis`. Returns a warning if anything was cut.
+    pub fn truncate(&mut self, max: usize, ellipsis: &str) -> Result<Option<String>, EncodeError> {
+        let original = self.message.len();
+        if let Cow::Owned(truncated) = truncate_message(&self.message, max, ellipsis)? {
+            self.message = truncated;
+            return Ok(Some(format!("Message truncated from {} to {} bytes", original, self.message.len())));
+        }
+        Ok(None)
+    }
+
+    /// Control, bidi, zero-width and lookalike characters in the message,
+    /// and a copy of it that is safe to print.
+    pub fn safety(&self) -> TextSafety {
+        analyze_text(&self.message)
+    }
+
+    /// Caveats for whoever receives the message.
+    pub fn notes(&self) -> Vec<String> {
+        text_notes(&self.message)
+    }
+
+    fn rfc8203_note(&self) -> Option<String> {
+        rfc8203_note(self.message.len())
+    }
+
+    /// Serialize to wire format, header included.
+    pub fn to_bytes(&self) -> Result<Vec<u8>, EncodeError> {
+        self.to_notification()?.to_bytes()
+    }
+}
+
+impl LenientShutdown {
+    /// Parse a complete NOTIFICATION message, header included. A header
+    /// length that disagrees with the bytes received is only a diagnostic.
+    pub fn parse(bytes: &[u8]) -> Result<Self, DecodeError> {
+        match Notification::parse(bytes) {
+            Ok(notification) => Self::from_notification(&notification),
+            Err(e @ DecodeError::LengthMismatch { .. }) if bytes[18] == BGP_NOTIFICATION => {
+                let notification = Notification::new(bytes[19], bytes[20], bytes[MIN_NOTIFICATION_LEN..].to_vec());
+                let mut shutdown = Self::from_notification(&notification)?;
+                shutdown.diagnostics.insert(0, e);
+                Ok(shutdown)
+            },
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Parse a hex dump; whitespace, `:` and `-` separators are ignored.
+    pub fn from_hex(hex_input: &str) -> Result<Self, DecodeError> {
+        Self::parse(&parse_hex(hex_input)?)
+    }
+
+    /// Only a notification that is not a Cease at all is an error.
+    pub fn from_notification(notification: &Notification) -> Result<Self, DecodeError> {
+        if notification.error_code != BGP_ERROR_CEASE {
+            return Err(DecodeError::NotCease { actual: notification.error_code });
+        }
+        Ok(Self::from_data(notification.subcode, &notification.data))
+    }
+
+    /// Recover the text from the data field of a Cease with `subcode`.
+    pub fn from_data(subcode: u8, data: &[u8]) -> Self {
+        let mut diagnostics = Vec::new();
+        if BgpCeaseSubcode::from_u8(subcode).is_none() {
+            diagnostics.push(DecodeError::UnknownCeaseSubcode { actual: subcode });
+        }
+
+        let (declared_length, body) = match data.split_first() {
+            Some((&length, body)) => (Some(length as usize), body),
+            None => {
+                diagnostics.push(DecodeError::MissingShutdownLength);
+                (None, data)
+            },
+        };
+        if let Some(length) = declared_length.filter(|&length| length != body.len()) {
+            diagnostics.push(DecodeError::ShutdownLengthMismatch {
+                expected: MIN_NOTIFICATION_LEN + 1 + length,
+                actual: MIN_NOTIFICATION_LEN + data.len(),
+            });
+        }
+
+        let mut text = String::with_capacity(body.len());
+        let mut position = 0;
+        for chunk in body.utf8_chunks() {
+            text.push_str(chunk.valid());
+            position += chunk.valid().len();
+            if !chunk.invalid().is_empty() {
+                text.push(char::REPLACEMENT_CHARACTER);
+                diagnostics.push(DecodeError::InvalidUtf8 { offset: MIN_NOTIFICATION_LEN + 1 + position });
+                position += chunk.invalid().len();
+            }
+        }
+
+        LenientShutdown { subcode, text, declared_length, actual_length: body.len(), diagnostics }
+    }
+
+    /// True if the strict decoder would have accepted the same bytes.
+    pub fn is_clean(&self) -> bool {
+        self.diagnostics.is_empty()
+    }
+
+    /// The strict form, when there is nothing to complain about.
+    pub fn to_communication(&self) -> Option<ShutdownCommunication> {
+        let subcode = BgpCeaseSubcode::from_u8(self.subcode)?;
+        self.is_clean().then(|| ShutdownCommunication::new(subcode, self.text.clone()))
+    }
+
+    pub fn safety(&self) -> TextSafety {
+        analyze_text(&self.text)
+    }
+
+    /// Caveats about the text itself; see `diagnostics` for the encoding.
+    pub fn notes(&self) -> Vec<String> {
+        text_notes(&self.text)
+    }
+
+    /// Tickets, links, contacts, AS numbers and times mentioned in the text.
+    pub fn entities(&self) -> Vec<Entity> {
+        extract_entities(&self.text)
+    }
+}
+
+impl From<&LenientShutdown> for DecodeResponse {
+    fn from(shutdown: &LenientShutdown) -> Self {
+        let safety = shutdown.safety();
+        DecodeResponse {
+            subcode: get_error_names(BGP_ERROR_CEASE, shutdown.subcode).1,
+            subcode_value: shutdown.subcode,
+            message: shutdown.text.clone(),
+            sanitized: safety.sanitized,
+            safety: safety.issues,
+            notes: shutdown.notes(),
+            declared_length: shutdown.declared_length,
+            actual_length: shutdown.actual_length,
+            diagnostics: shutdown.diagnostics.iter().map(Into::into).collect(),
+            entities: shutdown.entities(),
+        }
+    }
+}
+
+/// Measure `text` against a limit of `max` UTF-8 bytes.
+pub fn message_budget(text: &str, max: usize) -> MessageBudget {
+    let overflow = text
+        .grapheme_indices(true)
+        .enumerate()
+        .find(|(_, (offset, grapheme))| offset + grapheme.len() > max)
+        .map(|(index, (offset, grapheme))| BudgetOverflow {
+            grapheme: grapheme.to_string(),
+            offset,
+            length: grapheme.len(),
+            index,
+        });
+
+    MessageBudget { bytes: text.len(), max, remaining: max.saturating_sub(text.len()), overflow }
+}
+
+/// `text` unchanged if it fits in `max` bytes, otherwise the longest run
+/// of whole grapheme clusters that fits together with `ellipsis`. Cutting
+/// by cluster keeps emoji sequences and combining accents intact.
+pub fn truncate_message<'a>(text: &'a str, max: usize, ellipsis: &str) -> Result<Cow<'a, str>, EncodeError> {
+    if text.len() <= max {
+        return Ok(Cow::Borrowed(text));
+    }
+    if ellipsis.len() > max {
+        return Err(EncodeError::ShutdownMessageTooLong { actual: ellipsis.len(), max });
+    }
+
+    let room = max - ellipsis.len();
+    let end = text
+        .grapheme_indices(true)
+        .map(|(offset, grapheme)| offset + grapheme.len())
+        .take_while(|&end| end <= room)
+        .last()
+        .unwrap_or(0);
+
+    Ok(Cow::Owned(format!("{}{}", &text[..end], ellipsis)))
+}
+
+fn text_notes(text: &str) -> Vec<String> {
+    let mut notes: Vec<String> = rfc8203_note(text.len()).into_iter().collect();
+    notes.extend(analyze_text(text).issues.iter().map(|issue| format!("Suspicious text: {}", issue.description)));
+    notes
+}
+
+fn rfc8203_note(len: usize) -> Option<String> {
+    (RFC8203_MAX_SHUTDOWN_MSG_LEN + 1..=MAX_SHUTDOWN_MSG_LEN).contains(&len).then(|| format!(
+        "Message is {} bytes: may be rejected by RFC 8203-only speakers (limit {})",
+        len, RFC8203_MAX_SHUTDOWN_MSG_LEN
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_shutdown_round_trip() {
+        let comm = ShutdownCommunication::new(BgpCeaseSubcode::AdminReset, "Přestávka 🛠");
+        let bytes = comm.to_bytes().unwrap();
+        assert_eq!(bytes.len(), 22 + comm.message.len());
+        assert_eq!(ShutdownCommunication::parse(&bytes).unwrap(), comm);
+    }
+
+    #[test]
+    fn test_shutdown_compatibility() {
+        let short = ShutdownCommunication::new(BgpCeaseSubcode::AdminShutdown, "x".repeat(128));
+        assert_eq!(short.check_compatibility(ShutdownCompatibility::Rfc8203), Ok(Vec::new()));
+        assert!(short.notes().is_empty());
+
+        let long = ShutdownCommunication::new(BgpCeaseSubcode::AdminShutdown, "x".repeat(129));
+        assert_eq!(long.check_compatibility(ShutdownCompatibility::Rfc9003), Ok(Vec::new()));
+        assert_eq!(long.check_compatibility(ShutdownCompatibility::Rfc8203Warn).unwrap().len(), 1);
+        let err = long.check_compatibility(ShutdownCompatibility::Rfc8203).unwrap_err();
+        assert_eq!(err.rfc(), Some("RFC 8203 2"));
+
+        // The decoder flags it too
+        let decoded = ShutdownCommunication::parse(&long.to_bytes().unwrap()).unwrap();
+        assert!(decoded.notes()[0].contains("may be rejected by RFC 8203-only speakers"));
+    }
+
+    #[test]
+    fn test_shutdown_rejects_bad_input() {
+        let long = ShutdownCommunication::new(BgpCeaseSubcode::AdminShutdown, "x".repeat(256));
+        assert!(long.to_bytes().is_err());
+
+        let not_cease = Notification::new(4, 0, vec![]);
+        assert!(ShutdownCommunication::from_notification(&not_cease).is_err());
+
+        let off_by_one = Notification::new(6, 2, vec![3, b'a', b'b']);
+        assert_eq!(
+            ShutdownCommunication::from_notification(&off_by_one),
+            Err(DecodeError::ShutdownLengthMismatch { expected: 25, actual: 24 })
+        );
+
+        let bad_utf8 = Notification::new(6, 2, vec![2, b'a', 0xff]);
+        assert_eq!(
+            ShutdownCommunication::from_notification(&bad_utf8),
+            Err(DecodeError::InvalidUtf8 { offset: 23 })
+        );
+    }
+
+    #[test]
+    fn test_lenient_recovers_text() {
+        let clean = LenientShutdown::from_data(2, &[2, b'o', b'k']);
+        assert!(clean.is_clean());
+        assert_eq!(clean.to_communication(), Some(ShutdownCommunication::new(BgpCeaseSubcode::AdminShutdown, "ok")));
+
+        // Length octet one short, a stray 0xff, and a subcode nobody assigned
+        let broken = LenientShutdown::from_data(42, &[3, b'a', 0xff, b'b', b'c']);
+        assert_eq!(broken.text, "a\u{fffd}bc");
+        assert_eq!((broken.declared_length, broken.actual_length), (Some(3), 4));
+        assert_eq!(broken.diagnostics, vec![
+            DecodeError::UnknownCeaseSubcode { actual: 42 },
+            DecodeError::ShutdownLengthMismatch { expected: 25, actual: 26 },
+            DecodeError::InvalidUtf8 { offset: 23 },
+        ]);
+        assert_eq!(broken.to_communication(), None);
+
+        let empty = LenientShutdown::from_data(4, &[]);
+        assert_eq!((empty.declared_length, empty.text.as_str()), (None, ""));
+        assert_eq!(empty.diagnostics, vec![DecodeError::MissingShutdownLength]);
+    }
+
+    #[test]
+    fn test_lenient_header_length() {
+        // Header says 0x18 bytes but 0x19 arrived
+        let lenient = LenientShutdown::from_hex("ffffffffffffffffffffffffffffffff 0018 03 06 02 02 68 69 21").unwrap();
+        assert_eq!(lenient.text, "hi!");
+        assert_eq!(lenient.diagnostics[0], DecodeError::LengthMismatch { declared: 24, received: 25 });
+
+        assert_eq!(
+            LenientShutdown::from_hex("ffffffffffffffffffffffffffffffff 0015 03 04 00"),
+            Err(DecodeError::NotCease { actual: 4 })
+        );
+    }
+
+    #[test]
+    fn test_truncate_at_grapheme_boundary() {
+        // "e" + combining acute and a family emoji must not be split
+        let text = format!("{}{}", "Údržba e\u{301}", "👨\u{200d}👩\u{200d}👧");
+        let budget = message_budget(&text, 12);
+        let overflow = budget.overflow.unwrap();
+        assert_eq!((overflow.offset, overflow.index, overflow.grapheme.as_str()), (12, 8, "👨\u{200d}👩\u{200d}👧"));
+        assert_eq!(budget.remaining, 0);
+
+        assert_eq!(truncate_message(&text, 15, "...").unwrap(), "Údržba e\u{301}...");
+        assert_eq!(truncate_message(&text, 10, "").unwrap(), "Údržba ");
+        assert_eq!(truncate_message(&text, 30, DEFAULT_ELLIPSIS).unwrap(), Cow::Borrowed(text.as_str()));
+        assert!(truncate_message(&text, 2, DEFAULT_ELLIPSIS).is_err());
+
+        let mut comm = ShutdownCommunication::new(BgpCeaseSubcode::AdminShutdown, "メンテナンス".repeat(20));
+        let warning = comm.truncate(ShutdownCompatibility::Rfc8203.max_len(), DEFAULT_ELLIPSIS).unwrap();
+        assert_eq!(warning.as_deref(), Some("Message truncated from 360 to 126 bytes"));
+        assert!(comm.message.ends_with("ン\u{2026}"));
+        assert_eq!(message_budget(&comm.message, 128).remaining, 2);
+    }
+}
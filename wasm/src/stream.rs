@@ -0,0 +1,375 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    message_type_name, parse_hex_bounded, DecodeError, Message, SessionContext,
+    StreamDecodeResponse, StreamMessageResponse, BGP_HEADER_LEN, BGP_MARKER, BGP_OPEN,
+    BGP_ROUTE_REFRESH, MAX_EXTENDED_MESSAGE_LEN,
+};
+
+/// Largest buffer accepted by [`MessageStream::from_hex`].
+pub const MAX_STREAM_LEN: usize = 65536;
+
+/// Several BGP messages sent back to back, e.g. one TCP payload.
+///
+/// Decode errors inside a message do not stop the walk; only a header
+/// that cannot be framed (bad marker or impossible length) does, since
+/// the next message boundary is then unknown.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MessageStream {
+    pub messages: Vec<FramedMessage>,
+    /// Bytes at the end that do not yet form a complete message.
+    pub partial: Option<PartialMessage>,
+    pub framing_error: Option<FramingError>,
+}
+
+/// One message and where it sits in the buffer. Offsets inside `message`
+/// errors are relative to `offset`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FramedMessage {
+    pub offset: usize,
+    pub length: usize,
+    pub message_type: u8,
+    pub message: Result<Message, DecodeError>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PartialMessage {
+    pub offset: usize,
+    pub available: usize,
+    /// Declared length, once the header is complete.
+    pub expected: Option<usize>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FramingError {
+    pub offset: usize,
+    pub error: DecodeError,
+}
+
+impl MessageStream {
+    /// Split `bytes` on each header's length field and decode every message.
+    pub fn decode(bytes: &[u8]) -> Self {
+        Self::decode_with(bytes, &SessionContext::default())
+    }
+
+    /// Decode with the size limits of `context`.
+    pub fn decode_with(bytes: &[u8], context: &SessionContext) -> Self {
+        let mut messages = Vec::new();
+        let mut partial = None;
+        let mut framing_error = None;
+        let mut offset = 0;
+
+        while offset < bytes.len() {
+            let rest = &bytes[offset..];
+            match frame_length(rest, context.max_message_len()) {
+                Ok(Some(length)) if length <= rest.len() => {
+                    let frame = &rest[..length];
+                    messages.push(FramedMessage {
+                        offset,
+                        length,
+                        message_type: frame[18],
+                        message: Message::parse_with(frame, context),
+                    });
+                    offset += length;
+                },
+                Ok(expected) => {
+                    partial = Some(PartialMessage { offset, available: rest.len(), expected });
+                    break;
+                },
+                Err(error) => {
+                    framing_error = Some(FramingError { offset, error });
+                    break;
+                },
+            }
+        }
+
+        MessageStream { messages, partial, framing_error }
+    }
+
+    /// Parse a hex dump of up to [`MAX_STREAM_LEN`] bytes and decode it.
+    pub fn from_hex(hex_input: &str) -> Result<Self, DecodeError> {
+        Self::from_hex_with(hex_input, &SessionContext::default())
+    }
+
+    pub fn from_hex_with(hex_input: &str, context: &SessionContext) -> Result<Self, DecodeError> {
+        Ok(Self::decode_with(&parse_hex_bounded(hex_input, MAX_STREAM_LEN)?, context))
+    }
+}
+
+/// Incremental decoder for one direction of a BGP session.
+///
+/// Feed TCP payload as it arrives with [`push`](Self::push) and call
+/// [`decode`](Self::decode) until it returns `None`, in the manner of a
+/// `tokio_util::codec::Decoder` but without tying it to any runtime.
+/// Partial headers and bodies stay buffered until the rest arrives. A bad
+/// marker or impossible length drops bytes up to the next plausible
+/// header, reported as [`StreamItem::Resync`].
+#[derive(Debug, Clone)]
+pub struct MessageDecoder {
+    buffer: Vec<u8>,
+    // Stream offset of buffer[0]
+    offset: usize,
+    context: SessionContext,
+}
+
+/// What [`MessageDecoder::decode`] produced.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StreamItem {
+    Message(FramedMessage),
+    /// `skipped` bytes starting at `offset` were discarded after `error`.
+    Resync { offset: usize, skipped: usize, error: DecodeError },
+}
+
+impl MessageDecoder {
+    /// Messages may be up to 4096 bytes, or 65535 when `context` has
+    /// Extended Message (RFC 8654) negotiated.
+    pub fn new(context: SessionContext) -> Self {
+        MessageDecoder { buffer: Vec::new(), offset: 0, context }
+    }
+
+    /// Switch limits mid-stream, e.g. once both OPENs have been seen.
+    pub fn set_context(&mut self, context: SessionContext) {
+        self.context = context;
+    }
+
+    pub fn push(&mut self, bytes: &[u8]) {
+        self.buffer.extend_from_slice(bytes);
+    }
+
+    /// Drop anything buffered and carry on at stream `offset`, after bytes
+    /// in between were lost (e.g. missing from a capture).
+    pub fn reset_at(&mut self, offset: usize) {
+        self.buffer.clear();
+        self.offset = offset;
+    }
+
+    /// The next complete message, or `None` until more bytes are pushed.
+    pub fn decode(&mut self) -> Option<StreamItem> {
+        match frame_length(&self.buffer, self.context.max_message_len()) {
+            Ok(Some(length)) if length <= self.buffer.len() => {
+                let message = Message::parse_with(&self.buffer[..length], &self.context);
+                let item = StreamItem::Message(FramedMessage {
+                    offset: self.offset,
+                    length,
+                    message_type: self.buffer[18],
+                    message,
+                });
+                self.consume(length);
+                Some(item)
+            },
+            Ok(_) => None,
+            Err(error) => {
+                let offset = self.offset;
+                let skipped = self.resync_point();
+                self.consume(skipped);
+                Some(StreamItem::Resync { offset, skipped, error })
+            },
+        }
+    }
+
+    /// Buffered bytes that do not form a complete message yet.
+    pub fn pending(&self) -> Option<PartialMessage> {
+        if self.buffer.is_empty() {
+            return None;
+        }
+        Some(PartialMessage {
+            offset: self.offset,
+            available: self.buffer.len(),
+            expected: frame_length(&self.buffer, self.context.max_message_len()).ok().flatten(),
+        })
+    }
+
+    fn consume(&mut self, len: usize) {
+        self.buffer.drain(..len);
+        self.offset += len;
+    }
+
+    // First position after the broken header that could start a message:
+    // a full marker followed by a sane length and a known type, or a run
+    // of 0xff reaching the end of the buffer that may still become one
+    fn resync_point(&self) -> usize {
+        (1..self.buffer.len())
+            .find(|&i| {
+                let candidate = &self.buffer[i..];
+                match frame_length(candidate, self.context.max_message_len()) {
+                    Ok(Some(_)) => matches!(candidate[18], BGP_OPEN..=BGP_ROUTE_REFRESH),
+                    Ok(None) => true,
+                    Err(_) => false,
+                }
+            })
+            .unwrap_or(self.buffer.len())
+    }
+}
+
+impl Default for MessageDecoder {
+    fn default() -> Self {
+        Self::new(SessionContext::default())
+    }
+}
+
+// Declared length of the message at the start of `bytes`, or None while
+// the header itself is incomplete. The same marker and length checks as
+// `validate_message`, for buffers that may hold more or less than one message
+fn frame_length(bytes: &[u8], max_len: usize) -> Result<Option<usize>, DecodeError> {
+    let marker_len = bytes.len().min(BGP_MARKER.len());
+    if let Some(offset) = bytes[..marker_len].iter().position(|&b| b != 0xff) {
+        return Err(DecodeError::BadMarker { offset });
+    }
+    if bytes.len() < BGP_HEADER_LEN {
+        return Ok(None);
+    }
+
+    let declared = ((bytes[16] as usize) << 8) | (bytes[17] as usize);
+    if !(BGP_HEADER_LEN..=max_len).contains(&declared) {
+        return Err(DecodeError::BadDeclaredLength { declared, min: BGP_HEADER_LEN, max: max_len });
+    }
+    Ok(Some(declared))
+}
+
+// `stream` cut at each header's length field without decoding anything;
+// whatever does not frame as a message is the last piece. For writing
+// crafted, possibly malformed, messages out one at a time
+pub(crate) fn split_messages(stream: &[u8]) -> Vec<&[u8]> {
+    let mut pieces = Vec::new();
+    let mut rest = stream;
+    while !rest.is_empty() {
+        let length = match frame_length(rest, MAX_EXTENDED_MESSAGE_LEN) {
+            Ok(Some(length)) if length <= rest.len() => length,
+            _ => rest.len(),
+        };
+        let (piece, tail) = rest.split_at(length);
+        pieces.push(piece);
+        rest = tail;
+    }
+    pieces
+}
+
+impl StreamDecodeResponse {
+    /// Describe `stream` as decoded with `context`.
+    pub fn with_context(stream: &MessageStream, context: &SessionContext) -> Self {
+        StreamDecodeResponse {
+            messages: stream.messages.iter()
+                .map(|framed| StreamMessageResponse::with_context(framed, context))
+                .collect(),
+            partial: stream.partial,
+            framing_error: stream.framing_error.as_ref()
+                .map(|e| format!("{} (message starting at byte {})", e.error, e.offset)),
+        }
+    }
+}
+
+impl From<&MessageStream> for StreamDecodeResponse {
+    fn from(stream: &MessageStream) -> Self {
+        Self::with_context(stream, &SessionContext::default())
+    }
+}
+
+impl StreamMessageResponse {
+    /// Describe `framed` as decoded with `context`.
+    pub fn with_context(framed: &FramedMessage, context: &SessionContext) -> Self {
+        let (summary, error) = match &framed.message {
+            Ok(message) => (message.summary_with(context), None),
+            Err(e) => (Vec::new(), Some(e.to_string())),
+        };
+        StreamMessageResponse {
+            offset: framed.offset,
+            length: framed.length,
+            message_type: framed.message_type,
+            type_name: message_type_name(framed.message_type).to_string(),
+            summary,
+            error,
+        }
+    }
+}
+
+impl From<&FramedMessage> for StreamMessageResponse {
+    fn from(framed: &FramedMessage) -> Self {
+        Self::with_context(framed, &SessionContext::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{parse_hex, Keepalive};
+
+    const KEEPALIVE: &str = "ffffffffffffffffffffffffffffffff 0013 04";
+    const CEASE: &str = "ffffffffffffffffffffffffffffffff 0015 03 06 02";
+
+    #[test]
+    fn test_stream_with_partial_tail() {
+        let hex = format!("{} {} {} ffffffffffffffff", KEEPALIVE, CEASE, KEEPALIVE);
+        let stream = MessageStream::from_hex(&hex).unwrap();
+
+        let offsets: Vec<_> = stream.messages.iter().map(|m| (m.offset, m.message_type)).collect();
+        assert_eq!(offsets, vec![(0, 4), (19, 3), (40, 4)]);
+        assert_eq!(stream.messages[0].message, Ok(Message::Keepalive(Keepalive)));
+        assert_eq!(stream.partial, Some(PartialMessage { offset: 59, available: 8, expected: None }));
+        assert_eq!(stream.framing_error, None);
+    }
+
+    #[test]
+    fn test_stream_errors() {
+        // A bad message body is reported in place; the walk continues
+        let hex = format!("ffffffffffffffffffffffffffffffff 0014 04 00 {}", KEEPALIVE);
+        let stream = MessageStream::from_hex(&hex).unwrap();
+        assert_eq!(stream.messages.len(), 2);
+        assert!(stream.messages[0].message.is_err());
+
+        // Declared length longer than what was captured
+        let mut bytes = parse_hex(CEASE).unwrap();
+        bytes[17] = 0x40;
+        let stream = MessageStream::decode(&bytes);
+        assert_eq!(stream.partial, Some(PartialMessage { offset: 0, available: 21, expected: Some(64) }));
+
+        // A broken marker stops framing
+        let hex = format!("{} ffff00ffffffffffffffffffffffffff 0013 04", KEEPALIVE);
+        let stream = MessageStream::from_hex(&hex).unwrap();
+        assert_eq!(stream.framing_error, Some(FramingError { offset: 19, error: DecodeError::BadMarker { offset: 2 } }));
+    }
+
+    #[test]
+    fn test_stream_extended_context() {
+        // Bad Message Length 5000 is within the limit once Extended Message is on
+        let hex = format!("ffffffffffffffffffffffffffffffff 0017 03 01 02 1388 {}", KEEPALIVE);
+        let context = SessionContext::new(true);
+        let stream = MessageStream::from_hex_with(&hex, &context).unwrap();
+        let response = StreamDecodeResponse::with_context(&stream, &context);
+        assert!(response.messages[0].summary[2].contains("valid range: 19-65535, Extended Message"));
+        let response = StreamDecodeResponse::from(&stream);
+        assert!(response.messages[0].summary[2].contains("valid range: 19-4096"));
+    }
+
+    #[test]
+    fn test_decoder_incremental() {
+        let bytes = parse_hex(&format!("{} {}", CEASE, KEEPALIVE)).unwrap();
+        let mut decoder = MessageDecoder::default();
+
+        // Feed in awkward chunks: mid-marker, mid-length, mid-body
+        let mut items = Vec::new();
+        for chunk in [&bytes[..10], &bytes[10..17], &bytes[17..20], &bytes[20..]] {
+            decoder.push(chunk);
+            while let Some(item) = decoder.decode() {
+                items.push(item);
+            }
+        }
+
+        assert_eq!(items.len(), 2);
+        assert!(matches!(&items[1], StreamItem::Message(m) if m.offset == 21 && m.message_type == 4));
+        assert_eq!(decoder.pending(), None);
+    }
+
+    #[test]
+    fn test_decoder_resync() {
+        // Garbage, then a corrupted marker, then two good messages
+        let hex = format!("0102 ffffffffffffff00ffffffffffffffff 0013 04 {} {}", KEEPALIVE, CEASE);
+        let mut decoder = MessageDecoder::new(SessionContext::new(true));
+        decoder.push(&parse_hex_bounded(&hex, MAX_STREAM_LEN).unwrap());
+
+        let resync = decoder.decode().unwrap();
+        assert_eq!(resync, StreamItem::Resync { offset: 0, skipped: 21, error: DecodeError::BadMarker { offset: 0 } });
+        assert!(matches!(decoder.decode(), Some(StreamItem::Message(m)) if m.offset == 21));
+        assert!(matches!(decoder.decode(), Some(StreamItem::Message(m)) if m.offset == 40));
+        assert_eq!(decoder.decode(), None);
+    }
+}
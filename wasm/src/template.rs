@@ -0,0 +1,298 @@
+// Shutdown communication templates. Placeholders in braces are filled from
+// the session (AS number, nick) and per-message values (ticket, end of the
+// maintenance window, contact), so every message a team sends reads the
+// same and stays parseable by whoever receives it.
+
+use serde::{Deserialize, Serialize};
+
+use crate::{BgpCeaseSubcode, EncodeError, MAX_SHUTDOWN_MSG_LEN};
+
+/// Placeholders a template may use.
+pub const PLACEHOLDERS: &[&str] = &["asn", "nick", "ticket", "until", "contact"];
+
+/// Values for a template's placeholders. A placeholder the template uses
+/// must have a value; the rest may be left out.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TemplateValues {
+    #[serde(default)]
+    pub asn: Option<u32>,
+    #[serde(default)]
+    pub nick: Option<String>,
+    #[serde(default)]
+    pub ticket: Option<String>,
+    /// An RFC 3339 time or a duration such as `2h30m` or `PT2H30M`.
+    #[serde(default)]
+    pub until: Option<String>,
+    #[serde(default)]
+    pub contact: Option<String>,
+}
+
+/// A template shipped with the crate.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct ShutdownTemplate {
+    pub name: &'static str,
+    pub description: &'static str,
+    /// Administrative Shutdown (2) or Administrative Reset (4).
+    pub subcode: u8,
+    pub template: &'static str,
+}
+
+/// Built-in templates, for planned work and for sessions going away for good.
+pub const TEMPLATES: &[ShutdownTemplate] = &[
+    ShutdownTemplate {
+        name: "maintenance",
+        description: "Planned maintenance with a known end",
+        subcode: BgpCeaseSubcode::AdminShutdown as u8,
+        template: "{asn} maintenance until {until}; ticket {ticket}; contact {contact}",
+    },
+    ShutdownTemplate {
+        name: "reboot",
+        description: "Router reload, session expected back",
+        subcode: BgpCeaseSubcode::AdminReset as u8,
+        template: "{asn} reboot, back by {until}; ticket {ticket}; contact {contact}",
+    },
+    ShutdownTemplate {
+        name: "decommission",
+        description: "Session removed permanently",
+        subcode: BgpCeaseSubcode::AdminShutdown as u8,
+        template: "{asn} decommissioning this session; ticket {ticket}; contact {contact}",
+    },
+];
+
+/// Built-in template called `name`.
+pub fn find_template(name: &str) -> Option<&'static ShutdownTemplate> {
+    TEMPLATES.iter().find(|template| template.name.eq_ignore_ascii_case(name))
+}
+
+/// Fill in `template`'s placeholders. `{{` and `}}` stand for literal
+/// braces. Fails if a placeholder is unknown or has no value, or if the
+/// result does not fit a shutdown communication.
+pub fn expand_template(template: &str, values: &TemplateValues) -> Result<String, EncodeError> {
+    let unbalanced = EncodeError::InvalidFormat {
+        field: "template",
+        expected: "balanced braces, with {{ and }} for literal ones",
+    };
+    let mut message = String::with_capacity(template.len());
+    let mut rest = template;
+
+    while let Some(start) = rest.find(['{', '}']) {
+        message.push_str(&rest[..start]);
+        let tail = &rest[start..];
+        if tail.starts_with("{{") || tail.starts_with("}}") {
+            message.push_str(&tail[..1]);
+            rest = &tail[2..];
+            continue;
+        }
+        if tail.starts_with('}') {
+            return Err(unbalanced);
+        }
+
+        let end = tail.find('}').ok_or(unbalanced.clone())?;
+        let name = &tail[1..end];
+        let offset = template.len() - tail.len();
+        message.push_str(&placeholder_value(name, offset, values)?);
+        rest = &tail[end + 1..];
+    }
+    message.push_str(rest);
+
+    if message.len() > MAX_SHUTDOWN_MSG_LEN {
+        return Err(EncodeError::ShutdownMessageTooLong { actual: message.len(), max: MAX_SHUTDOWN_MSG_LEN });
+    }
+    Ok(message)
+}
+
+fn placeholder_value(name: &str, offset: usize, values: &TemplateValues) -> Result<String, EncodeError> {
+    let missing = |name| EncodeError::MissingPlaceholder { name };
+    match name {
+        "asn" => values.asn.map(|asn| format!("AS{}", asn)).ok_or(missing("asn")),
+        "nick" => values.nick.clone().ok_or(missing("nick")),
+        "ticket" => values.ticket.clone().ok_or(missing("ticket")),
+        "until" => format_until(values.until.as_deref().ok_or(missing("until"))?),
+        "contact" => values.contact.clone().ok_or(missing("contact")),
+        _ => Err(EncodeError::UnknownPlaceholder { name: name.to_string(), offset }),
+    }
+}
+
+/// Normalize an `{until}` value: an RFC 3339 time comes back with
+/// upper-case `T` and `Z`, a duration as ISO 8601 (RFC 3339 appendix A),
+/// e.g. `90m` becomes `PT1H30M`.
+pub fn format_until(value: &str) -> Result<String, EncodeError> {
+    let value = value.trim();
+    if let Some(time) = parse_rfc3339(value) {
+        return Ok(time);
+    }
+    parse_duration(value).filter(|&seconds| seconds > 0).map(iso_duration).ok_or(EncodeError::InvalidFormat {
+        field: "until",
+        expected: "an RFC 3339 time or a duration such as 2h30m",
+    })
+}
+
+// RFC 3339 section 5.6 date-time, checked field by field
+pub(crate) fn parse_rfc3339(value: &str) -> Option<String> {
+    let bytes = value.as_bytes();
+    let digits = |range: std::ops::Range<usize>| -> Option<u32> {
+        let field = value.get(range)?;
+        field.bytes().all(|b| b.is_ascii_digit()).then(|| field.parse().ok())?
+    };
+    if bytes.len() < 20 || bytes[4] != b'-' || bytes[7] != b'-' || bytes[13] != b':' || bytes[16] != b':' {
+        return None;
+    }
+    if !matches!(bytes[10], b'T' | b't' | b' ') {
+        return None;
+    }
+
+    let (year, month, day) = (digits(0..4)?, digits(5..7)?, digits(8..10)?);
+    let (hour, minute, second) = (digits(11..13)?, digits(14..16)?, digits(17..19)?);
+    if !(1..=12).contains(&month) || day == 0 || day > days_in_month(year, month) {
+        return None;
+    }
+    // Second 60 is a leap second
+    if hour > 23 || minute > 59 || second > 60 {
+        return None;
+    }
+
+    let mut rest = &value[19..];
+    if let Some(fraction) = rest.strip_prefix('.') {
+        let len = fraction.bytes().take_while(u8::is_ascii_digit).count();
+        if len == 0 {
+            return None;
+        }
+        rest = &fraction[len..];
+    }
+    let valid_offset = match rest {
+        "Z" | "z" => true,
+        _ => {
+            let offset = rest.as_bytes();
+            offset.len() == 6
+                && matches!(offset[0], b'+' | b'-')
+                && offset[3] == b':'
+                && rest[1..3].parse::<u32>().is_ok_and(|h| h <= 23)
+                && rest[4..6].parse::<u32>().is_ok_and(|m| m <= 59)
+        },
+    };
+    if !valid_offset {
+        return None;
+    }
+
+    let (date, time) = value.split_at(10);
+    Some(format!("{}T{}", date, &time[1..]).replace('z', "Z"))
+}
+
+fn days_in_month(year: u32, month: u32) -> u32 {
+    match month {
+        2 if year.is_multiple_of(4) && (!year.is_multiple_of(100) || year.is_multiple_of(400)) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+// `1d2h30m15s` in any combination, or the same as ISO 8601 `P1DT2H30M15S`
+pub(crate) fn parse_duration(value: &str) -> Option<u64> {
+    let upper = value.to_ascii_uppercase();
+    let units = match upper.strip_prefix('P') {
+        // Before the T an ISO 8601 M means months, which have no fixed length
+        Some(iso) => match iso.split_once('T') {
+            Some((days, time)) if days.trim_end_matches('D').bytes().all(|b| b.is_ascii_digit()) && !time.contains('D') => {
+                format!("{}{}", days, time)
+            },
+            None if iso.ends_with('D') => iso.to_string(),
+            _ => return None,
+        },
+        None => upper,
+    };
+
+    let mut total: u64 = 0;
+    let mut number = String::new();
+    for c in units.chars() {
+        match c {
+            '0'..='9' => number.push(c),
+            'D' | 'H' | 'M' | 'S' if !number.is_empty() => {
+                let scale = match c {
+                    'D' => 86_400,
+                    'H' => 3_600,
+                    'M' => 60,
+                    _ => 1,
+                };
+                total = total.checked_add(number.parse::<u64>().ok()?.checked_mul(scale)?)?;
+                number.clear();
+            },
+            _ => return None,
+        }
+    }
+    number.is_empty().then_some(total)
+}
+
+pub(crate) fn iso_duration(seconds: u64) -> String {
+    let (days, rest) = (seconds / 86_400, seconds % 86_400);
+    let mut duration = String::from("P");
+    if days > 0 {
+        duration.push_str(&format!("{}D", days));
+    }
+    if rest > 0 {
+        duration.push('T');
+        for (value, unit) in [(rest / 3_600, 'H'), (rest % 3_600 / 60, 'M'), (rest % 60, 'S')] {
+            if value > 0 {
+                duration.push_str(&format!("{}{}", value, unit));
+            }
+        }
+    }
+    duration
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn values() -> TemplateValues {
+        TemplateValues {
+            asn: Some(64500),
+            ticket: Some("CHG-1234".to_string()),
+            until: Some("2026-10-17t22:00:00z".to_string()),
+            contact: Some("noc@example.net".to_string()),
+            ..TemplateValues::default()
+        }
+    }
+
+    #[test]
+    fn test_builtin_templates() {
+        let maintenance = find_template("maintenance").unwrap();
+        assert_eq!(
+            expand_template(maintenance.template, &values()).unwrap(),
+            "AS64500 maintenance until 2026-10-17T22:00:00Z; ticket CHG-1234; contact noc@example.net"
+        );
+        for template in TEMPLATES {
+            assert!(expand_template(template.template, &values()).is_ok(), "{}", template.name);
+        }
+    }
+
+    #[test]
+    fn test_template_errors() {
+        assert_eq!(expand_template("{{literal}} {nick}", &TemplateValues { nick: Some("ix".into()), ..values() }).unwrap(), "{literal} ix");
+        assert_eq!(expand_template("{nick}", &values()), Err(EncodeError::MissingPlaceholder { name: "nick" }));
+        assert_eq!(
+            expand_template("hi {ticke}", &values()),
+            Err(EncodeError::UnknownPlaceholder { name: "ticke".to_string(), offset: 3 })
+        );
+        assert!(expand_template("{asn", &values()).is_err());
+        assert!(expand_template("asn}", &values()).is_err());
+
+        let long = TemplateValues { contact: Some("x".repeat(250)), ..values() };
+        assert_eq!(expand_template("{contact}", &long).unwrap().len(), 250);
+        assert!(matches!(
+            expand_template("{asn} {contact}", &long),
+            Err(EncodeError::ShutdownMessageTooLong { actual: 258, .. })
+        ));
+    }
+
+    #[test]
+    fn test_format_until() {
+        assert_eq!(format_until("90m").unwrap(), "PT1H30M");
+        assert_eq!(format_until("1d2h").unwrap(), "P1DT2H");
+        assert_eq!(format_until("pt45s").unwrap(), "PT45S");
+        assert_eq!(format_until("2028-02-29 08:30:00.5+01:00").unwrap(), "2028-02-29T08:30:00.5+01:00");
+        for bad in ["0m", "2h30", "P1M", "PT1D", "soon", "2027-02-29T08:30:00Z", "2026-10-17T24:00:00Z", "2026-10-17T22:00:00"] {
+            assert!(format_until(bad).is_err(), "{}", bad);
+        }
+    }
+}
@@ -0,0 +1,674 @@
+use std::fmt;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+
+use serde::{Deserialize, Serialize};
+
+use crate::reader::Reader;
+use crate::{
+    extended_length_note, family_name, parse_hex, to_hex, validate_message, DecodeError,
+    PathAttributeResponse, SessionContext, UpdateDecodeResponse, BGP_HEADER_LEN, BGP_UPDATE,
+    MIN_NOTIFICATION_LEN,
+};
+
+const RFC_UPDATE: &str = "RFC 4271 4.3";
+const RFC_UPDATE_ERROR: &str = "RFC 4271 6.3";
+
+/// Smallest UPDATE message: header plus the two length fields.
+pub const MIN_UPDATE_LEN: usize = 23;
+
+pub const ATTR_FLAG_OPTIONAL: u8 = 0x80;
+pub const ATTR_FLAG_TRANSITIVE: u8 = 0x40;
+pub const ATTR_FLAG_PARTIAL: u8 = 0x20;
+pub const ATTR_FLAG_EXTENDED_LENGTH: u8 = 0x10;
+
+/// A BGP UPDATE message (RFC 4271 section 4.3).
+///
+/// AS numbers in AS_PATH are read as 4 octets (RFC 6793) unless the
+/// segments only line up as 2 octets. ADD-PATH encoded NLRI is not
+/// recognized, since that depends on capabilities negotiated earlier.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Update {
+    pub withdrawn_routes: Vec<Prefix>,
+    pub path_attributes: Vec<PathAttribute>,
+    pub nlri: Vec<Prefix>,
+}
+
+/// An IP prefix from the withdrawn routes, NLRI or MP_(UN)REACH_NLRI fields.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Prefix {
+    pub address: IpAddr,
+    pub length: u8,
+}
+
+/// One path attribute with its raw value and, where known, a decoded form.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PathAttribute {
+    pub flags: u8,
+    pub type_code: u8,
+    pub data: Vec<u8>,
+    pub value: AttributeValue,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AsPathSegment {
+    /// 1 = AS_SET, 2 = AS_SEQUENCE, 3 = AS_CONFED_SEQUENCE, 4 = AS_CONFED_SET.
+    pub segment_type: u8,
+    pub asns: Vec<u32>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LargeCommunity {
+    pub global_admin: u32,
+    pub local_data_1: u32,
+    pub local_data_2: u32,
+}
+
+/// Decoded path attribute value.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum AttributeValue {
+    Origin { origin: u8 },
+    AsPath { segments: Vec<AsPathSegment>, four_octet: bool },
+    NextHop { next_hop: Ipv4Addr },
+    MultiExitDisc { med: u32 },
+    LocalPref { local_pref: u32 },
+    AtomicAggregate,
+    Aggregator { asn: u32, address: Ipv4Addr },
+    Communities { communities: Vec<u32> },
+    OriginatorId { originator_id: Ipv4Addr },
+    ClusterList { cluster_ids: Vec<Ipv4Addr> },
+    MpReachNlri { afi: u16, safi: u8, next_hops: Vec<IpAddr>, nlri: Vec<Prefix> },
+    MpUnreachNlri { afi: u16, safi: u8, withdrawn_routes: Vec<Prefix> },
+    ExtendedCommunities { communities: Vec<u64> },
+    As4Path { segments: Vec<AsPathSegment> },
+    As4Aggregator { asn: u32, address: Ipv4Addr },
+    LargeCommunities { communities: Vec<LargeCommunity> },
+    OnlyToCustomer { asn: u32 },
+    /// A type this crate does not decode; see `PathAttribute::data`.
+    Unknown,
+    /// The value does not match its type's format.
+    Malformed { reason: String },
+}
+
+impl Update {
+    /// Parse a complete UPDATE message, header included.
+    pub fn parse(bytes: &[u8]) -> Result<Self, DecodeError> {
+        Self::parse_with(bytes, &SessionContext::default())
+    }
+
+    /// Parse with the size limit of `context`.
+    pub fn parse_with(bytes: &[u8], context: &SessionContext) -> Result<Self, DecodeError> {
+        validate_message(bytes, BGP_UPDATE, MIN_UPDATE_LEN, context.max_message_len())?;
+
+        let mut r = Reader::new(&bytes[BGP_HEADER_LEN..], BGP_HEADER_LEN, RFC_UPDATE);
+
+        let withdrawn_len = r.u16("withdrawn routes length")? as usize;
+        let mut withdrawn = r.sub(withdrawn_len, "withdrawn routes")?;
+        let withdrawn_routes = parse_prefixes(&mut withdrawn, 1)?;
+
+        let attrs_len = r.u16("total path attribute length")? as usize;
+        let mut attrs = r.sub(attrs_len, "path attributes")?;
+        let mut path_attributes = Vec::new();
+        while !attrs.is_empty() {
+            path_attributes.push(PathAttribute::read(&mut attrs)?);
+        }
+
+        let nlri = parse_prefixes(&mut r, 1)?;
+
+        Ok(Update { withdrawn_routes, path_attributes, nlri })
+    }
+
+    /// Parse a hex dump; whitespace, `:` and `-` separators are ignored.
+    pub fn from_hex(hex_input: &str) -> Result<Self, DecodeError> {
+        Self::parse(&parse_hex(hex_input)?)
+    }
+
+    /// Octets taken by the withdrawn routes field.
+    pub fn withdrawn_routes_length(&self) -> usize {
+        self.withdrawn_routes.iter().map(Prefix::encoded_len).sum()
+    }
+
+    /// Octets taken by the path attributes field.
+    pub fn total_path_attribute_length(&self) -> usize {
+        self.path_attributes.iter().map(PathAttribute::encoded_len).sum()
+    }
+
+    /// Size of the message on the wire, header included.
+    pub fn encoded_len(&self) -> usize {
+        let nlri: usize = self.nlri.iter().map(Prefix::encoded_len).sum();
+        MIN_UPDATE_LEN + self.withdrawn_routes_length() + self.total_path_attribute_length() + nlri
+    }
+
+    /// True for an UPDATE with no routes or attributes (End-of-RIB, RFC 4724).
+    pub fn is_end_of_rib(&self) -> bool {
+        self.withdrawn_routes.is_empty() && self.path_attributes.is_empty() && self.nlri.is_empty()
+    }
+
+    /// Human-readable description, one line per prefix or attribute.
+    pub fn summary(&self) -> Vec<String> {
+        if self.is_end_of_rib() {
+            return vec!["End-of-RIB marker (IPv4 unicast)".to_string()];
+        }
+
+        let mut lines = Vec::new();
+        for prefix in &self.withdrawn_routes {
+            lines.push(format!("Withdrawn: {}", prefix));
+        }
+        for attribute in &self.path_attributes {
+            lines.push(attribute.to_string());
+        }
+        for prefix in &self.nlri {
+            lines.push(format!("NLRI: {}", prefix));
+        }
+        lines.extend(extended_length_note(self.encoded_len()));
+        lines
+    }
+}
+
+impl Prefix {
+    fn encoded_len(&self) -> usize {
+        1 + (self.length as usize).div_ceil(8)
+    }
+}
+
+impl PathAttribute {
+    /// Decode one attribute TLV; malformed values are kept as `Malformed`,
+    /// only an attribute running past its container fails the whole read.
+    pub(crate) fn read(r: &mut Reader) -> Result<Self, DecodeError> {
+        let flags = r.u8("attribute flags")?;
+        let type_code = r.u8("attribute type code")?;
+        let len = if flags & ATTR_FLAG_EXTENDED_LENGTH != 0 {
+            r.u16("attribute length")? as usize
+        } else {
+            r.u8("attribute length")? as usize
+        };
+        let mut value_reader = r.sub(len, "attribute value")?;
+        let value = decode_value(type_code, &mut value_reader.clone())
+            .unwrap_or_else(|e| AttributeValue::Malformed { reason: e.to_string() });
+
+        Ok(PathAttribute { flags, type_code, data: value_reader.rest().to_vec(), value })
+    }
+
+    fn encoded_len(&self) -> usize {
+        let header = if self.flags & ATTR_FLAG_EXTENDED_LENGTH != 0 { 4 } else { 3 };
+        header + self.data.len()
+    }
+
+    /// Flag names, e.g. "Optional, Transitive".
+    pub fn flags_description(&self) -> String {
+        let mut names = vec![if self.flags & ATTR_FLAG_OPTIONAL != 0 { "Optional" } else { "Well-known" }];
+        if self.flags & ATTR_FLAG_TRANSITIVE != 0 {
+            names.push("Transitive");
+        }
+        if self.flags & ATTR_FLAG_PARTIAL != 0 {
+            names.push("Partial");
+        }
+        if self.flags & ATTR_FLAG_EXTENDED_LENGTH != 0 {
+            names.push("Extended Length");
+        }
+        names.join(", ")
+    }
+
+    /// The decoded value without the attribute name.
+    pub fn interpretation(&self) -> String {
+        describe_value(&self.value, &self.data)
+    }
+}
+
+fn decode_value(type_code: u8, r: &mut Reader) -> Result<AttributeValue, DecodeError> {
+    let value = match type_code {
+        1 => AttributeValue::Origin { origin: r.u8("ORIGIN")? },
+        2 => {
+            let base = r.offset();
+            let (segments, four_octet) = parse_as_path_any(r.rest(), base)?;
+            AttributeValue::AsPath { segments, four_octet }
+        },
+        3 => AttributeValue::NextHop { next_hop: Ipv4Addr::from(r.u32("NEXT_HOP")?) },
+        4 => AttributeValue::MultiExitDisc { med: r.u32("MULTI_EXIT_DISC")? },
+        5 => AttributeValue::LocalPref { local_pref: r.u32("LOCAL_PREF")? },
+        6 => AttributeValue::AtomicAggregate,
+        7 => {
+            // 2-octet AS unless the attribute is long enough for 4 (RFC 6793)
+            let asn = if r.remaining() == 8 { r.u32("AGGREGATOR AS")? } else { r.u16("AGGREGATOR AS")? as u32 };
+            AttributeValue::Aggregator { asn, address: Ipv4Addr::from(r.u32("AGGREGATOR address")?) }
+        },
+        8 => {
+            let mut communities = Vec::new();
+            while !r.is_empty() {
+                communities.push(r.u32("COMMUNITIES")?);
+            }
+            AttributeValue::Communities { communities }
+        },
+        9 => AttributeValue::OriginatorId { originator_id: Ipv4Addr::from(r.u32("ORIGINATOR_ID")?) },
+        10 => {
+            let mut cluster_ids = Vec::new();
+            while !r.is_empty() {
+                cluster_ids.push(Ipv4Addr::from(r.u32("CLUSTER_LIST")?));
+            }
+            AttributeValue::ClusterList { cluster_ids }
+        },
+        14 => {
+            let afi = r.u16("MP_REACH_NLRI AFI")?;
+            let safi = r.u8("MP_REACH_NLRI SAFI")?;
+            let nh_len = r.u8("MP_REACH_NLRI next hop length")? as usize;
+            let next_hops = parse_next_hops(r.take(nh_len, "MP_REACH_NLRI next hop")?);
+            r.u8("MP_REACH_NLRI reserved octet")?;
+            let nlri = if is_ip_family(afi, safi) { parse_prefixes(r, afi)? } else { Vec::new() };
+            AttributeValue::MpReachNlri { afi, safi, next_hops, nlri }
+        },
+        15 => {
+            let afi = r.u16("MP_UNREACH_NLRI AFI")?;
+            let safi = r.u8("MP_UNREACH_NLRI SAFI")?;
+            let withdrawn_routes = if is_ip_family(afi, safi) { parse_prefixes(r, afi)? } else { Vec::new() };
+            AttributeValue::MpUnreachNlri { afi, safi, withdrawn_routes }
+        },
+        16 => {
+            let mut communities = Vec::new();
+            while !r.is_empty() {
+                let b = r.take(8, "EXTENDED COMMUNITIES")?;
+                communities.push(u64::from_be_bytes([b[0], b[1], b[2], b[3], b[4], b[5], b[6], b[7]]));
+            }
+            AttributeValue::ExtendedCommunities { communities }
+        },
+        17 => AttributeValue::As4Path { segments: parse_as_path(r, 4)? },
+        18 => AttributeValue::As4Aggregator {
+            asn: r.u32("AS4_AGGREGATOR AS")?,
+            address: Ipv4Addr::from(r.u32("AS4_AGGREGATOR address")?),
+        },
+        32 => {
+            let mut communities = Vec::new();
+            while !r.is_empty() {
+                communities.push(LargeCommunity {
+                    global_admin: r.u32("LARGE_COMMUNITY")?,
+                    local_data_1: r.u32("LARGE_COMMUNITY")?,
+                    local_data_2: r.u32("LARGE_COMMUNITY")?,
+                });
+            }
+            AttributeValue::LargeCommunities { communities }
+        },
+        35 => AttributeValue::OnlyToCustomer { asn: r.u32("OTC")? },
+        _ => return Ok(AttributeValue::Unknown),
+    };
+
+    r.finish(attribute_name(type_code))?;
+    Ok(value)
+}
+
+fn is_ip_family(afi: u16, safi: u8) -> bool {
+    matches!(afi, 1 | 2) && matches!(safi, 1 | 2)
+}
+
+// Next hop field of MP_REACH_NLRI: IPv4, IPv6, IPv6 global + link-local,
+// or any of those prefixed by an 8-octet VPN route distinguisher
+fn parse_next_hops(bytes: &[u8]) -> Vec<IpAddr> {
+    let v6 = |b: &[u8]| IpAddr::V6(Ipv6Addr::from(<[u8; 16]>::try_from(b).unwrap()));
+    let v4 = |b: &[u8]| IpAddr::V4(Ipv4Addr::from(<[u8; 4]>::try_from(b).unwrap()));
+    match bytes.len() {
+        4 => vec![v4(bytes)],
+        12 => vec![v4(&bytes[8..])],
+        16 => vec![v6(bytes)],
+        24 => vec![v6(&bytes[8..])],
+        32 => vec![v6(&bytes[..16]), v6(&bytes[16..])],
+        48 => vec![v6(&bytes[8..24]), v6(&bytes[32..])],
+        _ => Vec::new(),
+    }
+}
+
+// Prefixes encoded as (length, significant octets), RFC 4271 section 4.3
+pub(crate) fn parse_prefixes(r: &mut Reader, afi: u16) -> Result<Vec<Prefix>, DecodeError> {
+    let max_len: u8 = if afi == 2 { 128 } else { 32 };
+    let mut prefixes = Vec::new();
+
+    while !r.is_empty() {
+        let offset = r.offset();
+        let length = r.u8("prefix length")?;
+        if length > max_len {
+            return Err(DecodeError::InvalidPrefixLength { offset, actual: length, max: max_len });
+        }
+        let octets = r.take((length as usize).div_ceil(8), "prefix")?;
+
+        let address = if afi == 2 {
+            let mut buf = [0u8; 16];
+            buf[..octets.len()].copy_from_slice(octets);
+            IpAddr::V6(Ipv6Addr::from(buf))
+        } else {
+            let mut buf = [0u8; 4];
+            buf[..octets.len()].copy_from_slice(octets);
+            IpAddr::V4(Ipv4Addr::from(buf))
+        };
+        prefixes.push(Prefix { address, length });
+    }
+
+    Ok(prefixes)
+}
+
+// AS_PATH whose AS size is unknown: 4 octets unless only 2 octets parse
+fn parse_as_path_any(bytes: &[u8], base: usize) -> Result<(Vec<AsPathSegment>, bool), DecodeError> {
+    match parse_as_path(&mut Reader::new(bytes, base, RFC_UPDATE), 4) {
+        Ok(segments) => Ok((segments, true)),
+        Err(four_octet_error) => parse_as_path(&mut Reader::new(bytes, base, RFC_UPDATE), 2)
+            .map(|segments| (segments, false))
+            .map_err(|_| four_octet_error),
+    }
+}
+
+pub(crate) fn parse_as_path(r: &mut Reader, as_size: usize) -> Result<Vec<AsPathSegment>, DecodeError> {
+    let mut segments = Vec::new();
+    while !r.is_empty() {
+        let segment_type = r.u8("AS_PATH segment type")?;
+        let count = r.u8("AS_PATH segment length")? as usize;
+        let mut asns = Vec::with_capacity(count);
+        for _ in 0..count {
+            asns.push(if as_size == 4 { r.u32("AS_PATH segment")? } else { r.u16("AS_PATH segment")? as u32 });
+        }
+        segments.push(AsPathSegment { segment_type, asns });
+    }
+    Ok(segments)
+}
+
+/// Describe the Data field of an UPDATE Message Error NOTIFICATION
+/// (RFC 4271 section 6.3): usually the offending attribute as a TLV.
+pub(crate) fn describe_notification_data(subcode: u8, data: &[u8]) -> String {
+    let mut r = Reader::new(data, MIN_NOTIFICATION_LEN, RFC_UPDATE_ERROR);
+    match subcode {
+        10 => {
+            // Invalid Network Field: the prefix, IPv6 if too long for IPv4
+            let prefixes = parse_prefixes(&mut r.clone(), 1)
+                .or_else(|_| parse_prefixes(&mut r, 2));
+            match prefixes {
+                Ok(prefixes) => format!("Invalid network field: {}", format_prefixes(&prefixes)),
+                Err(e) => format!("Invalid network field, undecodable: {}", e),
+            }
+        },
+        11 => {
+            // Either the whole AS_PATH attribute or just its segments
+            let mut tlv = r.clone();
+            match PathAttribute::read(&mut tlv) {
+                Ok(attribute) if attribute.type_code == 2 && tlv.is_empty() => {
+                    format!("Malformed AS_PATH: {}", attribute.interpretation())
+                },
+                _ => match parse_as_path_any(data, MIN_NOTIFICATION_LEN) {
+                    Ok((segments, _)) => format!("Malformed AS_PATH: {}", format_as_path(&segments)),
+                    Err(e) => format!("Malformed AS_PATH, undecodable: {}", e),
+                },
+            }
+        },
+        _ => {
+            let label = match subcode {
+                2 => "Unrecognized well-known attribute",
+                4 => "Attribute flags error",
+                5 => "Attribute length error",
+                6 => "Invalid ORIGIN attribute",
+                8 => "Invalid NEXT_HOP attribute",
+                _ => "Optional attribute error",
+            };
+            match PathAttribute::read(&mut r) {
+                Ok(attribute) => {
+                    let mut text = format!(
+                        "{}: {} [flags 0x{:02x}: {}]",
+                        label, attribute, attribute.flags, attribute.flags_description()
+                    );
+                    if !r.is_empty() {
+                        text.push_str(&format!(", {} trailing bytes", r.remaining()));
+                    }
+                    text
+                },
+                Err(e) => format!("{}, truncated attribute: {}", label, e),
+            }
+        },
+    }
+}
+
+/// IANA name of a path attribute type code.
+pub fn attribute_name(type_code: u8) -> &'static str {
+    match type_code {
+        1 => "ORIGIN",
+        2 => "AS_PATH",
+        3 => "NEXT_HOP",
+        4 => "MULTI_EXIT_DISC",
+        5 => "LOCAL_PREF",
+        6 => "ATOMIC_AGGREGATE",
+        7 => "AGGREGATOR",
+        8 => "COMMUNITIES",
+        9 => "ORIGINATOR_ID",
+        10 => "CLUSTER_LIST",
+        14 => "MP_REACH_NLRI",
+        15 => "MP_UNREACH_NLRI",
+        16 => "EXTENDED COMMUNITIES",
+        17 => "AS4_PATH",
+        18 => "AS4_AGGREGATOR",
+        22 => "PMSI_TUNNEL",
+        23 => "Tunnel Encapsulation",
+        25 => "IPv6 Address Specific Extended Community",
+        26 => "AIGP",
+        29 => "BGP-LS Attribute",
+        32 => "LARGE_COMMUNITY",
+        33 => "BGPsec_Path",
+        35 => "OTC",
+        40 => "BGP Prefix-SID",
+        128 => "ATTR_SET",
+        _ => "Unknown",
+    }
+}
+
+fn origin_name(origin: u8) -> &'static str {
+    match origin {
+        0 => "IGP",
+        1 => "EGP",
+        2 => "INCOMPLETE",
+        _ => "invalid",
+    }
+}
+
+fn community_name(community: u32) -> String {
+    match community {
+        0xFFFF0000 => "GRACEFUL_SHUTDOWN".to_string(),
+        0xFFFF029A => "BLACKHOLE".to_string(),
+        0xFFFFFF01 => "NO_EXPORT".to_string(),
+        0xFFFFFF02 => "NO_ADVERTISE".to_string(),
+        0xFFFFFF03 => "NO_EXPORT_SUBCONFED".to_string(),
+        0xFFFFFF04 => "NOPEER".to_string(),
+        c => format!("{}:{}", c >> 16, c & 0xffff),
+    }
+}
+
+pub(crate) fn format_as_path(segments: &[AsPathSegment]) -> String {
+    segments.iter().map(|segment| {
+        let asns: Vec<String> = segment.asns.iter().map(u32::to_string).collect();
+        let asns = asns.join(" ");
+        match segment.segment_type {
+            1 => format!("{{{}}}", asns),
+            3 => format!("({})", asns),
+            4 => format!("[{}]", asns),
+            _ => asns,
+        }
+    }).collect::<Vec<_>>().join(" ")
+}
+
+fn format_prefixes(prefixes: &[Prefix]) -> String {
+    prefixes.iter().map(Prefix::to_string).collect::<Vec<_>>().join(", ")
+}
+
+fn describe_value(value: &AttributeValue, data: &[u8]) -> String {
+    match value {
+        AttributeValue::Origin { origin } => format!("{} ({})", origin_name(*origin), origin),
+        AttributeValue::AsPath { segments, four_octet } => {
+            let path = format_as_path(segments);
+            let path = if path.is_empty() { "(empty)".to_string() } else { path };
+            if *four_octet { path } else { format!("{} (2-octet AS)", path) }
+        },
+        AttributeValue::NextHop { next_hop } => next_hop.to_string(),
+        AttributeValue::MultiExitDisc { med } => med.to_string(),
+        AttributeValue::LocalPref { local_pref } => local_pref.to_string(),
+        AttributeValue::AtomicAggregate => "set".to_string(),
+        AttributeValue::Aggregator { asn, address } | AttributeValue::As4Aggregator { asn, address } => {
+            format!("AS{} {}", asn, address)
+        },
+        AttributeValue::Communities { communities } => {
+            communities.iter().map(|c| community_name(*c)).collect::<Vec<_>>().join(" ")
+        },
+        AttributeValue::OriginatorId { originator_id } => originator_id.to_string(),
+        AttributeValue::ClusterList { cluster_ids } => {
+            cluster_ids.iter().map(Ipv4Addr::to_string).collect::<Vec<_>>().join(" ")
+        },
+        AttributeValue::MpReachNlri { afi, safi, next_hops, nlri } => {
+            let hops: Vec<String> = next_hops.iter().map(IpAddr::to_string).collect();
+            let mut text = format!("{} via {}", family_name(*afi, *safi), hops.join(", "));
+            if !nlri.is_empty() {
+                text.push_str(&format!(": {}", format_prefixes(nlri)));
+            }
+            text
+        },
+        AttributeValue::MpUnreachNlri { afi, safi, withdrawn_routes } if withdrawn_routes.is_empty() => {
+            format!("{} (End-of-RIB or non-IP NLRI)", family_name(*afi, *safi))
+        },
+        AttributeValue::MpUnreachNlri { afi, safi, withdrawn_routes } => {
+            format!("{}: {}", family_name(*afi, *safi), format_prefixes(withdrawn_routes))
+        },
+        AttributeValue::ExtendedCommunities { communities } => {
+            communities.iter().map(|c| format!("{:016x}", c)).collect::<Vec<_>>().join(" ")
+        },
+        AttributeValue::As4Path { segments } => format_as_path(segments),
+        AttributeValue::LargeCommunities { communities } => communities.iter()
+            .map(|c| format!("{}:{}:{}", c.global_admin, c.local_data_1, c.local_data_2))
+            .collect::<Vec<_>>()
+            .join(" "),
+        AttributeValue::OnlyToCustomer { asn } => format!("AS{}", asn),
+        AttributeValue::Unknown => format!("{} bytes: {}", data.len(), to_hex(data)),
+        AttributeValue::Malformed { reason } => format!("malformed ({}): {}", reason, to_hex(data)),
+    }
+}
+
+impl fmt::Display for Prefix {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}", self.address, self.length)
+    }
+}
+
+impl fmt::Display for PathAttribute {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.value {
+            AttributeValue::Unknown | AttributeValue::Malformed { .. } => {
+                write!(f, "{} ({}): {}", attribute_name(self.type_code), self.type_code, self.interpretation())
+            },
+            _ => write!(f, "{}: {}", attribute_name(self.type_code), self.interpretation()),
+        }
+    }
+}
+
+impl From<&Update> for UpdateDecodeResponse {
+    fn from(update: &Update) -> Self {
+        UpdateDecodeResponse {
+            withdrawn_routes_length: update.withdrawn_routes_length(),
+            withdrawn_routes: update.withdrawn_routes.iter().map(Prefix::to_string).collect(),
+            total_path_attribute_length: update.total_path_attribute_length(),
+            path_attributes: update.path_attributes.iter().map(PathAttributeResponse::from).collect(),
+            nlri: update.nlri.iter().map(Prefix::to_string).collect(),
+            notes: extended_length_note(update.encoded_len()).into_iter().collect(),
+        }
+    }
+}
+
+impl From<&PathAttribute> for PathAttributeResponse {
+    fn from(attribute: &PathAttribute) -> Self {
+        PathAttributeResponse {
+            flags: attribute.flags,
+            flags_description: attribute.flags_description(),
+            type_code: attribute.type_code,
+            type_name: attribute_name(attribute.type_code).to_string(),
+            length: attribute.data.len(),
+            data_hex: to_hex(&attribute.data),
+            interpretation: attribute.interpretation(),
+            value: attribute.value.clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Withdraws 10.1.0.0/16; announces 192.0.2.0/24 with ORIGIN IGP,
+    // AS_PATH 65001 65002, NEXT_HOP 198.51.100.1, COMMUNITIES 65001:100 NO_EXPORT
+    const UPDATE: &str = "ffffffffffffffffffffffffffffffff 0041 02 \
+                          0003 100a01 \
+                          0023 400101 00 \
+                          40020a 0202 0000fde9 0000fdea \
+                          400304 c6336401 \
+                          c00808 fde90064 ffffff01 \
+                          18c00002";
+
+    #[test]
+    fn test_parse_update() {
+        let update = Update::from_hex(UPDATE).unwrap();
+        assert_eq!(update.withdrawn_routes[0].to_string(), "10.1.0.0/16");
+        assert_eq!(update.nlri[0].to_string(), "192.0.2.0/24");
+        assert_eq!(update.withdrawn_routes_length(), 3);
+        assert_eq!(update.total_path_attribute_length(), 35);
+
+        let summary = update.summary();
+        assert_eq!(summary, vec![
+            "Withdrawn: 10.1.0.0/16",
+            "ORIGIN: IGP (0)",
+            "AS_PATH: 65001 65002",
+            "NEXT_HOP: 198.51.100.1",
+            "COMMUNITIES: 65001:100 NO_EXPORT",
+            "NLRI: 192.0.2.0/24",
+        ]);
+
+        let response = UpdateDecodeResponse::from(&update);
+        assert_eq!(response.path_attributes[4 - 1].flags_description, "Optional, Transitive");
+    }
+
+    #[test]
+    fn test_mp_reach_and_malformed_attribute() {
+        // MP_REACH_NLRI IPv6 unicast 2001:db8::/32 via 2001:db8::1, plus an
+        // ORIGIN with a 2-byte value that must be reported, not rejected
+        let hex = "ffffffffffffffffffffffffffffffff 003a 02 0000 0023 \
+                   40010200 00 \
+                   900e 001a 0002 01 10 20010db8000000000000000000000001 00 20 20010db8";
+        let update = Update::from_hex(hex).unwrap();
+
+        assert!(matches!(update.path_attributes[0].value, AttributeValue::Malformed { .. }));
+        assert_eq!(
+            update.path_attributes[1].to_string(),
+            "MP_REACH_NLRI: IPv6 unicast via 2001:db8::1: 2001:db8::/32"
+        );
+    }
+
+    #[test]
+    fn test_update_framing_errors() {
+        let end_of_rib = Update::from_hex("ffffffffffffffffffffffffffffffff 0017 02 0000 0000").unwrap();
+        assert!(end_of_rib.is_end_of_rib());
+
+        // Path attribute length overruns the message
+        let err = Update::from_hex("ffffffffffffffffffffffffffffffff 0017 02 0000 0005").unwrap_err();
+        assert_eq!(err.code(), "truncated");
+        assert_eq!(err.offset(), Some(23));
+
+        // /33 is not an IPv4 prefix
+        let err = Update::from_hex("ffffffffffffffffffffffffffffffff 001c 02 0000 0000 21c0000201").unwrap_err();
+        assert_eq!(err.code(), "invalid_prefix_length");
+    }
+
+    #[test]
+    fn test_update_error_data() {
+        // Attribute Flags Error: ORIGIN sent as optional
+        assert_eq!(
+            describe_notification_data(4, &[0xc0, 0x01, 0x01, 0x00]),
+            "Attribute flags error: ORIGIN: IGP (0) [flags 0xc0: Optional, Transitive]"
+        );
+        // Attribute Length Error: NEXT_HOP of 3 octets
+        assert!(describe_notification_data(5, &[0x40, 0x03, 0x03, 0x0a, 0x00, 0x00]).contains("malformed"));
+
+        assert_eq!(describe_notification_data(10, &[0x18, 0xc0, 0x00, 0x02]), "Invalid network field: 192.0.2.0/24");
+        assert_eq!(describe_notification_data(10, &[0x30, 0x20, 0x01, 0x0d, 0xb8, 0x00, 0x00]), "Invalid network field: 2001:db8::/48");
+
+        // Malformed AS_PATH, as the full attribute or the bare segments
+        let segments = [0x02, 0x02, 0x00, 0x00, 0xfd, 0xe9, 0x00, 0x00, 0xfd, 0xea];
+        assert_eq!(describe_notification_data(11, &segments), "Malformed AS_PATH: 65001 65002");
+        let mut attribute = vec![0x40, 0x02, segments.len() as u8];
+        attribute.extend_from_slice(&segments);
+        assert_eq!(describe_notification_data(11, &attribute), "Malformed AS_PATH: 65001 65002");
+    }
+}
@@ -0,0 +1,332 @@
+use wasm_bindgen::prelude::*;
+
+use crate::{
+    expand_template, find_template, message_budget, parse_hex_bounded, to_hex, BgpCeaseSubcode,
+    BmpResponse, BmpStream, Capture, CaptureResponse, CaptureTime, DecodeError, DecodeResponse, EncodeError,
+    EncodeRequest, EncodeResponse, Keepalive, LenientShutdown, MessageStream, MrtCeases,
+    MrtResponse, MrtSession, Notification, Open,
+    OpenDecodeResponse, RouteRefresh, RouteRefreshDecodeResponse, SessionContext,
+    ShutdownCommunication, StreamDecodeResponse, SyntheticSession, TemplateResponse,
+    TemplateValues, UniversalDecodeResponse, UniversalEncodeRequest, Update, UpdateDecodeResponse,
+    BGP_CEASE_ADMIN_RESET, BGP_CEASE_ADMIN_SHUTDOWN, BGP_HEADER_LEN, DEFAULT_ELLIPSIS, ERROR_CODES,
+    MAX_SHUTDOWN_MSG_LEN, MAX_STREAM_LEN, TEMPLATES,
+};
+
+// Errors surface in JS as `Error` objects with `code`, `offset`, `expected`,
+// `actual` and `rfc` properties so the frontend can localize or highlight them
+fn js_error(
+    message: &str,
+    code: &str,
+    offset: Option<usize>,
+    expected: Option<usize>,
+    actual: Option<usize>,
+    rfc: Option<&str>,
+) -> JsValue {
+    let error = js_sys::Error::new(message);
+    let set = |key: &str, value: JsValue| {
+        let _ = js_sys::Reflect::set(&error, &JsValue::from_str(key), &value);
+    };
+
+    set("code", JsValue::from_str(code));
+    if let Some(offset) = offset {
+        set("offset", JsValue::from_f64(offset as f64));
+    }
+    if let Some(expected) = expected {
+        set("expected", JsValue::from_f64(expected as f64));
+    }
+    if let Some(actual) = actual {
+        set("actual", JsValue::from_f64(actual as f64));
+    }
+    if let Some(rfc) = rfc {
+        set("rfc", JsValue::from_str(rfc));
+    }
+
+    error.into()
+}
+
+impl From<DecodeError> for JsValue {
+    fn from(error: DecodeError) -> Self {
+        js_error(
+            &error.to_string(), error.code(), error.offset(),
+            error.expected(), error.actual(), error.rfc(),
+        )
+    }
+}
+
+impl From<EncodeError> for JsValue {
+    fn from(error: EncodeError) -> Self {
+        js_error(
+            &error.to_string(), error.code(), error.offset(),
+            error.expected(), error.actual(), error.rfc(),
+        )
+    }
+}
+
+// Optional trailing `extended_message` argument: JS callers may omit it
+fn session(extended_message: Option<bool>) -> SessionContext {
+    SessionContext::new(extended_message.unwrap_or(false))
+}
+
+fn to_js<T: serde::Serialize>(value: &T) -> Result<JsValue, JsValue> {
+    serde_wasm_bindgen::to_value(value)
+        .map_err(|e| JsValue::from_str(&format!("Serialization error: {}", e)))
+}
+
+// Main encode function (backward compatible)
+#[wasm_bindgen]
+pub fn encode_shutdown_message(request: JsValue) -> Result<JsValue, JsValue> {
+    let req: EncodeRequest = serde_wasm_bindgen::from_value(request)
+        .map_err(|e| JsValue::from_str(&format!("Invalid request: {}", e)))?;
+
+    let subcode = BgpCeaseSubcode::from_u8(req.subcode)
+        .ok_or(EncodeError::InvalidShutdownSubcode { actual: req.subcode })?;
+
+    let mut communication = ShutdownCommunication::new(subcode, req.message);
+    let mut warnings = Vec::new();
+    if req.truncate {
+        let ellipsis = req.ellipsis.as_deref().unwrap_or(DEFAULT_ELLIPSIS);
+        warnings.extend(communication.truncate(req.compatibility.max_len(), ellipsis)?);
+    }
+    warnings.extend(communication.check_compatibility(req.compatibility)?);
+    let message_bytes = communication.message.len();
+    let message = communication.to_bytes()?;
+
+    to_js(&EncodeResponse {
+        hex: to_hex(&message),
+        total_bytes: message.len(),
+        message_bytes,
+        warnings,
+    })
+}
+
+// Uncompressed MRT dump contents (a Uint8Array); every Cease notification in
+// its BGP4MP records, with sender, receiver and time
+#[wasm_bindgen]
+pub fn decode_mrt(bytes: &[u8], extended_message: Option<bool>) -> Result<JsValue, JsValue> {
+    let scan = MrtCeases::parse_with(bytes, &session(extended_message))?;
+    to_js(&MrtResponse::from(&scan))
+}
+
+// Recorded BMP feed (RFC 7854): every message with its peer, and the
+// NOTIFICATION of each Peer Down decoded as decode_universal_notification does
+#[wasm_bindgen]
+pub fn decode_bmp(bytes: &[u8], extended_message: Option<bool>) -> Result<JsValue, JsValue> {
+    let context = session(extended_message);
+    let stream = BmpStream::parse_with(bytes, &context)?;
+    to_js(&BmpResponse::with_context(&stream, &context))
+}
+
+// Encoded hex (one message or several back to back) as a pcap of a made-up
+// TCP session, returned as a Uint8Array. `session` may set `client`,
+// `server` (e.g. "192.0.2.2:179") and `start`; without a start time the
+// capture is stamped with the current time
+#[wasm_bindgen]
+pub fn encode_capture(hex_input: &str, session: JsValue) -> Result<Vec<u8>, JsValue> {
+    let mut session: SyntheticSession = if session.is_undefined() || session.is_null() {
+        SyntheticSession::default()
+    } else {
+        serde_wasm_bindgen::from_value(session)
+            .map_err(|e| JsValue::from_str(&format!("Invalid session: {}", e)))?
+    };
+    if session.start == CaptureTime::default() {
+        let millis = js_sys::Date::now() as u64;
+        session.start = CaptureTime::new(millis / 1_000, (millis % 1_000) as u32 * 1_000_000);
+    }
+
+    Ok(session.to_pcap(&parse_hex_bounded(hex_input, MAX_STREAM_LEN)?)?)
+}
+
+// Encoded hex as an MRT dump of BGP4MP_MESSAGE_AS4 records, one per message,
+// returned as a Uint8Array. `session` may set `peer_as`, `peer_ip`,
+// `local_as`, `local_ip`, `interface_index` and `start`; without a start
+// time the dump is stamped with the current second
+#[wasm_bindgen]
+pub fn encode_mrt(hex_input: &str, session: JsValue) -> Result<Vec<u8>, JsValue> {
+    let mut session: MrtSession = if session.is_undefined() || session.is_null() {
+        MrtSession::default()
+    } else {
+        serde_wasm_bindgen::from_value(session)
+            .map_err(|e| JsValue::from_str(&format!("Invalid session: {}", e)))?
+    };
+    if session.start == CaptureTime::default() {
+        session.start = CaptureTime::new(js_sys::Date::now() as u64 / 1_000, 0);
+    }
+
+    Ok(session.to_mrt(&parse_hex_bounded(hex_input, MAX_STREAM_LEN)?)?)
+}
+
+// Byte budget for a shutdown message as typed, so the UI need not count
+// UTF-8 itself; `max_bytes` defaults to the RFC 9003 limit
+#[wasm_bindgen]
+pub fn shutdown_message_budget(text: &str, max_bytes: Option<usize>) -> Result<JsValue, JsValue> {
+    to_js(&message_budget(text, max_bytes.unwrap_or(MAX_SHUTDOWN_MSG_LEN)))
+}
+
+// Main decode function (backward compatible)
+#[wasm_bindgen]
+pub fn decode_shutdown_message(hex_input: &str) -> Result<JsValue, JsValue> {
+    to_js(&DecodeResponse::from(&LenientShutdown::from_hex(hex_input)?))
+}
+
+// Universal encoder with proper validation
+#[wasm_bindgen]
+pub fn encode_universal_notification(request: JsValue, extended_message: Option<bool>) -> Result<JsValue, JsValue> {
+    let req: UniversalEncodeRequest = serde_wasm_bindgen::from_value(request)
+        .map_err(|e| JsValue::from_str(&format!("Invalid request: {}", e)))?;
+
+    encode_notification(&Notification::new(req.error_code, req.subcode, req.data), &session(extended_message))
+}
+
+#[wasm_bindgen]
+pub fn decode_universal_notification(hex_input: &str, extended_message: Option<bool>) -> Result<JsValue, JsValue> {
+    let context = session(extended_message);
+    let notification = Notification::parse_with(&context.parse_hex(hex_input)?, &context)?;
+    to_js(&UniversalDecodeResponse::with_context(&notification, &context))
+}
+
+// RFC-compliant data field creator with security hardening
+#[wasm_bindgen]
+pub fn create_notification_with_data(
+    error_code: u8,
+    subcode: u8,
+    data_type: &str,
+    data_value: &str,
+    extended_message: Option<bool>,
+) -> Result<JsValue, JsValue> {
+    let notification = Notification::with_data(error_code, subcode, data_type, data_value)?;
+    encode_notification(&notification, &session(extended_message))
+}
+
+fn encode_notification(notification: &Notification, context: &SessionContext) -> Result<JsValue, JsValue> {
+    let bytes = notification.to_bytes_with(context)?;
+
+    to_js(&EncodeResponse {
+        hex: to_hex(&bytes),
+        total_bytes: bytes.len(),
+        message_bytes: notification.data.len(),
+        warnings: Vec::new(),
+    })
+}
+
+// OPEN decoder: header fields, optional parameters and capabilities
+#[wasm_bindgen]
+pub fn decode_open_message(hex_input: &str) -> Result<JsValue, JsValue> {
+    let open = Open::from_hex(hex_input)?;
+    to_js(&OpenDecodeResponse::from(&open))
+}
+
+// UPDATE decoder: withdrawn routes, path attributes and NLRI
+#[wasm_bindgen]
+pub fn decode_update_message(hex_input: &str, extended_message: Option<bool>) -> Result<JsValue, JsValue> {
+    let context = session(extended_message);
+    let update = Update::parse_with(&context.parse_hex(hex_input)?, &context)?;
+    to_js(&UpdateDecodeResponse::from(&update))
+}
+
+// Concatenated messages, e.g. a TCP payload; each entry has its byte offset
+#[wasm_bindgen]
+pub fn decode_message_stream(hex_input: &str, extended_message: Option<bool>) -> Result<JsValue, JsValue> {
+    let context = session(extended_message);
+    let stream = MessageStream::from_hex_with(hex_input, &context)?;
+    to_js(&StreamDecodeResponse::with_context(&stream, &context))
+}
+
+// pcap or pcapng file contents (a Uint8Array); every BGP message on every
+// TCP port 179 flow, with its capture time and endpoints
+#[wasm_bindgen]
+pub fn decode_capture(bytes: &[u8], extended_message: Option<bool>) -> Result<JsValue, JsValue> {
+    let context = session(extended_message);
+    let capture = Capture::parse_with(bytes, &context)?;
+    to_js(&CaptureResponse::with_context(&capture, &context))
+}
+
+// OPEN encoder; the request has the same shape as the decoded `open` object
+#[wasm_bindgen]
+pub fn encode_open_message(request: JsValue) -> Result<JsValue, JsValue> {
+    let open: Open = serde_wasm_bindgen::from_value(request)
+        .map_err(|e| JsValue::from_str(&format!("Invalid request: {}", e)))?;
+    let bytes = open.to_bytes()?;
+
+    to_js(&EncodeResponse {
+        hex: to_hex(&bytes),
+        total_bytes: bytes.len(),
+        message_bytes: bytes.len() - BGP_HEADER_LEN,
+        warnings: Vec::new(),
+    })
+}
+
+#[wasm_bindgen]
+pub fn encode_keepalive_message() -> Result<JsValue, JsValue> {
+    let bytes = Keepalive.to_bytes()?;
+
+    to_js(&EncodeResponse {
+        hex: to_hex(&bytes),
+        total_bytes: bytes.len(),
+        message_bytes: 0,
+        warnings: Vec::new(),
+    })
+}
+
+// KEEPALIVE decoder; succeeds only for a well-formed 19-byte message
+#[wasm_bindgen]
+pub fn decode_keepalive_message(hex_input: &str) -> Result<JsValue, JsValue> {
+    Keepalive::from_hex(hex_input)?;
+    to_js(&vec!["KEEPALIVE: header only, 19 bytes"])
+}
+
+// ROUTE-REFRESH encoder; subtype 0 is a normal refresh, 1/2 are BoRR/EoRR
+#[wasm_bindgen]
+pub fn encode_route_refresh_message(afi: u16, safi: u8, subtype: u8) -> Result<JsValue, JsValue> {
+    let bytes = RouteRefresh::new(afi, safi, subtype).to_bytes()?;
+
+    to_js(&EncodeResponse {
+        hex: to_hex(&bytes),
+        total_bytes: bytes.len(),
+        message_bytes: bytes.len() - BGP_HEADER_LEN,
+        warnings: Vec::new(),
+    })
+}
+
+#[wasm_bindgen]
+pub fn decode_route_refresh_message(hex_input: &str, extended_message: Option<bool>) -> Result<JsValue, JsValue> {
+    let context = session(extended_message);
+    let route_refresh = RouteRefresh::parse_with(&context.parse_hex(hex_input)?, &context)?;
+    to_js(&RouteRefreshDecodeResponse::from(&route_refresh))
+}
+
+#[wasm_bindgen]
+pub fn get_subcodes() -> JsValue {
+    let subcodes = vec![
+        (BGP_CEASE_ADMIN_SHUTDOWN, "Administrative Shutdown"),
+        (BGP_CEASE_ADMIN_RESET, "Administrative Reset"),
+    ];
+    serde_wasm_bindgen::to_value(&subcodes).unwrap()
+}
+
+/// Built-in shutdown message templates: name, description, subcode and template.
+#[wasm_bindgen]
+pub fn get_shutdown_templates() -> Result<JsValue, JsValue> {
+    to_js(&TEMPLATES)
+}
+
+/// Expand a built-in template (by name) or an ad-hoc one with `values`
+/// (`asn`, `nick`, `ticket`, `until`, `contact`).
+#[wasm_bindgen]
+pub fn expand_shutdown_template(template: &str, values: JsValue) -> Result<JsValue, JsValue> {
+    let values: TemplateValues = serde_wasm_bindgen::from_value(values)
+        .map_err(|e| JsValue::from_str(&format!("Invalid template values: {}", e)))?;
+    let builtin = find_template(template);
+    let message = expand_template(builtin.map_or(template, |builtin| builtin.template), &values)?;
+
+    to_js(&TemplateResponse {
+        message_bytes: message.len(),
+        message,
+        subcode: builtin.map(|builtin| builtin.subcode),
+    })
+}
+
+/// The IANA error code registry: code, name, rfc, deprecated and subcodes.
+#[wasm_bindgen]
+pub fn get_registry() -> Result<JsValue, JsValue> {
+    to_js(&ERROR_CODES)
+}